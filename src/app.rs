@@ -1,11 +1,21 @@
+use crate::calibrate::Calibration;
 use crate::colors::Palette;
-use crate::dsp::{SpectrogramBuilder, WindowType};
+use crate::dsp::{self, Multitaper, Scalloping, SpectrogramBuilder, SpectrumUnits, Weighting, WindowType};
+use crate::cqt::CqtSpec;
 use crate::export;
-use crate::input::{self, AudioInputKind};
+use crate::gen::GenSpec;
+use crate::input::{self, AudioInputKind, ChannelSelect, InputStats, PipelineOptions, RawFormat, RawSpec, TimeRange, Transport};
+use crate::measure::{self, Distortion, MeasureSpec};
+use crate::pitch::{self, Pitch, PitchTracker};
+use crate::resample::ResampleQuality;
+use crate::trace::{TraceKind, TraceSet, Traces};
+use crate::view::{self, FreqMap};
 use anyhow::{anyhow, Result};
-use crossbeam_channel::{bounded, Receiver};
+use crossbeam_channel::{bounded, unbounded, Receiver, Sender};
 use std::collections::VecDeque;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 #[derive(Copy, Clone, Debug)]
@@ -40,6 +50,8 @@ pub enum AnimationStyle {
     Horizontal,
     Waterfall,
     Spectrum,
+    /// Fractional-octave band levels as bars
+    Octave,
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -49,22 +61,75 @@ pub enum FreqScale {
     Mel,
 }
 
+/// How frequency axes and readouts are labelled
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FreqAxis {
+    Hz,
+    /// Note names (A4, C#5, ...), with cents in the hover and tuner readouts
+    Notes,
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum BinsMode {
     All,
     Peaks,
 }
 
+/// A measurement marker: a frequency pinned to one analysis row, or
+/// following the newest row on screen (a live marker, as on a bench
+/// analyzer's trace).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Marker {
+    /// Center-relative frequency in Hz
+    pub freq: f32,
+    /// Stream row number (0 = first row since the history started), or
+    /// `None` for a live marker
+    pub row: Option<usize>,
+}
+
+/// Most markers that can be placed at once (M1..M4).
+const MAX_MARKERS: usize = 4;
+
 #[derive(Copy, Clone, Debug)]
 pub struct Settings {
     pub fft_size: usize,
     pub hop_size: usize,
     pub window_len: usize,
+    /// Analysis rate. With `native_rate` this is only a placeholder until
+    /// the source reports its rate (see `App::sync_sample_rate`).
     pub sample_rate: u32,
+    /// Analyze at the source's own rate instead of resampling to `sample_rate`
+    pub native_rate: bool,
+    pub resample_quality: ResampleQuality,
+    pub channel: ChannelSelect,
+    /// Treat the input as complex baseband I/Q; rows then span -fs/2..fs/2
+    pub iq: bool,
+    /// Raw I/Q sample format (`None` = stereo WAV or guess from the extension)
+    pub iq_format: Option<RawFormat>,
+    /// Sample rate of a raw I/Q file, which has no header to carry it
+    pub iq_rate: Option<u32>,
+    /// Layout of headerless PCM input (stdin, named pipes, raw files); `None`
+    /// means the source describes itself
+    pub raw: Option<RawSpec>,
+    /// UDP datagrams carry a little-endian u32 sequence number before the PCM
+    pub udp_seq: bool,
+    /// Running without the TUI (`render`): network sources end at the first
+    /// drop or idle gap instead of waiting, and endless generators are
+    /// bounded to `HEADLESS_GEN_LEN`.
+    pub headless: bool,
+    /// Offset added to displayed frequencies (the tuner frequency for I/Q)
+    pub center_freq: f64,
+    /// Part of the source to analyze (--start/--duration/--end)
+    pub range: TimeRange,
+    /// Seconds jumped by the transport seek keys
+    pub seek_step: f64,
     pub db_floor: f32,
     pub db_ceiling: f32,
     pub fps: u64,
     pub zoom: f32,
+    /// Initial frequency band edges in displayed Hz (--fmin/--fmax);
+    /// either may be left open
+    pub band: (Option<f64>, Option<f64>),
     pub palette: ColorPalette,
     pub style: AnimationStyle,
     pub detailed: bool,
@@ -72,6 +137,9 @@ pub struct Settings {
     pub history: usize,
     pub render_mode: RenderMode,
     pub freq_scale: FreqScale,
+    pub freq_axis: FreqAxis,
+    /// A4 reference in Hz for note names
+    pub a4: f32,
     pub alpha: u8,
     pub pre_emphasis: Option<f32>,
     pub overview: bool,
@@ -79,9 +147,41 @@ pub struct Settings {
     pub clamp_floor: bool,
     pub normalize: bool,
     pub window: WindowType,
+    /// Tone (dBFS) or noise-density (dBFS/Hz, dBFS/√Hz) scaling
+    pub units: SpectrumUnits,
+    /// Offset to dB SPL or dBV, applied by the analyzer to every value
+    pub calibration: Option<Calibration>,
+    /// A/C/Z frequency weighting, applied by the analyzer
+    pub weighting: Weighting,
+    /// Bands per octave in the octave view: 1, 3 or 6
+    pub octave_fraction: u32,
+    /// Spectrum-view traces shown over the live frame
+    pub traces: TraceSet,
+    /// Rows in the moving RMS average (0 = every row since the reset)
+    pub avg_frames: usize,
+    /// Exponential average time constant in seconds
+    pub trace_tau: f64,
+    /// Distortion measurements track the tone near this frequency
+    /// (`None`: the strongest tone)
+    pub fundamental: Option<f64>,
+    /// Highest harmonic order in THD
+    pub harmonics: usize,
+    /// Run a YIN f0 tracker next to each channel's analyzer
+    pub pitch: bool,
+    /// f0 search range in Hz
+    pub pitch_range: (f32, f32),
+    /// `--transform cqt`: constant-Q rows instead of FFT bins
+    pub cqt: Option<CqtSpec>,
+    /// `--transform reassigned`: FFT bins with time-frequency reassignment
+    pub reassign: bool,
+    /// `--multitaper`: DPSS tapers in place of `window`
+    pub multitaper: Option<Multitaper>,
     pub bins_mode: BinsMode,
 }
 
+/// Seconds rendered from an endless `gen:` source when there is no TUI.
+const HEADLESS_GEN_LEN: f64 = 10.0;
+
 pub struct App {
     pub settings: Settings,
     pub running: bool,
@@ -89,11 +189,18 @@ pub struct App {
     pub palette: Palette,
     pub style: AnimationStyle,
     pub zoom: f32,
+    /// Lower edge of a panned frequency band (see `FreqMap::fmin`)
+    pub fmin: Option<f32>,
     pub db_floor: f32,
     pub db_ceiling: f32,
-    pub buffer: VecDeque<Vec<f32>>, // normalized 0..1 rows (bins)
+    /// History per analyzed channel (one entry unless `--channel all`);
+    /// rows are dB values per bin, newest at index 0.
+    pub buffers: Vec<VecDeque<Vec<f32>>>,
+    /// Display label per entry of `buffers`
+    pub channel_labels: Vec<String>,
     pub max_history: usize,
-    pub spectrogram_rx: Receiver<Vec<f32>>,
+    /// One row per analyzed channel for each analysis frame
+    pub spectrogram_rx: Receiver<Vec<Vec<f32>>>,
     pub input_desc: String,
     pub detailed: bool,
     pub fullscreen: bool,
@@ -113,8 +220,59 @@ pub struct App {
     pub hover: Option<(u16, u16)>,
     pub hover_at: Instant,
     pub pipeline_error: std::sync::Arc<std::sync::Mutex<Option<String>>>,
+    /// Effective analysis rate published by the input thread (0 = not yet known)
+    pub analysis_rate: Arc<AtomicU32>,
+    /// Counters published by the input thread (network health)
+    pub input_stats: Arc<InputStats>,
+    /// "tcp", "udp" or "udp+seq" for network sources
+    pub net_proto: Option<&'static str>,
     /// Sticky input error shown in the status bar (unlike status_msg, no expiry)
     pub error: Option<String>,
+    /// Source time of the first analysis row for file inputs (or any source
+    /// given a --start/--duration window); time axes then show absolute
+    /// source time. `None` for live streams, labelled in seconds ago.
+    pub time_origin: Option<f64>,
+    /// Commands to the decoder thread; only file inputs in the TUI have one
+    pub transport: Option<Sender<Transport>>,
+    /// Playback speed last requested through the transport
+    pub speed: f64,
+    /// Looped region in source seconds
+    pub loop_region: Option<(f64, f64)>,
+    /// Loop start picked with the loop key, waiting for its end
+    pub loop_mark: Option<f64>,
+    /// Scrollback while paused: rows between the newest row in history and
+    /// the newest row on screen (0 = live edge)
+    pub scroll: usize,
+    /// History rows one pane shows at once, recorded by the renderer; sets
+    /// the page size and how far back scrolling can go
+    pub view_rows: usize,
+    /// Placed markers, M1 first; M1 is the reference in delta mode
+    pub markers: Vec<Marker>,
+    /// Index into `markers` that peak search and placement act on
+    pub active_marker: usize,
+    /// Report markers after M1 as Δt/Δf/ΔdB relative to M1
+    pub delta_markers: bool,
+    /// Pane rectangles from the last draw, for mapping mouse clicks
+    pub panes: Vec<ratatui::layout::Rect>,
+    /// Scalloping loss of the analysis window, for sub-bin peak readouts
+    pub scalloping: Scalloping,
+    /// Bands per octave in the octave view: 1, 3 or 6
+    pub octave_fraction: u32,
+    /// Noise bandwidth of the analysis window in bins, for band levels
+    pub enbw_bins: f32,
+    /// Trace accumulators per analyzed channel, fed every row
+    pub traces: Vec<Traces>,
+    /// Traces shown in the spectrum view
+    pub trace_set: TraceSet,
+    /// Show the distortion panel in the details overlay
+    pub show_distortion: bool,
+    /// One f0 estimate per analysis frame, sent just ahead of its rows
+    pub pitch_rx: Receiver<Vec<Pitch>>,
+    /// f0 per channel, aligned with `buffers` (newest first); empty unless
+    /// `settings.pitch`
+    pub pitches: Vec<VecDeque<Pitch>>,
+    /// Draw the f0 contour over the waterfall and horizontal views
+    pub show_pitch: bool,
 }
 
 impl App {
@@ -128,55 +286,209 @@ impl App {
         // bar, hover readout, PNG time axis, render summaries) agrees on the
         // effective values, not the raw CLI ones.
         let mut settings = settings;
+        if let Some(cqt) = settings.cqt {
+            // The constant-Q frame is the lowest bin's window at this rate
+            settings.window_len = cqt.frame_len(settings.sample_rate);
+            settings.fft_size = settings.window_len.next_power_of_two();
+        }
         settings.fft_size = settings.fft_size.max(16);
         settings.window_len = settings.window_len.min(settings.fft_size).max(16);
         settings.hop_size = settings.hop_size.min(settings.window_len).max(1);
 
+        if settings.range.duration.is_some_and(|d| d <= 0.0) {
+            return Err(anyhow!("empty time range: --end must be after --start and --duration above 0"));
+        }
+        let source = input.clone();
         let input_kind = if input.to_lowercase() == "mic" {
             if cfg!(feature = "mic") && !no_mic {
                 AudioInputKind::Mic { device: mic_device }
             } else {
                 return Err(anyhow!("Mic feature not enabled at compile time. Rebuild with --features mic or provide a WAV file."));
             }
+        } else if input == "-" || input.eq_ignore_ascii_case("stdin") {
+            AudioInputKind::Raw { path: None, spec: settings.raw.unwrap_or(RawSpec::DEFAULT) }
+        } else if let Some(addr) = input.strip_prefix("tcp://") {
+            let spec = settings.raw.unwrap_or(RawSpec::DEFAULT);
+            AudioInputKind::Tcp { addr: addr.to_string(), spec, persistent: !settings.headless }
+        } else if let Some(addr) = input.strip_prefix("udp://") {
+            let spec = settings.raw.unwrap_or(RawSpec::DEFAULT);
+            AudioInputKind::Udp { addr: addr.to_string(), spec, seq: settings.udp_seq, persistent: !settings.headless }
+        } else if input.starts_with("gen:") {
+            let mut spec = GenSpec::parse(&input)?;
+            if settings.headless && spec.len.is_none() && settings.range.duration.is_none() {
+                spec.len = Some(settings.range.start + HEADLESS_GEN_LEN);
+            }
+            AudioInputKind::Gen { spec, rate: settings.sample_rate }
+        } else if let Some(spec) = settings.raw {
+            AudioInputKind::Raw { path: Some(PathBuf::from(input)), spec }
+        } else if settings.iq {
+            // One complex analysis of both channels, whatever --channel said
+            settings.channel = ChannelSelect::Iq;
+            input::iq_source(PathBuf::from(input), settings.iq_format, settings.iq_rate)?
         } else {
             AudioInputKind::Wav(PathBuf::from(input))
         };
 
-        let (spectrogram_tx, spectrogram_rx) = bounded::<Vec<f32>>(64);
+        let time_origin = match &input_kind {
+            AudioInputKind::Mic { .. } | AudioInputKind::Tcp { .. } | AudioInputKind::Udp { .. }
+                if !settings.range.is_full() =>
+            {
+                return Err(anyhow!("--start/--duration/--end need a file, pipe or generator input"));
+            }
+            AudioInputKind::Wav(_) | AudioInputKind::Iq { .. } | AudioInputKind::Raw { path: Some(_), .. } => {
+                Some(settings.range.start)
+            }
+            _ if !settings.range.is_full() => Some(settings.range.start),
+            _ => None,
+        };
+
+        let (spectrogram_tx, spectrogram_rx) = bounded::<Vec<Vec<f32>>>(64);
+        // Unbounded: the row channel's bound already paces the analysis
+        let (pitch_tx, pitch_rx) = unbounded::<Vec<Pitch>>();
+        // Render plays the range once; the TUI keeps file decoders alive for
+        // the transport keys
+        let (transport, control) = match &input_kind {
+            AudioInputKind::Wav(_) | AudioInputKind::Iq { .. } | AudioInputKind::Raw { path: Some(_), .. }
+                if !settings.headless =>
+            {
+                let (tx, rx) = unbounded();
+                (Some(tx), Some(rx))
+            }
+            _ => (None, None),
+        };
 
         // Start input + DSP thread
-        let sr = settings.sample_rate;
         let fft_size = settings.fft_size;
         let frame_len = settings.window_len;
         let hop = settings.hop_size;
         let floor = settings.db_floor;
         let alpha = settings.alpha;
         let pre_emph = settings.pre_emphasis;
+        let pitch = settings.pitch && !settings.iq;
+        let pitch_range = settings.pitch_range;
         let input_desc = match &input_kind {
             AudioInputKind::Mic { device } => match &device {
                 Some(d) => format!("Microphone: {d}"),
                 None => "Microphone (default)".to_string(),
             },
             AudioInputKind::Wav(p) => format!("WAV: {}", p.display()),
+            AudioInputKind::Iq { path, .. } => format!("I/Q: {}", path.display()),
+            AudioInputKind::Raw { path, spec } => format!(
+                "raw {} {}ch: {}",
+                spec.format.name(),
+                spec.channels,
+                path.as_ref().map(|p| p.display().to_string()).unwrap_or_else(|| "stdin".to_string())
+            ),
+            AudioInputKind::Gen { rate, .. } => format!("generator: {source} @ {rate} Hz"),
+            AudioInputKind::Tcp { addr, spec, .. } => {
+                format!("tcp://{addr} (raw {} {}ch)", spec.format.name(), spec.channels)
+            }
+            AudioInputKind::Udp { addr, spec, .. } => {
+                format!("udp://{addr} (raw {} {}ch)", spec.format.name(), spec.channels)
+            }
+        };
+        let input_desc = match settings.channel {
+            ChannelSelect::Mix | ChannelSelect::Iq => input_desc,
+            ChannelSelect::All => format!("{input_desc} [all channels]"),
+            sel => format!("{input_desc} [{}]", sel.labels(1)[0]),
         };
 
         let pipeline_error = std::sync::Arc::new(std::sync::Mutex::new(None::<String>));
         let thread_error = pipeline_error.clone();
         let thread_kind = input_kind.clone();
+        let analysis_rate = Arc::new(AtomicU32::new(0));
+        let build_rate = analysis_rate.clone();
+        let pitch_rate = analysis_rate.clone();
+        let input_stats = Arc::new(InputStats::default());
+        let net_proto = match &input_kind {
+            AudioInputKind::Tcp { .. } => Some("tcp"),
+            AudioInputKind::Udp { seq, .. } => Some(if *seq { "udp+seq" } else { "udp" }),
+            _ => None,
+        };
+        let opts = PipelineOptions {
+            target_sr: if settings.native_rate { None } else { Some(settings.sample_rate) },
+            quality: settings.resample_quality,
+            realtime: settings.realtime,
+            channel: settings.channel,
+            rate: analysis_rate.clone(),
+            stats: input_stats.clone(),
+            range: settings.range,
+            control,
+        };
         std::thread::spawn(move || {
-            let mut spec = SpectrogramBuilder::new(fft_size, frame_len, hop)
-                .window(settings.window)
-                .db_floor(floor)
-                .alpha(alpha)
-                .pre_emphasis(pre_emph)
-                .clamp_floor(settings.clamp_floor)
-                .normalize(settings.normalize)
-                .build();
+            let build = move || {
+                SpectrogramBuilder::new(fft_size, frame_len, hop)
+                    .window(settings.window)
+                    .units(settings.units)
+                    .offset_db(settings.calibration.map(|c| c.offset_db).unwrap_or(0.0))
+                    .weighting(settings.weighting)
+                    .sample_rate(build_rate.load(Ordering::Acquire))
+                    .db_floor(floor)
+                    .alpha(alpha)
+                    .pre_emphasis(pre_emph)
+                    .clamp_floor(settings.clamp_floor)
+                    .normalize(settings.normalize)
+                    .cqt(settings.cqt)
+                    .reassign(settings.reassign)
+                    .multitaper(settings.multitaper)
+                    .build()
+            };
+            // Same framing as the analyzers, whose frame length a constant-Q
+            // layout sets
+            let tracker = move |frame_len| PitchTracker::new(frame_len, hop, pitch_rate.load(Ordering::Acquire), pitch_range);
+            // One analyzer (and f0 tracker) per channel, created once the
+            // channel count is known
+            let mut specs = Vec::new();
+            let mut trackers = Vec::new();
+            // f0 estimates waiting for their rows, which reassignment delays
+            let mut f0_queue: Vec<VecDeque<Pitch>> = Vec::new();
+            let iq = settings.channel == ChannelSelect::Iq;
             if let Err(e) =
-                input::run_input_pipeline(thread_kind, sr, settings.realtime, move |samples| {
-                    let rows = spec.process_samples(samples);
-                    for row in rows {
-                        let _ = spectrogram_tx.send(row);
+                input::run_input_pipeline(thread_kind, &opts, move |blocks| {
+                    if blocks.is_empty() {
+                        // Seek: fresh analyzers, and tell the UI to start over
+                        specs.clear();
+                        trackers.clear();
+                        f0_queue.clear();
+                        let _ = spectrogram_tx.send(Vec::new());
+                        return;
+                    }
+                    if iq {
+                        // I and Q blocks feed a single complex analysis
+                        if specs.is_empty() {
+                            specs.push(build());
+                        }
+                        for row in specs[0].process_complex(blocks[0], blocks[1]) {
+                            let _ = spectrogram_tx.send(vec![row]);
+                        }
+                        return;
+                    }
+                    while specs.len() < blocks.len() {
+                        specs.push(build());
+                    }
+                    // Equal-length blocks yield the same number of rows per
+                    // channel; regroup them by analysis frame.
+                    let mut per_chan: Vec<_> = specs
+                        .iter_mut()
+                        .zip(blocks)
+                        .map(|(spec, samples)| spec.process_samples(samples).into_iter())
+                        .collect();
+                    if pitch {
+                        while trackers.len() < blocks.len() {
+                            trackers.push(tracker(specs[0].frame_len()));
+                            f0_queue.push(VecDeque::new());
+                        }
+                        for ((t, q), samples) in trackers.iter_mut().zip(&mut f0_queue).zip(blocks) {
+                            q.extend(t.process_samples(samples));
+                        }
+                    }
+                    while let Some(frame) = per_chan.iter_mut().map(Iterator::next).collect::<Option<Vec<_>>>() {
+                        if pitch {
+                            // Ahead of the rows, so push_rows finds it waiting
+                            let f0 = f0_queue.iter_mut().map(|q| q.pop_front().unwrap_or_default()).collect();
+                            let _ = pitch_tx.send(f0);
+                        }
+                        let _ = spectrogram_tx.send(frame);
                     }
                 })
             {
@@ -186,20 +498,25 @@ impl App {
             }
         });
 
-        Ok(Self {
+        // Peak and band readouts follow the analyzer's windows
+        let tapers = dsp::analysis_windows(settings.window, settings.multitaper, settings.window_len);
+        let mut app = Self {
             settings,
             running: true,
             paused: false,
             palette: settings.palette.palette(),
             style: settings.style,
             zoom: settings.zoom,
+            fmin: None,
             db_floor: settings.db_floor,
             db_ceiling: settings.db_ceiling,
-            buffer: VecDeque::new(),
+            buffers: vec![VecDeque::new()],
+            channel_labels: settings.channel.labels(1),
             max_history: settings.history.max(16),
             spectrogram_rx,
             input_desc,
-            detailed: settings.detailed,
+            // The distortion panel lives in the details overlay
+            detailed: settings.detailed || settings.fundamental.is_some(),
             fullscreen: settings.fullscreen,
             export_png_path: None,
             export_csv_path: None,
@@ -217,39 +534,553 @@ impl App {
             hover: None,
             hover_at: Instant::now(),
             pipeline_error,
+            analysis_rate,
+            input_stats,
+            net_proto,
             error: None,
-        })
+            time_origin,
+            transport,
+            speed: 1.0,
+            loop_region: None,
+            loop_mark: None,
+            scroll: 0,
+            view_rows: 1,
+            markers: Vec::new(),
+            active_marker: 0,
+            delta_markers: false,
+            panes: Vec::new(),
+            scalloping: match settings.cqt {
+                // Every constant-Q bin is a Hann window one bin spacing wide
+                Some(_) => Scalloping::new(WindowType::Hann, 64, 64),
+                None => Scalloping::from_tapers(&tapers, settings.fft_size),
+            },
+            octave_fraction: settings.octave_fraction,
+            enbw_bins: dsp::enbw_bins(&tapers, settings.fft_size),
+            traces: vec![Traces::new(settings.avg_frames)],
+            trace_set: settings.traces,
+            show_distortion: settings.fundamental.is_some(),
+            pitch_rx,
+            pitches: Vec::new(),
+            show_pitch: settings.pitch,
+        };
+        app.apply_band_setting();
+        Ok(app)
     }
 
     pub fn tick_rate(&self) -> Duration {
         Duration::from_millis(1000 / self.settings.fps.max(1))
     }
 
-    pub fn push_row(&mut self, row: Vec<f32>) {
+    /// Why a headless run got no rows: the decoder's error if it recorded
+    /// one. The decoder thread drops its sender (ending a `recv` loop) just
+    /// before it records the failure, so give it a moment to land.
+    pub fn no_frames_error(&self, input: &str) -> anyhow::Error {
+        let mut cause = None;
+        for _ in 0..50 {
+            if let Some(e) = self.pipeline_error.lock().unwrap().take() {
+                cause = Some(e);
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        let cause = cause.map(|e| format!(": {e}")).unwrap_or_default();
+        anyhow::anyhow!("no audio frames decoded from {input}{cause}")
+    }
+
+    /// Adopt the rate the input thread actually analyzes at. It is published
+    /// before the first row is sent, so call this after receiving rows.
+    pub fn sync_sample_rate(&mut self) {
+        let sr = self.analysis_rate.load(Ordering::Acquire);
+        if sr > 0 && sr != self.settings.sample_rate {
+            self.settings.sample_rate = sr;
+            if let Some(cqt) = self.settings.cqt {
+                self.settings.window_len = cqt.frame_len(sr);
+                self.settings.fft_size = self.settings.window_len.next_power_of_two();
+            }
+            // A --fmin/--fmax band was sized against the placeholder rate
+            self.apply_band_setting();
+        }
+    }
+
+    /// A4 reference when axes are labelled in notes, else `None`.
+    pub fn note_a4(&self) -> Option<f64> {
+        (self.settings.freq_axis == FreqAxis::Notes).then_some(self.settings.a4 as f64)
+    }
+
+    /// Frequency mapping for the current view, shared by the terminal
+    /// renderer, hover readout and PNG export.
+    pub fn freq_map(&self) -> FreqMap {
+        FreqMap {
+            sample_rate: self.settings.sample_rate,
+            zoom: self.zoom.max(1.0),
+            fmin: self.fmin,
+            scale: self.freq_scale,
+            two_sided: self.settings.iq,
+            center_freq: self.settings.center_freq,
+            cqt: self.settings.cqt,
+        }
+    }
+
+    /// Seconds of source time between consecutive history rows.
+    pub fn row_secs(&self) -> f64 {
+        self.settings.hop_size as f64 / self.settings.sample_rate.max(1) as f64
+    }
+
+    /// Source time of the newest history row, for inputs with a time origin.
+    pub fn newest_time(&self) -> Option<f64> {
+        self.time_origin.map(|t0| t0 + self.total_rows.saturating_sub(1) as f64 * self.row_secs())
+    }
+
+    /// Source time of the newest row; live inputs without a time origin
+    /// count from the start of the stream.
+    pub fn stream_time(&self) -> f64 {
+        self.newest_time().unwrap_or(self.total_rows.saturating_sub(1) as f64 * self.row_secs())
+    }
+
+    /// Hover readout time for a point `sec_ago` seconds before the newest
+    /// row: file time for files, stream time plus age for live inputs
+    /// ("1:23.45 (-3.20s)").
+    pub fn hover_time(&self, sec_ago: f64) -> String {
+        match self.time_origin {
+            Some(_) => self.time_label(sec_ago),
+            None => format!("{} (-{sec_ago:.2}s)", view::format_clock(self.stream_time() - sec_ago)),
+        }
+    }
+
+    /// Time label for a point `sec_ago` seconds before the newest row:
+    /// absolute source time ("12:31.20") when known, else "-1.23s".
+    pub fn time_label(&self, sec_ago: f64) -> String {
+        match self.newest_time() {
+            Some(t) => view::format_clock(t - sec_ago),
+            None => format!("-{sec_ago:.2}s"),
+        }
+    }
+
+    /// Connection health of a network source for the status bar, e.g.
+    /// "tcp: connected, 2 reconnects" or "udp: 14 packets lost".
+    pub fn net_status(&self) -> Option<String> {
+        let proto = self.net_proto?;
+        let st = &self.input_stats;
+        let connected = st.connected.load(Ordering::Relaxed);
+        Some(match proto {
+            "tcp" => {
+                let state = if connected { "connected" } else { "connecting" };
+                match st.reconnects.load(Ordering::Relaxed) {
+                    0 => format!("tcp: {state}"),
+                    1 => format!("tcp: {state}, 1 reconnect"),
+                    n => format!("tcp: {state}, {n} reconnects"),
+                }
+            }
+            "udp+seq" => format!("udp: {} packets lost", st.lost_packets.load(Ordering::Relaxed)),
+            _ => format!("udp: {}", if connected { "receiving" } else { "waiting" }),
+        })
+    }
+
+    /// History of the first analyzed channel; the time base every pane shares.
+    pub fn buffer(&self) -> &VecDeque<Vec<f32>> {
+        &self.buffers[0]
+    }
+
+    /// Push one analysis frame (a row per analyzed channel). An empty frame
+    /// marks a transport seek: history restarts at the new file position.
+    pub fn push_rows(&mut self, rows: Vec<Vec<f32>>) {
+        if rows.is_empty() {
+            self.clear();
+            self.total_rows = 0;
+            self.time_origin = Some(self.input_stats.resumed_at());
+            return;
+        }
+        self.total_rows = self.total_rows.saturating_add(1);
+        if rows.len() != self.buffers.len() {
+            // First frame tells us how many channels the source has
+            self.buffers.resize_with(rows.len().max(1), VecDeque::new);
+            self.traces.resize_with(rows.len().max(1), || Traces::new(self.settings.avg_frames));
+            self.channel_labels = self.settings.channel.labels(rows.len());
+        }
+        let coeff = self.trace_coeff();
+        for (traces, row) in self.traces.iter_mut().zip(&rows) {
+            traces.push(row, coeff);
+        }
+        if self.settings.pitch {
+            let f0 = self.pitch_rx.try_recv().unwrap_or_default();
+            self.pitches.resize_with(rows.len(), VecDeque::new);
+            for (i, track) in self.pitches.iter_mut().enumerate() {
+                track.push_front(f0.get(i).copied().unwrap_or_default());
+                track.truncate(self.max_history);
+            }
+        }
         // Store full-resolution rows; zoom is applied at render time so it is
         // reversible and history stays uniform when zoom changes mid-run.
-        self.buffer.push_front(row);
-        while self.buffer.len() > self.max_history {
-            self.buffer.pop_back();
+        for (buffer, row) in self.buffers.iter_mut().zip(rows) {
+            buffer.push_front(row);
+            while buffer.len() > self.max_history {
+                buffer.pop_back();
+            }
         }
     }
 
     pub fn clear(&mut self) {
-        self.buffer.clear();
+        for buffer in &mut self.buffers {
+            buffer.clear();
+        }
+        for track in &mut self.pitches {
+            track.clear();
+        }
+        self.scroll = 0;
+        self.reset_traces();
+        // Pinned rows are gone; keep the frequencies as live markers
+        for m in &mut self.markers {
+            m.row = None;
+        }
+    }
+
+    /// Restart averaging and holds from the next row.
+    pub fn reset_traces(&mut self) {
+        for t in &mut self.traces {
+            t.reset();
+        }
+    }
+
+    /// Weight of a new row in the exponential average: 1 - exp(-Δt/τ).
+    fn trace_coeff(&self) -> f64 {
+        1.0 - (-self.row_secs() / self.settings.trace_tau.max(1e-6)).exp()
+    }
+
+    /// Step the spectrum-view traces through off, avg, exp, max, min and
+    /// avg+max+min.
+    pub fn cycle_traces(&mut self) {
+        self.trace_set = self.trace_set.next();
+        self.set_status(format!("traces: {}", self.trace_set.label()));
+    }
+
+    /// "avg(16)", "exp(1.00s)", "max", "min": a trace's legend entry.
+    pub fn trace_label(&self, kind: TraceKind) -> String {
+        match kind {
+            TraceKind::Avg if self.settings.avg_frames == 0 => "avg(all)".to_string(),
+            TraceKind::Avg => format!("avg({})", self.settings.avg_frames),
+            TraceKind::Exp => format!("exp({:.2}s)", self.settings.trace_tau),
+            k => k.name().to_string(),
+        }
+    }
+
+    pub fn measure_spec(&self) -> MeasureSpec {
+        MeasureSpec::new(self.settings.fundamental, self.settings.harmonics, self.enbw_bins)
+    }
+
+    /// Analysis window as `--window` or `--multitaper` names it, e.g.
+    /// "hann" or "dpss:4:7"
+    pub fn window_name(&self) -> String {
+        match self.settings.multitaper {
+            Some(mt) => mt.name(),
+            None => self.settings.window.name(),
+        }
+    }
+
+    /// Rows hold plain FFT bin levels, as band sums and distortion
+    /// readings expect: not constant-Q bins, and not reassigned power.
+    fn fft_rows(&self) -> bool {
+        self.settings.cqt.is_none() && !self.settings.reassign
+    }
+
+    /// THD, THD+N, SINAD, ... of the displayed frame of `channel`, or
+    /// `None` for I/Q, weighted rows or when there is no tone. A/C weighting
+    /// scales each harmonic by a different gain, which skews every ratio.
+    pub fn distortion(&self, channel: usize) -> Option<Distortion> {
+        if self.settings.iq || !self.fft_rows() || self.settings.weighting != Weighting::Z {
+            return None;
+        }
+        let row = self.buffers.get(channel)?.get(self.scroll)?;
+        let hz_per_bin = self.settings.sample_rate as f64 / self.settings.fft_size as f64;
+        measure::analyze(row, hz_per_bin, &self.measure_spec(), &self.scalloping)
+    }
+
+    /// The distortion panel goes in the details overlay, so showing it
+    /// turns details on.
+    pub fn toggle_distortion(&mut self) {
+        if !self.fft_rows() {
+            self.set_status("distortion measures FFT bins (--transform stft)");
+            return;
+        }
+        if self.settings.weighting != Weighting::Z {
+            self.set_status("distortion needs unweighted levels (--weighting z)");
+            return;
+        }
+        self.show_distortion = !self.show_distortion;
+        if self.show_distortion {
+            self.detailed = true;
+        }
+        self.set_status(if self.show_distortion { "distortion panel on" } else { "distortion panel off" });
+    }
+
+    pub fn toggle_pitch(&mut self) {
+        if !self.settings.pitch {
+            self.set_status("pitch tracking is off (start with --pitch)");
+            return;
+        }
+        self.show_pitch = !self.show_pitch;
+        self.set_status(if self.show_pitch { "pitch contour on" } else { "pitch contour off" });
+    }
+
+    /// Median voiced f0 over history rows `r0..r1` (0 = newest) of `channel`.
+    pub fn pitch_over(&self, channel: usize, r0: usize, r1: usize) -> Option<f32> {
+        pitch::median_hz(self.pitches.get(channel)?, r0, r1)
+    }
+
+    /// Send a transport command, or explain why there is nowhere to send it.
+    fn send_transport(&mut self, cmd: Transport) -> bool {
+        let sent = self.transport.as_ref().is_some_and(|tx| tx.send(cmd).is_ok());
+        if !sent {
+            self.set_status("transport needs a file input");
+        }
+        sent
+    }
+
+    pub fn seek_by(&mut self, secs: f64) {
+        if self.send_transport(Transport::SeekBy(secs)) {
+            self.set_status(format!("seek {secs:+.0}s"));
+        }
+    }
+
+    /// Back to the start of the range (or loop).
+    pub fn restart(&mut self) {
+        if self.send_transport(Transport::Restart) {
+            self.set_status("restart");
+        }
+    }
+
+    /// Double (`up`) or halve the playback speed within 0.25x..8x.
+    pub fn step_speed(&mut self, up: bool) {
+        let speed = if up { self.speed * 2.0 } else { self.speed / 2.0 }.clamp(0.25, 8.0);
+        if self.send_transport(Transport::Speed(speed)) {
+            self.speed = speed;
+            self.realtime = true;
+            self.set_status(format!("speed {speed}x"));
+        }
+    }
+
+    /// Loop key: the first press marks the loop start at the newest row, the
+    /// second sets the end and starts looping, the third clears the loop.
+    pub fn loop_key(&mut self) {
+        if self.transport.is_none() {
+            self.set_status("transport needs a file input");
+            return;
+        }
+        let now = self.newest_time().unwrap_or(0.0);
+        if self.loop_region.is_some() {
+            self.loop_region = None;
+            self.loop_mark = None;
+            self.send_transport(Transport::Loop(None));
+            self.set_status("loop off");
+        } else if let Some(a) = self.loop_mark.take() {
+            let (a, b) = if now < a { (now, a) } else { (a, now) };
+            if b - a < self.row_secs() {
+                self.set_status("loop too short; mark the start again");
+                return;
+            }
+            self.loop_region = Some((a, b));
+            self.send_transport(Transport::Loop(Some((a, b))));
+            self.set_status(format!("loop {}..{}", view::format_clock(a), view::format_clock(b)));
+        } else {
+            self.loop_mark = Some(now);
+            self.set_status(format!("loop start {}; press l again at the end", view::format_clock(now)));
+        }
     }
 
+    /// Transport state for the status bar, e.g. "2x loop 1:05.00..1:07.50".
+    pub fn transport_status(&self) -> Option<String> {
+        self.transport.as_ref()?;
+        let mut s = if self.realtime { format!("{}x", self.speed) } else { "fast".to_string() };
+        if let Some((a, b)) = self.loop_region {
+            s += &format!(" loop {}..{}", view::format_clock(a), view::format_clock(b));
+        } else if let Some(a) = self.loop_mark {
+            s += &format!(" loop from {}", view::format_clock(a));
+        }
+        Some(s)
+    }
+
+    /// Resuming returns the view to the live edge.
     pub fn toggle_pause(&mut self) {
         self.paused = !self.paused;
+        if !self.paused {
+            self.scroll = 0;
+        }
+    }
+
+    /// History rows behind one terminal cell of the time axis, the step of
+    /// a single arrow-key scroll.
+    pub fn rows_per_cell(&self) -> usize {
+        match (self.style, self.render_mode) {
+            (AnimationStyle::Waterfall, RenderMode::Half | RenderMode::Quad) => 2,
+            _ => 1,
+        }
+    }
+
+    /// History index of the row a marker reads: its pinned row while that is
+    /// still in history, or the newest row on screen for a live marker.
+    pub fn marker_index(&self, m: &Marker) -> Option<usize> {
+        let idx = match m.row {
+            Some(row) => self.total_rows.checked_sub(1)?.checked_sub(row)?,
+            None => self.scroll,
+        };
+        (idx < self.buffer().len()).then_some(idx)
+    }
+
+    /// dB in the marker's bin (first channel), if its row is in history. On
+    /// a spectral peak this is the interpolated level, corrected for the
+    /// window's scalloping loss.
+    pub fn marker_value(&self, m: &Marker) -> Option<f32> {
+        let row = self.buffer().get(self.marker_index(m)?)?;
+        let bin = self.freq_map().freq_to_bin(m.freq, row.len()).round().max(0.0) as usize;
+        Some(view::interpolate_peak(row, bin, &self.scalloping).db).filter(|v| v.is_finite())
+    }
+
+    /// Stream row a marker reads.
+    fn marker_stream_row(&self, m: &Marker) -> usize {
+        m.row.unwrap_or_else(|| self.total_rows.saturating_sub(1 + self.scroll))
+    }
+
+    /// One readout per marker, shared by the TUI and the PNG legend:
+    /// "M1 4.0000kHz 1:23.45 -6.0dBFS" (or "live" for a live marker), and
+    /// in delta mode "Δ2 +100.0Hz +0.50s -3.2dB" relative to M1.
+    pub fn marker_readouts(&self) -> Vec<String> {
+        let fmap = self.freq_map();
+        let unit = self.unit_label();
+        let db = |v: Option<f32>, unit: &str| v.map(|v| format!("{v:+.1}{unit}")).unwrap_or_else(|| "---".to_string());
+        let reference = self.markers.first().copied();
+        self.markers
+            .iter()
+            .enumerate()
+            .map(|(i, m)| match reference {
+                Some(r) if self.delta_markers && i > 0 => {
+                    let df = (m.freq - r.freq) as f64;
+                    let df = if df >= 0.0 { format!("+{}", view::format_hz(df)) } else { view::format_hz(df) };
+                    let dt = (self.marker_stream_row(m) as f64 - self.marker_stream_row(&r) as f64) * self.row_secs();
+                    let dv = match (self.marker_value(m), self.marker_value(&r)) {
+                        (Some(a), Some(b)) => Some(a - b),
+                        _ => None,
+                    };
+                    format!("Δ{} {df} {dt:+.2}s {}", i + 1, db(dv, "dB"))
+                }
+                _ => {
+                    let t = match m.row {
+                        Some(row) => view::format_clock(self.time_origin.unwrap_or(0.0) + row as f64 * self.row_secs()),
+                        None => "live".to_string(),
+                    };
+                    format!("M{} {} {t} {}", i + 1, view::format_hz(fmap.absolute(m.freq)), db(self.marker_value(m), &unit))
+                }
+            })
+            .collect()
+    }
+
+    /// Place a marker at `freq` (center-relative Hz) on stream row `row`, or
+    /// a live marker when `None`. It becomes the active marker; once all
+    /// four are placed it replaces the active one.
+    pub fn place_marker(&mut self, freq: f32, row: Option<usize>) {
+        let m = Marker { freq, row };
+        if self.markers.len() < MAX_MARKERS {
+            self.markers.push(m);
+            self.active_marker = self.markers.len() - 1;
+        } else {
+            self.markers[self.active_marker] = m;
+        }
+    }
+
+    pub fn next_marker(&mut self) {
+        if !self.markers.is_empty() {
+            self.active_marker = (self.active_marker + 1) % self.markers.len();
+        }
+    }
+
+    pub fn clear_markers(&mut self) {
+        self.markers.clear();
+        self.active_marker = 0;
+    }
+
+    pub fn toggle_delta_markers(&mut self) {
+        self.delta_markers = !self.delta_markers;
+    }
+
+    /// Snap the active marker (placing a live M1 if there is none) to the
+    /// strongest bin of its row inside the displayed band.
+    pub fn peak_search(&mut self) {
+        if self.markers.is_empty() {
+            self.place_marker(0.0, None);
+        }
+        self.snap_marker(|row, lo, hi, _| Some(view::max_bin_in(row, lo, hi)));
+    }
+
+    /// Move the active marker to the next lower local peak in the band.
+    pub fn next_peak(&mut self) {
+        if self.markers.is_empty() {
+            self.set_status("no marker: k runs a peak search first");
+            return;
+        }
+        self.snap_marker(view::next_peak_below);
+    }
+
+    /// Move the active marker to the peak at the bin `find(row, lo, hi,
+    /// current bin)` picks within the displayed band of the marker's row,
+    /// interpolated between bins.
+    fn snap_marker(&mut self, find: impl Fn(&[f32], usize, usize, usize) -> Option<(usize, f32)>) {
+        let m = self.markers[self.active_marker];
+        let Some(idx) = self.marker_index(&m) else {
+            self.set_status("marker row is no longer in history");
+            return;
+        };
+        let row = &self.buffer()[idx];
+        let fmap = self.freq_map();
+        let (lo, hi) = fmap.visible_bin_range(row.len());
+        let from = fmap.freq_to_bin(m.freq, row.len()).round().max(0.0) as usize;
+        match find(row, lo, hi, from) {
+            Some((bin, _)) => {
+                let freq = fmap.bin_to_freq(view::interpolate_peak(row, bin, &self.scalloping).bin, row.len());
+                self.markers[self.active_marker].freq = freq;
+            }
+            None => self.set_status("no lower peak in the band"),
+        }
+    }
+
+    /// Furthest scroll that still fills a pane with history.
+    pub fn max_scroll(&self) -> usize {
+        self.buffer().len().saturating_sub(self.view_rows.max(1))
+    }
+
+    /// Pan through history by `rows` (positive = older). Scrolling pauses
+    /// ingestion so the rows on screen hold still.
+    pub fn scroll_by(&mut self, rows: isize) {
+        if self.max_scroll() == 0 {
+            self.set_status("all of history is on screen");
+            return;
+        }
+        if !self.paused {
+            self.paused = true;
+            self.set_status("paused for scrollback; p resumes live");
+        }
+        self.scroll = self.scroll.saturating_add_signed(rows).min(self.max_scroll());
     }
 
     pub fn toggle_style(&mut self) {
         self.style = match self.style {
             AnimationStyle::Horizontal => AnimationStyle::Waterfall,
             AnimationStyle::Waterfall => AnimationStyle::Spectrum,
-            AnimationStyle::Spectrum => AnimationStyle::Horizontal,
+            // Band levels sum FFT bins; constant-Q and reassigned rows skip
+            // the octave view
+            AnimationStyle::Spectrum if !self.fft_rows() => AnimationStyle::Horizontal,
+            AnimationStyle::Spectrum => AnimationStyle::Octave,
+            AnimationStyle::Octave => AnimationStyle::Horizontal,
         };
     }
 
+    /// Octave view resolution: 1/1 -> 1/3 -> 1/6 octave.
+    pub fn cycle_octave_fraction(&mut self) {
+        self.octave_fraction = match self.octave_fraction {
+            1 => 3,
+            3 => 6,
+            _ => 1,
+        };
+        self.set_status(format!("octave bands: 1/{}", self.octave_fraction));
+    }
+
     pub fn toggle_bins_mode(&mut self) {
         self.bins_mode = match self.bins_mode {
             BinsMode::All => BinsMode::Peaks,
@@ -265,8 +1096,62 @@ impl App {
         self.palette = self.palette.prev();
     }
 
-    pub fn adjust_zoom(&mut self, delta: f32) {
-        self.zoom = (self.zoom + delta).clamp(1.0, 64.0);
+    /// Deepest zoom: a few bins across the screen, or 64x, whichever is more.
+    fn max_zoom(&self) -> f32 {
+        let bins = if self.settings.iq { self.settings.fft_size } else { self.settings.fft_size / 2 };
+        (bins as f32 / 4.0).max(64.0)
+    }
+
+    /// Zoom the frequency axis by `factor` (>1 narrows the band). A panned
+    /// band keeps its center; the default band stays anchored at 0 Hz/DC.
+    pub fn adjust_zoom(&mut self, factor: f32) {
+        let (lo, hi) = self.freq_map().band();
+        let zoom = (self.zoom * factor).clamp(1.0, self.max_zoom());
+        if self.fmin.is_some() {
+            let span = (hi - lo) * self.zoom / zoom;
+            self.fmin = Some((lo + hi) / 2.0 - span / 2.0);
+        }
+        self.zoom = zoom;
+    }
+
+    /// Show the band [lo, hi] (center-relative Hz), clamped to the full range.
+    pub fn set_band(&mut self, lo: f32, hi: f32) {
+        let (full_lo, full_hi) = self.freq_map().full_range();
+        let (lo, hi) = (lo.max(full_lo), hi.min(full_hi));
+        if hi <= lo {
+            return;
+        }
+        self.zoom = ((full_hi - full_lo) / (hi - lo)).clamp(1.0, self.max_zoom());
+        self.fmin = Some(lo);
+    }
+
+    /// Apply the --fmin/--fmax band from the settings, if any.
+    fn apply_band_setting(&mut self) {
+        let (full_lo, full_hi) = self.freq_map().full_range();
+        let center = self.settings.center_freq;
+        let edge = |f: Option<f64>, default: f32| f.map(|hz| (hz - center) as f32).unwrap_or(default);
+        match self.settings.band {
+            (None, None) => {}
+            (lo, hi) => self.set_band(edge(lo, full_lo), edge(hi, full_hi)),
+        }
+    }
+
+    /// Move the displayed band by `frac` of its width (positive = up).
+    pub fn pan_freq(&mut self, frac: f32) {
+        if self.zoom <= 1.0 {
+            self.set_status("zoom in (+) before panning the frequency band");
+            return;
+        }
+        let m = self.freq_map();
+        let (lo, hi) = m.band();
+        let (full_lo, full_hi) = m.full_range();
+        self.fmin = Some((lo + frac * (hi - lo)).clamp(full_lo, full_hi - (hi - lo)));
+    }
+
+    /// Back to the full frequency range.
+    pub fn reset_band(&mut self) {
+        self.zoom = 1.0;
+        self.fmin = None;
     }
 
     pub fn adjust_floor(&mut self, delta: f32) {
@@ -297,14 +1182,15 @@ impl App {
         }
     }
 
-    /// Native export size: one pixel per (zoomed) frequency bin and history
-    /// row, doubled for crispness. The exporter adds axis margins on top.
+    /// Native export size per panel: one pixel per (zoomed) frequency bin and
+    /// history row, doubled for crispness. The exporter adds axis margins on
+    /// top and stacks one panel per analyzed channel.
     pub fn png_content_dims(&self) -> (u32, u32) {
         const MAX_DIM: u32 = 4096;
         const MIN_DIM: u32 = 480;
-        let full_bins = self.buffer.front().map(|r| r.len()).unwrap_or(1).max(1) as f32;
+        let full_bins = self.buffer().front().map(|r| r.len()).unwrap_or(1).max(1) as f32;
         let bins = ((full_bins / self.zoom.max(1.0)).round().max(1.0) as u32) * 2;
-        let rows = (self.buffer.len().max(1) as u32) * 2;
+        let rows = (self.buffer().len().max(1) as u32) * 2;
         let (w, h) = match self.export_style() {
             AnimationStyle::Horizontal => (rows, bins),
             _ => (bins, rows),
@@ -312,39 +1198,207 @@ impl App {
         (w.clamp(MIN_DIM, MAX_DIM), h.clamp(MIN_DIM, MAX_DIM))
     }
 
-    /// Spectrum view has no 2D export; fall back to waterfall.
+    /// Spectrum and octave views have no 2D export; fall back to waterfall.
     fn export_style(&self) -> AnimationStyle {
-        if self.style == AnimationStyle::Spectrum { AnimationStyle::Waterfall } else { self.style }
+        match self.style {
+            AnimationStyle::Spectrum | AnimationStyle::Octave => AnimationStyle::Waterfall,
+            style => style,
+        }
     }
 
     pub fn save_png(&self, path: PathBuf, width: u32, height: u32) -> Result<()> {
         let s = &self.settings;
+        // Pinned markers whose row has left history keep only their readout
+        let markers: Vec<export::PngMarker> = self
+            .markers
+            .iter()
+            .enumerate()
+            .filter(|(_, m)| m.row.is_none() || self.marker_index(m).is_some())
+            .map(|(i, m)| export::PngMarker {
+                label: format!("M{}", i + 1),
+                freq: m.freq,
+                row: m.row.and_then(|_| self.marker_index(m)),
+            })
+            .collect();
         export::save_png(
             &export::PngRequest {
-                buffer: &self.buffer,
+                buffers: &self.buffers,
+                labels: &self.channel_labels,
                 palette: &self.palette,
                 db_floor: self.db_floor,
                 db_ceiling: self.db_ceiling,
                 width,
                 height,
                 style: self.export_style(),
-                freq_scale: self.freq_scale,
-                sample_rate: s.sample_rate,
-                zoom: self.zoom,
+                fmap: self.freq_map(),
                 bins_mode: self.bins_mode,
                 hop: s.hop_size,
-                title: Some(format!(
-                    "fs={}Hz N={} L={} H={} floor={} ceil={}",
-                    s.sample_rate, s.fft_size, s.window_len, s.hop_size,
-                    self.db_floor as i32, self.db_ceiling as i32
-                )),
+                newest_time: self.newest_time(),
+                title: Some(format!("{} floor={} ceil={}", self.metadata(), self.db_floor as i32, self.db_ceiling as i32)),
+                unit: &self.unit_label(),
+                markers: &markers,
+                legend: &self.marker_readouts(),
+                pitch: if self.show_pitch { &self.pitches } else { &[] },
+                notes: self.note_a4(),
+            },
+            path,
+        )
+    }
+
+    /// Enabled traces of every channel for export, named after the channel
+    /// when there are several.
+    pub fn plot_traces(&self) -> Vec<export::PlotTrace> {
+        let mut out = Vec::new();
+        for (traces, name) in self.traces.iter().zip(&self.channel_labels) {
+            for kind in self.trace_set.kinds() {
+                let Some(values) = traces.get(kind) else { continue };
+                let label = if self.traces.len() > 1 { format!("{name} {}", self.trace_label(kind)) } else { self.trace_label(kind) };
+                out.push(export::PlotTrace { label, color: kind.rgb(), values });
+            }
+        }
+        out
+    }
+
+    /// `traces` (usually `plot_traces`) as a line plot over the displayed band.
+    pub fn save_spectrum_png(&self, traces: &[export::PlotTrace], path: PathBuf, width: u32, height: u32) -> Result<()> {
+        export::save_spectrum_png(
+            &export::PlotRequest {
+                traces,
+                fmap: self.freq_map(),
+                db_floor: self.db_floor,
+                db_ceiling: self.db_ceiling,
+                width,
+                height,
+                title: Some(format!("{} rows={}", self.metadata(), self.traces[0].count())),
+                unit: &self.unit_label(),
+                notes: self.note_a4(),
             },
             path,
         )
     }
 
+    /// `traces` as CSV columns, one row per bin.
+    pub fn save_spectrum_csv(&self, traces: &[export::PlotTrace], path: PathBuf) -> Result<()> {
+        let meta = format!("{} rows={}", self.metadata(), self.traces[0].count());
+        export::save_spectrum_csv(traces, &self.freq_map(), Some(&meta), path)
+    }
+
+    /// Unit of every dB value: "dBFS", or "dB SPL"/"dBV" when calibrated,
+    /// marked "(A)"/"(C)" when weighted, per Hz or √Hz in the density units.
+    pub fn unit_label(&self) -> String {
+        self.settings.units.label(&self.level_label())
+    }
+
+    /// Unit of a band level, which sums density over the band: the unit
+    /// label without the per-Hz part, e.g. "dB(A)" for A-weighted SPL.
+    pub fn level_label(&self) -> String {
+        let base = self.settings.calibration.map(|c| c.base_label()).unwrap_or("dBFS");
+        match self.settings.weighting {
+            Weighting::Z => base.to_string(),
+            w if base == "dB SPL" => format!("dB{}", w.suffix()),
+            w => format!("{base}{}", w.suffix()),
+        }
+    }
+
+    /// Octave-view bands inside the displayed (positive) frequency band,
+    /// each at least one bin wide.
+    pub fn octave_bands(&self) -> Vec<view::OctaveBand> {
+        let fmap = self.freq_map();
+        let bins = self.buffer().front().map(|r| r.len()).unwrap_or(self.settings.fft_size / 2);
+        let (lo, hi) = fmap.band();
+        view::octave_bands(self.octave_fraction, lo.max(0.0), hi, fmap.hz_per_bin(bins))
+    }
+
+    /// Turns a power sum of bins into a band level: a tone's main lobe sums
+    /// to its level times the window's noise bandwidth in dBFS units, and
+    /// density values integrate over the bin width.
+    pub fn band_correction_db(&self) -> f32 {
+        match self.settings.units {
+            SpectrumUnits::Dbfs => -10.0 * self.enbw_bins.max(f32::EPSILON).log10(),
+            SpectrumUnits::Psd | SpectrumUnits::Asd => {
+                let bins = self.buffer().front().map(|r| r.len()).unwrap_or(1);
+                10.0 * self.freq_map().hz_per_bin(bins).log10()
+            }
+        }
+    }
+
+    /// Band levels of one history row.
+    pub fn band_levels(&self, row: &[f32], bands: &[view::OctaveBand]) -> Vec<f32> {
+        view::band_levels(row, &self.freq_map(), bands, self.band_correction_db())
+    }
+
+    /// Leq (energy mean) and Lmax per band, plus the broadband sum of all
+    /// bands last, over the history on screen and behind it.
+    pub fn band_stats(&self, buf: &VecDeque<Vec<f32>>, bands: &[view::OctaveBand]) -> (Vec<f32>, Vec<f32>) {
+        let mut energy = vec![0.0f64; bands.len() + 1];
+        let mut lmax = vec![f32::NEG_INFINITY; bands.len() + 1];
+        let mut n = 0usize;
+        for row in buf.iter().skip(self.scroll) {
+            let mut levels = self.band_levels(row, bands);
+            levels.push(view::total_level(&levels));
+            for ((e, m), &l) in energy.iter_mut().zip(&mut lmax).zip(&levels) {
+                *e += 10f64.powf(l as f64 / 10.0);
+                *m = m.max(l);
+            }
+            n += 1;
+        }
+        let leq = energy
+            .iter()
+            .map(|&e| if e > 0.0 { (10.0 * (e / n.max(1) as f64).log10()) as f32 } else { f32::NEG_INFINITY })
+            .collect();
+        (leq, lmax)
+    }
+
+    /// Analysis parameters for the PNG title and CSV header, e.g.
+    /// "fs=48000Hz N=1024 L=1024 H=256 win=hann units=dBFS" (with
+    /// " reassigned" after the window when on), or
+    /// "fs=48000Hz cqt fmin=32.7Hz 24/oct L=50077 H=256 units=dBFS".
+    pub fn metadata(&self) -> String {
+        let s = &self.settings;
+        if let Some(cqt) = s.cqt {
+            return format!(
+                "fs={}Hz{} cqt fmin={} {}/oct L={} H={} units={}",
+                s.sample_rate, if s.native_rate { " native" } else { "" },
+                view::format_hz(cqt.fmin as f64), cqt.bins_per_octave, s.window_len, s.hop_size, self.unit_label()
+            );
+        }
+        format!(
+            "fs={}Hz{}{} N={} L={} H={} win={}{} units={}",
+            s.sample_rate, if s.native_rate { " native" } else { "" },
+            if s.iq { " I/Q" } else { "" },
+            s.fft_size, s.window_len, s.hop_size, self.window_name(),
+            if s.reassign { " reassigned" } else { "" }, self.unit_label()
+        )
+    }
+
+    /// One CSV per analyzed channel: `x.csv` becomes `x_left.csv`,
+    /// `x_right.csv`, ... when more than one channel is analyzed. Each
+    /// starts with a `#` line of `metadata`. With pitch tracking on, the f0
+    /// tracks of all channels go to `x_pitch.csv` next to them.
     pub fn save_csv(&self, path: PathBuf) -> Result<()> {
-        export::save_csv(&self.buffer, path)
+        let meta = self.metadata();
+        let stem = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+        let ext = path.extension().map(|e| e.to_string_lossy().to_string()).unwrap_or_else(|| "csv".to_string());
+        if self.buffers.len() == 1 {
+            export::save_csv(self.buffer(), Some(&meta), path.clone())?;
+        } else {
+            for (buffer, label) in self.buffers.iter().zip(&self.channel_labels) {
+                export::save_csv(buffer, Some(&meta), path.with_file_name(format!("{stem}_{label}.{ext}")))?;
+            }
+        }
+        if self.settings.pitch && !self.pitches.is_empty() {
+            self.save_pitch_csv(path.with_file_name(format!("{stem}_pitch.{ext}")))?;
+        }
+        Ok(())
+    }
+
+    /// f0 tracks as CSV, timed like the hover readout: file time for files,
+    /// stream time for live inputs.
+    pub fn save_pitch_csv(&self, path: PathBuf) -> Result<()> {
+        let newest = self.stream_time();
+        let row_secs = self.row_secs();
+        let meta = format!("{} f0={:.0}-{:.0}Hz yin", self.metadata(), self.settings.pitch_range.0, self.settings.pitch_range.1);
+        export::save_pitch_csv(&self.pitches, &self.channel_labels, |idx| newest - idx as f64 * row_secs, Some(&meta), path)
     }
 }
 
@@ -0,0 +1,230 @@
+//! Absolute calibration: an offset that turns dBFS readings into dB SPL or
+//! dBV. It comes from config.toml (a fixed offset, or a microphone
+//! sensitivity and the ADC's full-scale voltage) or from a calibrator tone
+//! measured by `sgram-tui calibrate` and stored per input device.
+
+use crate::app::{App, Settings};
+use crate::config::{self, CalibrationConfig, DeviceCalibration, DeviceCalibrations};
+use crate::dsp::{Scalloping, SpectrumUnits, Weighting};
+use crate::view;
+use anyhow::{anyhow, Result};
+use std::path::Path;
+use std::time::Duration;
+
+/// Seconds of calibrator tone averaged by `run`
+const MEASURE_SECS: f64 = 3.0;
+/// Largest level swing over the measurement that still counts as steady
+const MAX_SPREAD_DB: f32 = 1.0;
+/// Pressure reference for dB SPL
+const P_REF: f64 = 20e-6;
+
+/// What a calibrated dB value is relative to.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Reference {
+    /// dB re 20 µPa
+    Spl,
+    /// dB re 1 V RMS
+    Volts,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Calibration {
+    /// Added to every dBFS value
+    pub offset_db: f32,
+    pub reference: Reference,
+}
+
+impl Calibration {
+    /// From the `[calibration]` config section; `None` when it sets nothing.
+    pub fn from_config(c: &CalibrationConfig) -> Result<Option<Self>> {
+        let reference = match c.unit.as_deref().map(|u| u.to_ascii_lowercase().replace(' ', "")) {
+            None => Reference::Spl,
+            Some(u) if u == "spl" || u == "dbspl" => Reference::Spl,
+            Some(u) if u == "v" || u == "dbv" || u == "volts" => Reference::Volts,
+            Some(u) => return Err(anyhow!("calibration: unknown unit '{u}' (expected spl or dbv)")),
+        };
+        if let Some(offset) = c.offset_db {
+            return Ok(Some(Self { offset_db: offset as f32, reference }));
+        }
+        // A full-scale sine is 0 dBFS: its RMS voltage is full_scale_v / √2
+        let v_rms = match c.full_scale_v {
+            Some(v) if v > 0.0 => v / std::f64::consts::SQRT_2,
+            Some(v) => return Err(anyhow!("calibration: full_scale_v must be positive, got {v}")),
+            None if c.sensitivity_mv_pa.is_some() => {
+                return Err(anyhow!("calibration: sensitivity_mv_pa needs full_scale_v (peak volts at digital full scale)"))
+            }
+            None => return Ok(None),
+        };
+        Ok(Some(match c.sensitivity_mv_pa {
+            Some(s) if s > 0.0 => Self { offset_db: (20.0 * (v_rms / (s / 1000.0) / P_REF).log10()) as f32, reference: Reference::Spl },
+            Some(s) => return Err(anyhow!("calibration: sensitivity_mv_pa must be positive, got {s}")),
+            None => Self { offset_db: (20.0 * v_rms.log10()) as f32, reference: Reference::Volts },
+        }))
+    }
+
+    /// dB unit the offset produces
+    pub fn base_label(&self) -> &'static str {
+        match self.reference {
+            Reference::Spl => "dB SPL",
+            Reference::Volts => "dBV",
+        }
+    }
+}
+
+/// Calibration for a run: a stored offset for the microphone `device`
+/// (`None` for other inputs) wins over the config section.
+pub fn resolve(cfg: Option<&CalibrationConfig>, stored: &DeviceCalibrations, device: Option<&str>) -> Result<Option<Calibration>> {
+    if let Some(d) = device.and_then(|d| stored.devices.get(d)) {
+        return Ok(Some(Calibration { offset_db: d.offset_db as f32, reference: Reference::Spl }));
+    }
+    match cfg {
+        Some(c) => Calibration::from_config(c),
+        None => Ok(None),
+    }
+}
+
+/// Mean level and frequency of a calibrator tone near `ref_hz` across dBFS
+/// `rows`, from the interpolated peak of each row.
+pub fn measure_tone(rows: &[Vec<f32>], sample_rate: u32, ref_hz: f64, scallop: &Scalloping) -> Result<(f32, f64)> {
+    let first = rows.first().ok_or_else(|| anyhow!("no audio frames to measure"))?;
+    let bins = first.len();
+    let hz_per_bin = sample_rate as f64 / (2 * bins) as f64;
+    // Search ±10% around the reference, at least a few bins wide
+    let span = (ref_hz * 0.1 / hz_per_bin).max(3.0);
+    let center = ref_hz / hz_per_bin;
+    let lo = (center - span).max(1.0) as usize;
+    let hi = ((center + span).ceil() as usize + 1).min(bins);
+    if lo >= hi {
+        return Err(anyhow!("{ref_hz} Hz is outside the analyzed band (fs = {sample_rate} Hz)"));
+    }
+    let peaks: Vec<view::Peak> = rows
+        .iter()
+        .map(|row| view::interpolate_peak(row, view::max_bin_in(row, lo, hi).0, scallop))
+        .collect();
+    let level = peaks.iter().map(|p| p.db).sum::<f32>() / peaks.len() as f32;
+    let freq = peaks.iter().map(|p| p.bin as f64).sum::<f64>() / peaks.len() as f64 * hz_per_bin;
+    let spread = peaks.iter().map(|p| p.db).fold(f32::NEG_INFINITY, f32::max)
+        - peaks.iter().map(|p| p.db).fold(f32::INFINITY, f32::min);
+    if (freq - ref_hz).abs() > ref_hz * 0.05 || level < -100.0 {
+        return Err(anyhow!("no {ref_hz} Hz calibrator tone found (strongest nearby: {:.1} dBFS at {})", level, view::format_hz(freq)));
+    }
+    if spread > MAX_SPREAD_DB {
+        return Err(anyhow!("level not steady ({spread:.1} dB swing over the measurement); hold the calibrator in place and retry"));
+    }
+    Ok((level, freq))
+}
+
+/// Guided calibration: measure a `ref_db` dB SPL tone at `ref_hz` on
+/// `input` through the usual analysis pipeline, and store the offset for
+/// `device` in `store`. Returns the offset.
+pub fn run(input: &str, mut settings: Settings, device: Option<String>, ref_db: f64, ref_hz: f64, store: &Path) -> Result<f32> {
+    let key = device.clone().unwrap_or_else(|| "default".to_string());
+    // Measure raw dBFS tone levels
+    settings.units = SpectrumUnits::Dbfs;
+    settings.calibration = None;
+    settings.weighting = Weighting::Z;
+    settings.normalize = false;
+    settings.clamp_floor = false;
+    settings.pitch = false;
+    let live = input == "mic";
+    if live {
+        println!("Calibrating input device '{key}'.");
+        println!("Fit the {ref_db} dB SPL / {} calibrator to the microphone, switch it on, then press Enter.", view::format_hz(ref_hz));
+        let mut line = String::new();
+        std::io::stdin().read_line(&mut line)?;
+    } else {
+        settings.headless = true;
+        settings.realtime = false;
+    }
+    let mut app = App::new(input.to_string(), settings, false, device)?;
+    // Skip the first half second of a live input while the tone settles
+    let skip_secs = if live { 0.5 } else { 0.0 };
+    let mut rows = Vec::new();
+    let mut seen = 0usize;
+    loop {
+        match app.spectrogram_rx.recv_timeout(Duration::from_secs(5)) {
+            Ok(frame) => {
+                app.sync_sample_rate();
+                let Some(row) = frame.into_iter().next() else { continue };
+                seen += 1;
+                if seen as f64 * app.row_secs() > skip_secs {
+                    rows.push(row);
+                }
+                if rows.len() as f64 * app.row_secs() >= MEASURE_SECS {
+                    break;
+                }
+            }
+            Err(_) if rows.is_empty() => {
+                let cause = app.pipeline_error.lock().unwrap().take().map(|e| format!(": {e}")).unwrap_or_default();
+                return Err(anyhow!("no audio from {input}{cause}"));
+            }
+            // A short file: measure what there is
+            Err(_) => break,
+        }
+    }
+    let secs = rows.len() as f64 * app.row_secs();
+    let (level, freq) = measure_tone(&rows, app.settings.sample_rate, ref_hz, &app.scalloping)?;
+    // Hundredths of a dB are well inside the measurement's accuracy
+    let offset_db = ((ref_db - level as f64) * 100.0).round() / 100.0;
+    let offset = offset_db as f32;
+    let mut cals = config::load_calibrations(store);
+    cals.devices.insert(key.clone(), DeviceCalibration { offset_db, ref_db, ref_hz });
+    config::save_calibrations(store, &cals)?;
+    println!(
+        "measured {level:.2} dBFS at {} over {secs:.1}s; offset {offset:+.2} dB saved for '{key}' in {}",
+        view::format_hz(freq),
+        store.display()
+    );
+    Ok(offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sensitivity_and_full_scale_give_spl_offset() {
+        // 1 V peak full scale is 0.707 V RMS; at 50 mV/Pa that is 14.1 Pa, 117 dB SPL
+        let c = CalibrationConfig { sensitivity_mv_pa: Some(50.0), full_scale_v: Some(1.0), ..Default::default() };
+        let cal = Calibration::from_config(&c).unwrap().unwrap();
+        assert_eq!(cal.reference, Reference::Spl);
+        assert!((cal.offset_db - 116.99).abs() < 0.01, "{}", cal.offset_db);
+        let volts = Calibration::from_config(&CalibrationConfig { full_scale_v: Some(2.0), ..Default::default() }).unwrap().unwrap();
+        assert_eq!(volts.reference, Reference::Volts);
+        assert!((volts.offset_db - 3.01).abs() < 0.01);
+        let fixed = CalibrationConfig { offset_db: Some(100.0), unit: Some("dBV".into()), ..Default::default() };
+        assert_eq!(Calibration::from_config(&fixed).unwrap(), Some(Calibration { offset_db: 100.0, reference: Reference::Volts }));
+        assert!(Calibration::from_config(&CalibrationConfig { sensitivity_mv_pa: Some(50.0), ..Default::default() }).is_err());
+        assert_eq!(Calibration::from_config(&CalibrationConfig::default()).unwrap(), None);
+    }
+
+    #[test]
+    fn calibrator_tone_measures_and_stores_device_offset() {
+        use clap::Parser;
+        let store = std::env::temp_dir().join(format!("sgram_tui_cal_{}.toml", std::process::id()));
+        let _ = std::fs::remove_file(&store);
+        // A -20 dBFS "calibrator" off the bin grid: 94 dB SPL needs +114 dB
+        let src = "gen:sine:1001:level=-20:len=4s";
+        let cli = crate::Cli::parse_from(["sgram-tui", "calibrate", src]);
+        let settings = crate::build_settings(&cli, None);
+        let offset = run(src, settings, Some("USB Mic".into()), 94.0, 1000.0, &store).unwrap();
+        assert!((offset - 114.0).abs() < 0.1, "offset {offset}");
+        let stored = config::load_calibrations(&store);
+        let cal = resolve(None, &stored, Some("USB Mic")).unwrap().unwrap();
+        assert_eq!((cal.offset_db, cal.base_label()), (offset, "dB SPL"));
+        // No tone at the reference frequency: refuse instead of storing junk
+        assert!(run("gen:sine:3000:len=4s", settings, None, 94.0, 1000.0, &store).is_err());
+        assert!(!config::load_calibrations(&store).devices.contains_key("default"));
+        let _ = std::fs::remove_file(&store);
+    }
+
+    #[test]
+    fn stored_device_offset_wins_over_config() {
+        let mut stored = DeviceCalibrations::default();
+        stored.devices.insert("USB Mic".into(), DeviceCalibration { offset_db: 120.5, ref_db: 94.0, ref_hz: 1000.0 });
+        let cfg = CalibrationConfig { offset_db: Some(90.0), ..Default::default() };
+        assert_eq!(resolve(Some(&cfg), &stored, Some("USB Mic")).unwrap().unwrap().offset_db, 120.5);
+        assert_eq!(resolve(Some(&cfg), &stored, Some("other")).unwrap().unwrap().offset_db, 90.0);
+        assert_eq!(resolve(None, &stored, None).unwrap(), None);
+    }
+}
@@ -1,7 +1,9 @@
+use anyhow::Result;
 use directories::ProjectDirs;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Deserialize)]
 pub struct FileConfig {
@@ -10,6 +12,38 @@ pub struct FileConfig {
     pub device: Option<String>,
     pub png_path: Option<String>,
     pub csv_path: Option<String>,
+    /// `[calibration]`: absolute units for every input
+    pub calibration: Option<CalibrationConfig>,
+}
+
+/// Fixed calibration from config.toml: either an offset in dB, or a
+/// microphone sensitivity with the ADC's full-scale voltage.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct CalibrationConfig {
+    /// Added to every dBFS reading
+    pub offset_db: Option<f64>,
+    /// What `offset_db` yields: "spl" (dB SPL, default) or "dbv"
+    pub unit: Option<String>,
+    /// Microphone sensitivity in mV/Pa; needs `full_scale_v`, gives dB SPL
+    pub sensitivity_mv_pa: Option<f64>,
+    /// Peak voltage at digital full scale; alone it gives dBV
+    pub full_scale_v: Option<f64>,
+}
+
+/// Offsets measured by `sgram-tui calibrate`, keyed by input device name
+/// (calibration.toml in the config dir).
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct DeviceCalibrations {
+    #[serde(default)]
+    pub devices: BTreeMap<String, DeviceCalibration>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct DeviceCalibration {
+    pub offset_db: f64,
+    /// Reference the offset was measured against, e.g. 94 dB SPL at 1000 Hz
+    pub ref_db: f64,
+    pub ref_hz: f64,
 }
 
 pub fn config_dir() -> Option<PathBuf> {
@@ -23,3 +57,20 @@ pub fn load_config() -> Option<FileConfig> {
     let data = fs::read_to_string(path).ok()?;
     toml::from_str::<FileConfig>(&data).ok()
 }
+
+pub fn calibrations_path() -> Option<PathBuf> {
+    config_dir().map(|d| d.join("calibration.toml"))
+}
+
+/// Stored device calibrations; a missing or unreadable file is empty.
+pub fn load_calibrations(path: &Path) -> DeviceCalibrations {
+    fs::read_to_string(path).ok().and_then(|s| toml::from_str(&s).ok()).unwrap_or_default()
+}
+
+pub fn save_calibrations(path: &Path, cals: &DeviceCalibrations) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, toml::to_string(cals)?)?;
+    Ok(())
+}
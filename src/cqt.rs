@@ -0,0 +1,177 @@
+//! Constant-Q transform (Brown & Puckette, 1992): bins spaced a fixed
+//! fraction of an octave apart, each analyzed over a Hann window
+//! `Q` periods long, so every bin's bandwidth matches its spacing. Rows
+//! hold `bins` values from `fmin` upwards and are drawn through the same
+//! `view::FreqMap` as FFT rows, which maps them on a log axis one for one.
+//!
+//! The kernels are applied in the frequency domain: one FFT of the frame,
+//! then a short dot product per bin with that bin's (sparse) kernel
+//! spectrum.
+
+use crate::dsp::SpectrumUnits;
+use rustfft::num_complex::Complex32;
+use rustfft::FftPlanner;
+
+/// Kernel spectrum values under this fraction of the kernel's peak are
+/// dropped (-80 dB, under the display range)
+const SPARSITY: f32 = 1e-4;
+
+/// Bin layout of `--transform cqt`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CqtSpec {
+    /// Center of the lowest bin in Hz
+    pub fmin: f32,
+    pub bins_per_octave: u32,
+}
+
+impl CqtSpec {
+    /// Periods per window: bin spacing equals bin bandwidth.
+    pub fn q(&self) -> f64 {
+        1.0 / (2f64.powf(1.0 / self.bins_per_octave as f64) - 1.0)
+    }
+
+    /// Bins whose upper edge stays under Nyquist at `sample_rate`.
+    pub fn bins(&self, sample_rate: u32) -> usize {
+        let b = self.bins_per_octave as f64;
+        let top = sample_rate as f64 / 2.0 / 2f64.powf(0.5 / b);
+        if top <= self.fmin as f64 {
+            return 0;
+        }
+        (b * (top / self.fmin as f64).log2()).floor() as usize + 1
+    }
+
+    /// Center frequency of (fractional) bin `k`.
+    pub fn freq(&self, k: f32) -> f32 {
+        self.fmin * 2f32.powf(k / self.bins_per_octave as f32)
+    }
+
+    /// Fractional bin of frequency `f`; -inf at 0 Hz and below.
+    pub fn bin(&self, f: f32) -> f32 {
+        if f <= 0.0 {
+            return f32::NEG_INFINITY;
+        }
+        self.bins_per_octave as f32 * (f / self.fmin).log2()
+    }
+
+    /// Window length of bin `k` in samples.
+    fn window_len(&self, k: usize, sample_rate: u32) -> usize {
+        ((self.q() * sample_rate as f64 / self.freq(k as f32) as f64).ceil() as usize).max(2)
+    }
+
+    /// Analysis frame: the lowest bin's window.
+    pub fn frame_len(&self, sample_rate: u32) -> usize {
+        self.window_len(0, sample_rate)
+    }
+}
+
+/// Precomputed kernel spectra for one sample rate.
+pub struct CqtKernel {
+    frame_len: usize,
+    fft_size: usize,
+    /// Per bin: the FFT bins its kernel spans and the conjugate kernel
+    /// values there, scaled so the dot product is calibrated
+    bins: Vec<(usize, Vec<Complex32>)>,
+}
+
+impl CqtKernel {
+    /// Kernels centered on the middle of a `spec.frame_len`-sample frame,
+    /// calibrated like the FFT rows: a full-scale sine reads 0 dBFS in its
+    /// bin, or each bin is divided by its own noise bandwidth for the
+    /// density units.
+    pub fn new(spec: CqtSpec, sample_rate: u32, units: SpectrumUnits) -> Self {
+        let frame_len = spec.frame_len(sample_rate);
+        let fft_size = frame_len.next_power_of_two();
+        let fft = FftPlanner::<f32>::new().plan_fft_forward(fft_size);
+        let center = frame_len as f64 / 2.0;
+        let mut buf = vec![Complex32::new(0.0, 0.0); fft_size];
+        let bins = (0..spec.bins(sample_rate))
+            .map(|k| {
+                let n = spec.window_len(k, sample_rate).min(frame_len);
+                let w = crate::dsp::window_coeffs(crate::dsp::WindowType::Hann, n);
+                let sum: f64 = w.iter().map(|&v| v as f64).sum();
+                let sq: f64 = w.iter().map(|&v| (v as f64) * (v as f64)).sum();
+                let scale = match units {
+                    SpectrumUnits::Dbfs => 2.0 / sum.max(f64::EPSILON),
+                    SpectrumUnits::Psd | SpectrumUnits::Asd => 2.0 / (sample_rate as f64 * sq).max(f64::EPSILON).sqrt(),
+                };
+                let start = ((center - n as f64 / 2.0).round() as usize).min(frame_len - n);
+                let omega = 2.0 * std::f64::consts::PI * spec.freq(k as f32) as f64 / sample_rate as f64;
+                buf.iter_mut().for_each(|c| *c = Complex32::new(0.0, 0.0));
+                for (i, &v) in w.iter().enumerate() {
+                    let ph = omega * ((start + i) as f64 - center);
+                    buf[start + i] = Complex32::new((v as f64 * ph.cos()) as f32, (v as f64 * ph.sin()) as f32);
+                }
+                fft.process(&mut buf);
+                // Parseval: sum x conj(h) = sum X conj(H) / N
+                let peak = buf.iter().map(|c| c.norm()).fold(0.0f32, f32::max);
+                let keep = |c: &Complex32| c.norm() >= peak * SPARSITY;
+                let lo = buf.iter().position(keep).unwrap_or(0);
+                let hi = buf.iter().rposition(keep).map(|i| i + 1).unwrap_or(lo);
+                let s = (scale / fft_size as f64) as f32;
+                (lo, buf[lo..hi].iter().map(|c| c.conj() * s).collect())
+            })
+            .collect();
+        Self { frame_len, fft_size, bins }
+    }
+
+    pub fn frame_len(&self) -> usize {
+        self.frame_len
+    }
+
+    pub fn fft_size(&self) -> usize {
+        self.fft_size
+    }
+
+    pub fn len(&self) -> usize {
+        self.bins.len()
+    }
+
+    /// Constant-Q coefficients of a frame from its `fft_size`-point spectrum.
+    pub fn apply(&self, spectrum: &[Complex32]) -> Vec<Complex32> {
+        self.bins
+            .iter()
+            .map(|(lo, kernel)| spectrum[*lo..*lo + kernel.len()].iter().zip(kernel).map(|(x, k)| x * k).sum())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsp::SpectrogramBuilder;
+    use std::f32::consts::TAU;
+
+    const SPEC: CqtSpec = CqtSpec { fmin: 110.0, bins_per_octave: 12 };
+
+    #[test]
+    fn layout_stops_under_nyquist() {
+        // 110 Hz to just under 24 kHz: 7.7 octaves of 12 bins
+        assert_eq!(SPEC.bins(48_000), 93);
+        assert!(SPEC.freq(92.5) < 24_000.0);
+        assert!((SPEC.freq(12.0) - 220.0).abs() < 1e-3);
+        assert!((SPEC.bin(440.0) - 24.0).abs() < 1e-4);
+        assert_eq!(SPEC.bin(0.0), f32::NEG_INFINITY);
+        // Q = 16.8 periods of 110 Hz at 48 kHz
+        assert_eq!(SPEC.frame_len(48_000), 7_339);
+    }
+
+    #[test]
+    fn sine_reads_full_scale_in_its_bin_only() {
+        let sr = 48_000;
+        let mut spec = SpectrogramBuilder::new(1024, 1024, 4096).sample_rate(sr).cqt(Some(SPEC)).build();
+        for (k, level) in [(24usize, 0.0f32), (60, -6.0)] {
+            let f = SPEC.freq(k as f32);
+            let a = 10f32.powf(level / 20.0);
+            let x: Vec<f32> = (0..16_000).map(|i| a * (TAU * f * i as f32 / sr as f32).sin()).collect();
+            let rows = spec.process_samples(&x);
+            let row = rows.last().unwrap();
+            assert_eq!(row.len(), 93);
+            let (best, v) = crate::view::max_bin_in(row, 0, row.len());
+            assert_eq!(best, k);
+            assert!((v - level).abs() < 0.1, "bin {k}: {v} dB");
+            // Three bins away is down in the Hann sidelobes
+            assert!(row[k + 3] < level - 30.0, "{}", row[k + 3]);
+            assert!(row[k - 3] < level - 30.0, "{}", row[k - 3]);
+        }
+    }
+}
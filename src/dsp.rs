@@ -1,7 +1,169 @@
-use rustfft::{num_complex::Complex32, FftPlanner};
+use crate::cqt::{CqtKernel, CqtSpec};
+use crate::resample::bessel_i0;
+use rustfft::{num_complex::{Complex, Complex32}, FftPlanner};
+use std::collections::VecDeque;
 
-#[derive(Clone, Copy, Debug)]
-pub enum WindowType { Hann, Hamming, Blackman }
+/// Analysis window. Parameterized windows carry their shape parameter.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WindowType {
+    Hann,
+    Hamming,
+    Blackman,
+    /// 4-term Blackman-Harris: -92 dB sidelobes
+    BlackmanHarris,
+    /// 5-term flat-top: under 0.01 dB scalloping loss, for amplitude readings
+    FlatTop,
+    /// Kaiser with shape β; higher β trades a wider main lobe for lower sidelobes
+    Kaiser(f32),
+    /// Gaussian with standard deviation σ as a fraction of half the frame
+    Gaussian(f32),
+    /// Tukey (tapered cosine): fraction α of the frame is tapered, 0 is
+    /// rectangular and 1 is Hann
+    Tukey(f32),
+    /// Dolph-Chebyshev with equiripple sidelobes this many dB down
+    Chebyshev(f32),
+}
+
+impl WindowType {
+    /// Parse `hann`, `flattop`, `blackman-harris`, or a parameterized window
+    /// with an optional parameter: `kaiser:9`, `gaussian:0.4`, `tukey:0.5`,
+    /// `chebyshev:100` (dB).
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.to_ascii_lowercase();
+        let (name, param) = match s.split_once(':') {
+            Some((n, p)) => (n, Some(p.parse::<f32>().ok().filter(|v| v.is_finite())?)),
+            None => (s.as_str(), None),
+        };
+        let fixed = |w: Self| param.is_none().then_some(w);
+        match name {
+            "hann" | "hanning" => fixed(Self::Hann),
+            "hamming" => fixed(Self::Hamming),
+            "blackman" => fixed(Self::Blackman),
+            "blackman-harris" | "blackmanharris" | "bh" => fixed(Self::BlackmanHarris),
+            "flattop" | "flat-top" => fixed(Self::FlatTop),
+            "kaiser" => Some(Self::Kaiser(param.unwrap_or(9.0))).filter(|_| param.unwrap_or(0.0) >= 0.0),
+            "gaussian" | "gauss" => Some(Self::Gaussian(param.unwrap_or(0.4))).filter(|_| param.unwrap_or(1.0) > 0.0),
+            "tukey" => Some(Self::Tukey(param.unwrap_or(0.5))).filter(|_| (0.0..=1.0).contains(&param.unwrap_or(0.5))),
+            "chebyshev" | "dolph-chebyshev" | "chebwin" => {
+                Some(Self::Chebyshev(param.unwrap_or(100.0))).filter(|_| param.unwrap_or(1.0) > 0.0)
+            }
+            _ => None,
+        }
+    }
+
+    /// Name as `parse` accepts it, e.g. "kaiser:9"
+    pub fn name(&self) -> String {
+        match self {
+            Self::Hann => "hann".to_string(),
+            Self::Hamming => "hamming".to_string(),
+            Self::Blackman => "blackman".to_string(),
+            Self::BlackmanHarris => "blackman-harris".to_string(),
+            Self::FlatTop => "flattop".to_string(),
+            Self::Kaiser(b) => format!("kaiser:{b}"),
+            Self::Gaussian(s) => format!("gaussian:{s}"),
+            Self::Tukey(a) => format!("tukey:{a}"),
+            Self::Chebyshev(db) => format!("chebyshev:{db}"),
+        }
+    }
+}
+
+/// What a bin's dB value measures.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpectrumUnits {
+    /// Tone amplitude (coherent gain): a full-scale sine reads 0 dBFS at
+    /// any FFT size and window
+    Dbfs,
+    /// Power spectral density in dBFS/Hz (equivalent noise bandwidth): a
+    /// white noise floor reads the same at any FFT size and window
+    Psd,
+    /// Amplitude spectral density in dBFS/√Hz; in dB the same numbers as Psd
+    Asd,
+}
+
+impl SpectrumUnits {
+    /// Unit label for axes, titles and metadata on a dB `base` ("dBFS",
+    /// or "dB SPL"/"dBV" when calibrated), e.g. "dBFS/Hz"
+    pub fn label(self, base: &str) -> String {
+        match self {
+            Self::Dbfs => base.to_string(),
+            Self::Psd => format!("{base}/Hz"),
+            Self::Asd => format!("{base}/√Hz"),
+        }
+    }
+}
+
+/// IEC 61672-1 frequency weighting, applied to every bin by the analyzer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Weighting {
+    /// Zero (flat): no correction
+    Z,
+    A,
+    C,
+}
+
+impl Weighting {
+    /// Gain in dB at `f` Hz; 0 dB at 1 kHz
+    pub fn gain_db(self, f: f64) -> f64 {
+        let f2 = f * f;
+        let (p1, p2, p3, p4) = (20.598997f64.powi(2), 107.65265f64.powi(2), 737.86223f64.powi(2), 12194.217f64.powi(2));
+        let r = match self {
+            Self::Z => return 0.0,
+            Self::A => p4 * f2 * f2 / ((f2 + p1) * ((f2 + p2) * (f2 + p3)).sqrt() * (f2 + p4)),
+            Self::C => p4 * f2 / ((f2 + p1) * (f2 + p4)),
+        };
+        let norm = match self {
+            Self::A => 2.0,
+            _ => 0.062,
+        };
+        20.0 * r.max(1e-15).log10() + norm
+    }
+
+    /// Unit suffix: "(A)", "(C)", or nothing for Z
+    pub fn suffix(self) -> &'static str {
+        match self {
+            Self::Z => "",
+            Self::A => "(A)",
+            Self::C => "(C)",
+        }
+    }
+}
+
+/// Multitaper estimation (Thomson, 1982): each frame is analyzed with
+/// `tapers` orthogonal Slepian (DPSS) windows of time-bandwidth product
+/// `nw`, and their power spectra are averaged. A tone spreads over ±`nw`
+/// bins, and a noise floor's bin-to-bin variance drops about `tapers`-fold
+/// without averaging frames.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Multitaper {
+    pub nw: f32,
+    pub tapers: usize,
+}
+
+impl Multitaper {
+    /// Parse `NW` or `NW:K`, NW in 1..=16 and K in 1..=2NW. K defaults to
+    /// 2NW - 1, the tapers that keep almost all their energy in band.
+    pub fn parse(s: &str) -> Option<Self> {
+        let (nw, k) = match s.split_once(':') {
+            Some((nw, k)) => (nw.parse::<f32>().ok()?, Some(k.parse::<usize>().ok()?)),
+            None => (s.parse::<f32>().ok()?, None),
+        };
+        if !(1.0..=16.0).contains(&nw) {
+            return None;
+        }
+        let max = (2.0 * nw).floor() as usize;
+        let tapers = match k {
+            Some(0) => return None,
+            Some(k) => k,
+            None => max - 1,
+        };
+        (tapers <= max).then_some(Self { nw, tapers })
+    }
+
+    /// Name as `parse` accepts it, e.g. "dpss:4:7"
+    pub fn name(&self) -> String {
+        format!("dpss:{}:{}", self.nw, self.tapers)
+    }
+}
 
 pub struct Spectrogram {
     fft_size: usize,
@@ -10,14 +172,30 @@ pub struct Spectrogram {
     db_floor: f32,
     window: Vec<f32>,
     mag_scale: f32,
+    /// Scale of the real-input DC bin, which has no two-sided split
+    dc_scale: f32,
     tmp: Vec<Complex32>,
     fft: std::sync::Arc<dyn rustfft::Fft<f32>>,
     overlap_buf: Vec<f32>,
+    /// Quadrature samples for complex (I/Q) input, aligned with `overlap_buf`
+    overlap_im: Vec<f32>,
     alpha: u8,
     pre_emph: Option<f32>,
     prev_sample: f32,
+    prev_im: f32,
     clamp_floor: bool,
     normalize: bool,
+    /// Calibration offset added to every bin (0 = dBFS)
+    offset_db: f32,
+    /// Weighting gain per output bin in dB; empty for Z
+    weights: Vec<f32>,
+    /// Constant-Q bins in place of the FFT bins (real input only)
+    cqt: Option<CqtKernel>,
+    /// Time-frequency reassignment of the FFT bins (real input only)
+    reassign: Option<Reassigner>,
+    /// Multitaper windows, `window` first; empty for a single window
+    tapers: Vec<Vec<f32>>,
+    taper_buf: Vec<Complex32>,
 }
 
 pub struct SpectrogramBuilder {
@@ -30,62 +208,155 @@ pub struct SpectrogramBuilder {
     pre_emph: Option<f32>,
     clamp_floor: bool,
     normalize: bool,
+    units: SpectrumUnits,
+    sample_rate: u32,
+    offset_db: f32,
+    weighting: Weighting,
+    cqt: Option<CqtSpec>,
+    reassign: bool,
+    multitaper: Option<Multitaper>,
 }
 
 impl SpectrogramBuilder {
     pub fn new(fft_size: usize, frame_len: usize, hop: usize) -> Self {
-        Self { fft_size, frame_len, hop, db_floor: -80.0, window: WindowType::Hann, alpha: 1, pre_emph: None, clamp_floor: false, normalize: false }
+        Self {
+            fft_size, frame_len, hop, db_floor: -80.0, window: WindowType::Hann, alpha: 1, pre_emph: None,
+            clamp_floor: false, normalize: false, units: SpectrumUnits::Dbfs, sample_rate: 48_000, offset_db: 0.0,
+            weighting: Weighting::Z, cqt: None, reassign: false,
+            multitaper: None,
+        }
     }
+    pub fn units(mut self, u: SpectrumUnits) -> Self { self.units = u; self }
+    /// Calibration offset in dB (e.g. dBFS to dB SPL), applied before the floor clamp
+    pub fn offset_db(mut self, db: f32) -> Self { self.offset_db = db; self }
+    pub fn weighting(mut self, w: Weighting) -> Self { self.weighting = w; self }
+    /// Input rate; the density units and weighting depend on it
+    pub fn sample_rate(mut self, fs: u32) -> Self { self.sample_rate = fs.max(1); self }
     pub fn db_floor(mut self, f: f32) -> Self { self.db_floor = f; self }
     pub fn window(mut self, w: WindowType) -> Self { self.window = w; self }
     pub fn alpha(mut self, a: u8) -> Self { self.alpha = if a == 2 { 2 } else { 1 }; self }
     pub fn pre_emphasis(mut self, beta: Option<f32>) -> Self { self.pre_emph = beta; self }
     pub fn clamp_floor(mut self, on: bool) -> Self { self.clamp_floor = on; self }
     pub fn normalize(mut self, on: bool) -> Self { self.normalize = on; self }
-    pub fn build(self) -> Spectrogram {
+    /// Constant-Q rows (`cqt::CqtSpec`) instead of FFT bins. The frame and
+    /// FFT sizes then follow from the lowest bin at the sample rate, and
+    /// the window is each bin's own.
+    pub fn cqt(mut self, spec: Option<CqtSpec>) -> Self { self.cqt = spec; self }
+    /// Reassigned rows: each bin's power moves to the time and frequency
+    /// it was measured at (see `Reassigner`). Rows then come out half a
+    /// frame late. Ignored with a constant-Q layout.
+    pub fn reassign(mut self, on: bool) -> Self { self.reassign = on; self }
+    /// Average the powers of a set of DPSS tapers in place of `window`.
+    /// Needs plain FFT bins: combining it with `cqt` or `reassign` is a
+    /// caller bug, asserted in debug builds and falling back to `window`
+    /// in release ones.
+    pub fn multitaper(mut self, mt: Option<Multitaper>) -> Self { self.multitaper = mt; self }
+    pub fn build(mut self) -> Spectrogram {
+        let cqt = self.cqt.map(|spec| CqtKernel::new(spec, self.sample_rate, self.units));
+        if let Some(k) = &cqt {
+            self.frame_len = k.frame_len();
+            self.fft_size = k.fft_size();
+        }
         let mut planner = FftPlanner::<f32>::new();
         let fft = planner.plan_fft_forward(self.fft_size);
-        let window = match self.window {
-            WindowType::Hann => hann(self.frame_len),
-            WindowType::Hamming => hamming(self.frame_len),
-            WindowType::Blackman => blackman(self.frame_len),
+        debug_assert!(
+            self.multitaper.is_none() || (cqt.is_none() && !self.reassign),
+            "multitaper cannot be combined with a constant-Q layout or reassignment"
+        );
+        let multitaper = self.multitaper.filter(|_| cqt.is_none() && !self.reassign);
+        let tapers = if cqt.is_some() {
+            vec![vec![1.0; self.frame_len]]
+        } else {
+            analysis_windows(self.window, multitaper, self.frame_len)
         };
         // Calibrate so a full-scale sine reads ~0 dBFS regardless of window/FFT size:
-        // the peak bin of a windowed sine has magnitude A * sum(w) / 2.
-        let win_sum: f32 = window.iter().sum();
-        let mag_scale = 2.0 / win_sum.max(f32::EPSILON);
+        // the peak bin of a windowed sine has magnitude A * sum(w) / 2. A
+        // multitaper mean power has the RMS of the tapers' sums in its place.
+        let (win_sum, sq) = taper_gains(&tapers);
+        let win_sum = match &tapers[..] {
+            [w] => w.iter().sum::<f32>(),
+            _ => win_sum as f32,
+        };
+        let (mag_scale, dc_scale) = match self.units {
+            SpectrumUnits::Dbfs => {
+                let s = 2.0 / win_sum.max(f32::EPSILON);
+                (s, s * 0.5)
+            }
+            // Density: divide the tone power by the equivalent noise
+            // bandwidth, fs * sum(w^2) / sum(w)^2. The one-sided DC bin is
+            // not doubled, so it reads 3 dB under a white floor.
+            SpectrumUnits::Psd | SpectrumUnits::Asd => {
+                let s = (2.0 / (self.sample_rate as f64 * sq).max(f64::EPSILON).sqrt()) as f32;
+                (s, s * std::f32::consts::FRAC_1_SQRT_2)
+            }
+        };
+        // Real rows hold bins 0..N/2; complex rows all N, DC in the middle
+        let weights = if self.weighting == Weighting::Z {
+            Vec::new()
+        } else if let (Some(k), Some(spec)) = (&cqt, self.cqt) {
+            (0..k.len()).map(|i| self.weighting.gain_db(spec.freq(i as f32) as f64) as f32).collect()
+        } else {
+            let df = self.sample_rate as f64 / self.fft_size as f64;
+            (0..self.fft_size)
+                .map(|k| self.weighting.gain_db(k.abs_diff(self.fft_size / 2) as f64 * df) as f32)
+                .collect()
+        };
+        let window = tapers[0].clone();
+        let hop = self.hop.min(self.frame_len).max(1);
+        let reassign = (self.reassign && cqt.is_none())
+            .then(|| Reassigner::new(&window, self.fft_size, hop, self.units == SpectrumUnits::Dbfs));
         Spectrogram {
             fft_size: self.fft_size,
             frame_len: self.frame_len,
-            hop: self.hop.min(self.frame_len).max(1),
+            hop,
             db_floor: self.db_floor,
             window,
             mag_scale,
+            dc_scale,
             tmp: vec![Complex32::new(0.0, 0.0); self.fft_size],
             fft,
             overlap_buf: Vec::new(),
+            overlap_im: Vec::new(),
             alpha: self.alpha,
             pre_emph: self.pre_emph,
             prev_sample: 0.0,
+            prev_im: 0.0,
             clamp_floor: self.clamp_floor,
             normalize: self.normalize,
+            offset_db: self.offset_db,
+            weights,
+            cqt,
+            reassign,
+            tapers: if tapers.len() > 1 { tapers } else { Vec::new() },
+            taper_buf: vec![Complex32::new(0.0, 0.0); self.fft_size],
         }
     }
 }
 
 impl Spectrogram {
+    /// Samples per analysis frame (set by the constant-Q layout when on).
+    pub fn frame_len(&self) -> usize {
+        self.frame_len
+    }
+
     pub fn process_samples(&mut self, samples: &[f32]) -> Vec<Vec<f32>> {
-        // Ingest input with optional pre-emphasis
-        if let Some(beta) = self.pre_emph {
-            for &x in samples {
-                let y = x - beta * self.prev_sample;
-                self.prev_sample = x;
-                self.overlap_buf.push(y);
-            }
-        } else {
-            self.overlap_buf.extend_from_slice(samples);
-        }
+        ingest(samples, self.pre_emph, &mut self.prev_sample, &mut self.overlap_buf);
+        self.drain_frames(false)
+    }
+
+    /// Complex (I/Q) input: `re` and `im` are the in-phase and quadrature
+    /// streams. Rows hold all `fft_size` bins, fft-shifted so index
+    /// `fft_size / 2` is DC and the row spans -fs/2..+fs/2. Each bin carries
+    /// half the real-input scale, so a full-scale complex exponential reads
+    /// ~0 dBFS in its single bin with no mirror image.
+    pub fn process_complex(&mut self, re: &[f32], im: &[f32]) -> Vec<Vec<f32>> {
+        let n = re.len().min(im.len());
+        ingest(&re[..n], self.pre_emph, &mut self.prev_sample, &mut self.overlap_buf);
+        ingest(&im[..n], self.pre_emph, &mut self.prev_im, &mut self.overlap_im);
+        self.drain_frames(true)
+    }
 
+    fn drain_frames(&mut self, complex: bool) -> Vec<Vec<f32>> {
         let mut out = Vec::new();
         while self.overlap_buf.len() >= self.frame_len {
             let frame = &self.overlap_buf[..self.frame_len];
@@ -95,44 +366,353 @@ impl Spectrogram {
                 t.re = x * w;
                 t.im = 0.0;
             }
+            if complex {
+                let frame_im = &self.overlap_im[..self.frame_len];
+                for (t, (&x, &w)) in self.tmp.iter_mut().zip(frame_im.iter().zip(self.window.iter())) {
+                    t.im = x * w;
+                }
+            }
             for t in self.tmp.iter_mut().skip(self.frame_len) {
                 t.re = 0.0;
                 t.im = 0.0;
             }
             self.fft.process(&mut self.tmp);
+            // Multitaper: mean power over the tapers, the first already in tmp
+            let power = (!self.tapers.is_empty()).then(|| {
+                let im = complex.then(|| &self.overlap_im[..self.frame_len]);
+                taper_power(&self.tapers, &mut self.taper_buf, frame, im, &self.tmp, &*self.fft)
+            });
 
-            // First N/2 bins to dB (magnitude or power)
-            let n_bins = self.fft_size / 2;
-            let mut row = vec![0.0f32; n_bins];
-            for (i, (v, c)) in row.iter_mut().zip(self.tmp.iter().take(n_bins)).enumerate() {
-                // DC has no two-sided split, so the sine calibration factor
-                // (2/sum(w)) would read it +6 dB high; use 1/sum(w) there.
-                let scale = if i == 0 { self.mag_scale * 0.5 } else { self.mag_scale };
-                let re2 = c.re * c.re; let im2 = c.im * c.im;
-                if self.alpha == 2 {
-                    let p = ((re2 + im2) * scale * scale).max(1e-24);
-                    *v = 10.0 * p.log10();
-                } else {
-                    let m = ((re2 + im2).sqrt() * scale).max(1e-12);
-                    *v = 20.0 * m.log10();
-                }
-            }
-            if self.normalize {
-                if let Some(&mx) = row.iter().max_by(|a,b| a.partial_cmp(b).unwrap()).filter(|_| !row.is_empty()) {
-                    for v in &mut row { *v -= mx; }
+            let row = if let Some(k) = self.cqt.as_ref().filter(|_| !complex) {
+                // Kernels carry their own calibration
+                Some(k.apply(&self.tmp).into_iter().map(|c| self.bin_db(c, 1.0)).collect())
+            } else if complex {
+                // All N bins, negative frequencies first
+                let n = self.fft_size;
+                let scale = self.mag_scale * 0.5;
+                Some((0..n).map(|j| self.level(power.as_deref(), (j + n / 2) % n, scale)).collect::<Vec<f32>>())
+            } else if let Some(r) = self.reassign.as_mut() {
+                // The oldest frame no later frame can add to, if any yet
+                r.frame(frame, &self.tmp, &*self.fft, self.mag_scale, self.dc_scale)
+            } else {
+                // First N/2 bins to dB (magnitude or power)
+                let n_bins = self.fft_size / 2;
+                let mut row = vec![0.0f32; n_bins];
+                for (i, v) in row.iter_mut().enumerate() {
+                    // DC has no two-sided split, so the sine calibration factor
+                    // (2/sum(w)) would read it +6 dB high; use 1/sum(w) there.
+                    let scale = if i == 0 { self.dc_scale } else { self.mag_scale };
+                    *v = self.level(power.as_deref(), i, scale);
                 }
+                Some(row)
+            };
+            if let Some(row) = row {
+                out.push(self.finish_row(row, complex));
             }
-            if self.clamp_floor {
-                for v in &mut row { if *v < self.db_floor { *v = self.db_floor; } }
-            }
-            out.push(row);
 
             // Advance by hop
             let hop = self.hop.min(self.overlap_buf.len());
             self.overlap_buf.drain(0..hop);
+            if complex {
+                self.overlap_im.drain(0..hop);
+            }
         }
         out
     }
+
+    /// Weighting, calibration offset, normalization and floor clamp.
+    fn finish_row(&self, mut row: Vec<f32>, complex: bool) -> Vec<f32> {
+        if !self.weights.is_empty() {
+            // Complex and constant-Q rows are weighted from their first
+            // bin; real FFT rows start at DC, halfway into the table
+            let start = if complex || self.cqt.is_some() { 0 } else { self.fft_size / 2 };
+            for (v, w) in row.iter_mut().zip(&self.weights[start..]) { *v += w; }
+        }
+        if self.offset_db != 0.0 {
+            for v in &mut row { *v += self.offset_db; }
+        }
+        if self.normalize {
+            if let Some(&mx) = row.iter().max_by(|a,b| a.partial_cmp(b).unwrap()).filter(|_| !row.is_empty()) {
+                for v in &mut row { *v -= mx; }
+            }
+        }
+        if self.clamp_floor {
+            for v in &mut row { if *v < self.db_floor { *v = self.db_floor; } }
+        }
+        row
+    }
+
+    fn bin_db(&self, c: Complex32, scale: f32) -> f32 {
+        let re2 = c.re * c.re; let im2 = c.im * c.im;
+        if self.alpha == 2 {
+            self.power_db(re2 + im2, scale)
+        } else {
+            let m = ((re2 + im2).sqrt() * scale).max(1e-12);
+            20.0 * m.log10()
+        }
+    }
+
+    /// dB of bin `j`: the multitaper mean `power` when given, otherwise
+    /// the spectrum in `tmp`
+    fn level(&self, power: Option<&[f32]>, j: usize, scale: f32) -> f32 {
+        match power {
+            Some(p) => self.power_db(p[j], scale),
+            None => self.bin_db(self.tmp[j], scale),
+        }
+    }
+
+    /// dB of a bin power (a multitaper mean); magnitude or power dB agree
+    fn power_db(&self, p: f32, scale: f32) -> f32 {
+        10.0 * (p * scale * scale).max(1e-24).log10()
+    }
+}
+
+/// Mean power per bin of a frame over a set of tapers, given the spectrum
+/// of the frame under the first one.
+fn taper_power(
+    tapers: &[Vec<f32>],
+    buf: &mut [Complex32],
+    re: &[f32],
+    im: Option<&[f32]>,
+    first: &[Complex32],
+    fft: &dyn rustfft::Fft<f32>,
+) -> Vec<f32> {
+    let mut power: Vec<f32> = first.iter().map(|c| c.norm_sqr()).collect();
+    for taper in &tapers[1..] {
+        buf.fill(Complex32::new(0.0, 0.0));
+        for (i, (c, &w)) in buf.iter_mut().zip(taper).enumerate() {
+            c.re = re[i] * w;
+            if let Some(im) = im {
+                c.im = im[i] * w;
+            }
+        }
+        fft.process(buf);
+        for (p, c) in power.iter_mut().zip(buf.iter()) {
+            *p += c.norm_sqr();
+        }
+    }
+    let k = tapers.len() as f32;
+    power.iter_mut().for_each(|p| *p /= k);
+    power
+}
+
+/// Time-frequency reassignment (Auger & Flandrin, 1995). A bin's power
+/// belongs at the center of gravity of the energy it picked up, not at the
+/// bin center and frame center: two more FFTs of the frame, with a
+/// time-ramped window `t w(t)` and a differentiated window `w'(t)`, give
+/// the offsets, and the power is summed into the bin and frame it lands
+/// on. Ridges of tones, chirps and clicks come out about one bin and one
+/// hop wide instead of a main lobe by a frame.
+///
+/// Power can move up to half a frame either way, so frames are held until
+/// no later frame can add to them: rows come out `delay` frames late, and
+/// the first row is still the first frame.
+struct Reassigner {
+    /// `(n - L/2) w[n]`: time from the frame center in samples
+    ramp: Vec<f32>,
+    /// `dw/dn` by central difference (the windows are periodic)
+    deriv: Vec<f32>,
+    timed: Vec<Complex32>,
+    diffed: Vec<Complex32>,
+    hop: usize,
+    delay: usize,
+    /// Power rows of frames `current - delay ..= current + delay`
+    pending: VecDeque<Vec<f32>>,
+    /// Leading pending rows that stand for frames before the first one
+    warmup: usize,
+    /// dBFS rows: a tone's main lobe sums to its level times the noise
+    /// bandwidth in bins, all of which lands in one bin, so divide it out.
+    /// Density rows keep their sum.
+    gain: f32,
+}
+
+impl Reassigner {
+    fn new(window: &[f32], fft_size: usize, hop: usize, tone_units: bool) -> Self {
+        let len = window.len();
+        let center = len as f32 / 2.0;
+        let ramp = window.iter().enumerate().map(|(i, &w)| (i as f32 - center) * w).collect();
+        let deriv = (0..len).map(|i| (window[(i + 1) % len] - window[(i + len - 1) % len]) / 2.0).collect();
+        let delay = (len / 2).div_ceil(hop);
+        let bins = fft_size / 2;
+        let sum: f64 = window.iter().map(|&w| w as f64).sum();
+        let sq: f64 = window.iter().map(|&w| (w as f64) * (w as f64)).sum();
+        let enbw = fft_size as f64 * sq / (sum * sum).max(f64::EPSILON);
+        Self {
+            ramp,
+            deriv,
+            timed: vec![Complex32::new(0.0, 0.0); fft_size],
+            diffed: vec![Complex32::new(0.0, 0.0); fft_size],
+            hop,
+            delay,
+            pending: (0..2 * delay).map(|_| vec![0.0; bins]).collect(),
+            warmup: delay,
+            gain: if tone_units { (1.0 / enbw) as f32 } else { 1.0 },
+        }
+    }
+
+    /// Reassign one frame, given its windowed `spectrum`, and return the dB
+    /// row of the oldest frame that is now complete.
+    fn frame(
+        &mut self,
+        frame: &[f32],
+        spectrum: &[Complex32],
+        fft: &dyn rustfft::Fft<f32>,
+        scale: f32,
+        dc_scale: f32,
+    ) -> Option<Vec<f32>> {
+        for (buf, win) in [(&mut self.timed, &self.ramp), (&mut self.diffed, &self.deriv)] {
+            buf.fill(Complex32::new(0.0, 0.0));
+            for (c, (&x, &w)) in buf.iter_mut().zip(frame.iter().zip(win)) {
+                c.re = x * w;
+            }
+            fft.process(buf);
+        }
+        let n = spectrum.len();
+        let bins = n / 2;
+        let d = self.delay as f32;
+        self.pending.push_back(vec![0.0; bins]);
+        for (k, &x) in spectrum.iter().take(bins).enumerate() {
+            let p = x.norm_sqr();
+            if p <= f32::MIN_POSITIVE {
+                continue;
+            }
+            // Frequency offset in bins and time offset in samples
+            let dk = -(self.diffed[k] * x.conj()).im / p * n as f32 / std::f32::consts::TAU;
+            let dt = (self.timed[k] * x.conj()).re / p;
+            let to = (k as f32 + dk).round();
+            if !(0.0..bins as f32).contains(&to) || !dt.is_finite() {
+                continue;
+            }
+            let frame = (dt / self.hop as f32).round().clamp(-d, d) + d;
+            let s = if k == 0 { dc_scale } else { scale };
+            self.pending[frame as usize][to as usize] += p * s * s * self.gain;
+        }
+        let row = self.pending.pop_front()?;
+        if self.warmup > 0 {
+            self.warmup -= 1;
+            return None;
+        }
+        Some(row.into_iter().map(|p| 10.0 * p.max(1e-24).log10()).collect())
+    }
+}
+
+/// Append `samples` to `buf`, applying optional pre-emphasis.
+fn ingest(samples: &[f32], pre_emph: Option<f32>, prev: &mut f32, buf: &mut Vec<f32>) {
+    if let Some(beta) = pre_emph {
+        for &x in samples {
+            buf.push(x - beta * *prev);
+            *prev = x;
+        }
+    } else {
+        buf.extend_from_slice(samples);
+    }
+}
+
+/// Coefficients of an `n`-point analysis window. All windows are periodic
+/// (DFT-even), like the cosine windows: the symmetric n+1 point window with
+/// its last point dropped.
+pub fn window_coeffs(kind: WindowType, n: usize) -> Vec<f32> {
+    match kind {
+        WindowType::Hann => hann(n),
+        WindowType::Hamming => hamming(n),
+        WindowType::Blackman => blackman(n),
+        WindowType::BlackmanHarris => cosine_sum(n, &[0.35875, 0.48829, 0.14128, 0.01168]),
+        WindowType::FlatTop => cosine_sum(n, &[0.21557895, 0.41663158, 0.277263158, 0.083578947, 0.006947368]),
+        WindowType::Kaiser(beta) => kaiser(n, beta as f64),
+        WindowType::Gaussian(sigma) => gaussian(n, sigma as f64),
+        WindowType::Tukey(alpha) => tukey(n, alpha as f64),
+        WindowType::Chebyshev(atten) => chebyshev(n, atten as f64),
+    }
+}
+
+/// The windows an analyzer applies: `kind`, or the DPSS set when
+/// multitaper estimation is on.
+pub fn analysis_windows(kind: WindowType, multitaper: Option<Multitaper>, n: usize) -> Vec<Vec<f32>> {
+    match multitaper {
+        Some(mt) => dpss(n, mt.nw as f64, mt.tapers),
+        None => vec![window_coeffs(kind, n)],
+    }
+}
+
+/// Coherent gain and energy of a set of tapers whose powers are averaged:
+/// sqrt(mean(sum(w)^2)) and mean(sum(w^2)); exactly sum(w) and sum(w^2)
+/// for a single window.
+fn taper_gains(tapers: &[Vec<f32>]) -> (f64, f64) {
+    let gains: Vec<(f64, f64)> = tapers
+        .iter()
+        .map(|w| (w.iter().map(|&v| v as f64).sum(), w.iter().map(|&v| (v as f64) * (v as f64)).sum()))
+        .collect();
+    match gains[..] {
+        [one] => one,
+        _ => {
+            let k = gains.len().max(1) as f64;
+            let coherent: f64 = gains.iter().map(|(s, _)| s * s).sum();
+            let energy: f64 = gains.iter().map(|(_, e)| e).sum();
+            ((coherent / k).sqrt(), energy / k)
+        }
+    }
+}
+
+/// Equivalent noise bandwidth of a window in `fft_size` bins:
+/// N * sum(w^2) / sum(w)^2, or its mean over a multitaper set (see
+/// `analysis_windows`). Summing the bin powers of a sine over its main lobe
+/// gives its level times this, so band sums divide it back out.
+pub fn enbw_bins(tapers: &[Vec<f32>], fft_size: usize) -> f32 {
+    let (sum, sq) = taper_gains(tapers);
+    let n = fft_size.max(tapers.first().map_or(1, Vec::len));
+    (n as f64 * sq / (sum * sum).max(f64::EPSILON)) as f32
+}
+
+/// Scalloping loss of a window: how far a tone's peak bin reads below its
+/// true level when the tone sits `delta` bins off the bin center. Tabulated
+/// once over 0..=0.5 bins (the loss is symmetric) from the window's
+/// transform, zero padding included, so peak interpolation can add it back.
+#[derive(Clone, Debug)]
+pub struct Scalloping {
+    /// Gain in dB relative to an on-bin tone at delta = i / STEPS
+    db: Vec<f32>,
+}
+
+impl Scalloping {
+    const STEPS: usize = 64;
+
+    pub fn new(kind: WindowType, frame_len: usize, fft_size: usize) -> Self {
+        Self::from_tapers(&[window_coeffs(kind, frame_len.max(1))], fft_size)
+    }
+
+    /// Loss of the mean power over a taper set (see `analysis_windows`).
+    pub fn from_tapers(tapers: &[Vec<f32>], fft_size: usize) -> Self {
+        let (sum, _) = taper_gains(tapers);
+        let n = fft_size.max(tapers.first().map_or(1, Vec::len)) as f64;
+        let db = (0..=Self::STEPS / 2)
+            .map(|i| {
+                let delta = i as f64 / Self::STEPS as f64;
+                let power: f64 = tapers
+                    .iter()
+                    .map(|w| {
+                        let (mut re, mut im) = (0.0f64, 0.0f64);
+                        for (k, &v) in w.iter().enumerate() {
+                            let ph = -2.0 * std::f64::consts::PI * delta * k as f64 / n;
+                            re += v as f64 * ph.cos();
+                            im += v as f64 * ph.sin();
+                        }
+                        re * re + im * im
+                    })
+                    .sum::<f64>()
+                    / tapers.len().max(1) as f64;
+                (10.0 * (power / (sum * sum).max(f64::EPSILON)).max(1e-24).log10()) as f32
+            })
+            .collect();
+        Self { db }
+    }
+
+    /// Gain (<= 0 dB) of a tone `delta` bins from the bin center, |delta| <= 0.5.
+    pub fn gain_db(&self, delta: f32) -> f32 {
+        let x = (delta.abs().min(0.5) * Self::STEPS as f32).min((self.db.len() - 1) as f32);
+        let i = (x as usize).min(self.db.len() - 2);
+        let t = x - i as f32;
+        self.db[i] + (self.db[i + 1] - self.db[i]) * t
+    }
 }
 
 fn hann(n: usize) -> Vec<f32> {
@@ -162,6 +742,199 @@ fn blackman(n: usize) -> Vec<f32> {
         .collect()
 }
 
+/// Generalized cosine window: sum of (-1)^k a_k cos(2πki/n).
+fn cosine_sum(n: usize, a: &[f64]) -> Vec<f32> {
+    (0..n)
+        .map(|i| {
+            let x = 2.0 * std::f64::consts::PI * i as f64 / n as f64;
+            a.iter()
+                .enumerate()
+                .map(|(k, &ak)| if k.is_multiple_of(2) { ak } else { -ak } * (k as f64 * x).cos())
+                .sum::<f64>() as f32
+        })
+        .collect()
+}
+
+fn kaiser(n: usize, beta: f64) -> Vec<f32> {
+    let norm = bessel_i0(beta);
+    (0..n)
+        .map(|i| {
+            let x = 2.0 * i as f64 / n as f64 - 1.0;
+            (bessel_i0(beta * (1.0 - x * x).max(0.0).sqrt()) / norm) as f32
+        })
+        .collect()
+}
+
+fn gaussian(n: usize, sigma: f64) -> Vec<f32> {
+    let half = n as f64 / 2.0;
+    (0..n)
+        .map(|i| {
+            let x = (i as f64 - half) / (sigma * half);
+            (-0.5 * x * x).exp() as f32
+        })
+        .collect()
+}
+
+fn tukey(n: usize, alpha: f64) -> Vec<f32> {
+    (0..n)
+        .map(|i| {
+            let x = i as f64 / n as f64;
+            let edge = x.min(1.0 - x);
+            if alpha <= 0.0 || edge >= alpha / 2.0 {
+                1.0
+            } else {
+                (0.5 - 0.5 * (2.0 * std::f64::consts::PI * edge / alpha).cos()) as f32
+            }
+        })
+        .collect()
+}
+
+/// Dolph-Chebyshev window, built from its frequency response (a Chebyshev
+/// polynomial with equiripple sidelobes `atten_db` down) by inverse DFT.
+fn chebyshev(n: usize, atten_db: f64) -> Vec<f32> {
+    if n < 2 {
+        return vec![1.0; n];
+    }
+    // Symmetric window of n+1 points, trimmed to periodic
+    let m = n + 1;
+    let order = (m - 1) as f64;
+    let even = m.is_multiple_of(2);
+    let x0 = ((10f64.powf(atten_db / 20.0)).acosh() / order).cosh();
+    let mut p: Vec<Complex<f64>> = (0..m)
+        .map(|k| {
+            let x = x0 * (std::f64::consts::PI * k as f64 / m as f64).cos();
+            let t = if x > 1.0 {
+                (order * x.acosh()).cosh()
+            } else if x < -1.0 {
+                let sign = if even { -1.0 } else { 1.0 };
+                sign * (order * (-x).acosh()).cosh()
+            } else {
+                (order * x.acos()).cos()
+            };
+            // Even lengths need a half-sample shift to stay real and symmetric
+            let shift = if even { std::f64::consts::PI * k as f64 / m as f64 } else { 0.0 };
+            Complex::from_polar(t, shift)
+        })
+        .collect();
+    FftPlanner::<f64>::new().plan_fft_forward(m).process(&mut p);
+    let w: Vec<f64> = if even {
+        let h = m / 2 + 1;
+        p[1..h].iter().rev().chain(&p[1..h]).map(|c| c.re).collect()
+    } else {
+        let h = m.div_ceil(2);
+        p[1..h].iter().rev().chain(&p[..h]).map(|c| c.re).collect()
+    };
+    let max = w.iter().cloned().fold(f64::MIN, f64::max);
+    w[..n].iter().map(|&v| (v / max) as f32).collect()
+}
+
+/// The `k` discrete prolate spheroidal (Slepian) sequences of length `n`
+/// with the most energy inside ±`nw`/`n` cycles per sample, best first,
+/// each of unit energy. They are the top eigenvectors of a symmetric
+/// tridiagonal matrix (Percival & Walden, 1993, ch. 8): eigenvalues by
+/// Sturm-sequence bisection, then three inverse-iteration solves each.
+/// Even tapers sum positive, odd ones start positive.
+pub fn dpss(n: usize, nw: f64, k: usize) -> Vec<Vec<f32>> {
+    if n < 2 {
+        return vec![vec![1.0; n]; k.max(1)];
+    }
+    let w = (nw / n as f64).min(0.5);
+    let c = (2.0 * std::f64::consts::PI * w).cos();
+    let diag: Vec<f64> = (0..n).map(|i| ((n - 1) as f64 / 2.0 - i as f64).powi(2) * c).collect();
+    let off: Vec<f64> = (1..n).map(|i| (i * (n - i)) as f64 / 2.0).collect();
+    // Eigenvalues below x: negative pivots of T - xI
+    let below = |x: f64| {
+        let mut q = diag[0] - x;
+        let mut count = (q < 0.0) as usize;
+        for i in 1..n {
+            let q_prev = if q == 0.0 { f64::EPSILON * off[i - 1].abs().max(1.0) } else { q };
+            q = diag[i] - x - off[i - 1] * off[i - 1] / q_prev;
+            count += (q < 0.0) as usize;
+        }
+        count
+    };
+    // Gershgorin bounds
+    let radius = |i: usize| if i > 0 { off[i - 1] } else { 0.0 } + off.get(i).copied().unwrap_or(0.0);
+    let lo = (0..n).map(|i| diag[i] - radius(i)).fold(f64::INFINITY, f64::min);
+    let hi = (0..n).map(|i| diag[i] + radius(i)).fold(f64::NEG_INFINITY, f64::max);
+    let tol = f64::EPSILON * lo.abs().max(hi.abs());
+    (0..k.clamp(1, n))
+        .map(|j| {
+            // The j-th largest eigenvalue is index n-1-j ascending
+            let m = n - 1 - j;
+            let (mut a, mut b) = (lo, hi);
+            while b - a > 2.0 * tol {
+                let mid = 0.5 * (a + b);
+                if below(mid) > m { b = mid } else { a = mid }
+            }
+            let lambda = 0.5 * (a + b);
+            // Asymmetric start, so odd tapers are reached too
+            let mut v: Vec<f64> = (0..n).map(|i| 1.0 + i as f64 / n as f64).collect();
+            for _ in 0..3 {
+                v = solve_shifted_tridiagonal(&diag, &off, lambda, &v, tol);
+                let norm = v.iter().map(|x| x * x).sum::<f64>().sqrt();
+                v.iter_mut().for_each(|x| *x /= norm);
+            }
+            let sign = if j % 2 == 0 { v.iter().sum::<f64>() } else { v.iter().enumerate().map(|(i, x)| ((n - 1) as f64 / 2.0 - i as f64) * x).sum() };
+            let sign = if sign < 0.0 { -1.0 } else { 1.0 };
+            v.iter().map(|&x| (sign * x) as f32).collect()
+        })
+        .collect()
+}
+
+/// Solve (T - λI) x = b for the symmetric tridiagonal T (diagonal `diag`,
+/// off-diagonal `off`) by LU with partial pivoting; pivots under `tiny`
+/// are nudged to it, as inverse iteration wants.
+fn solve_shifted_tridiagonal(diag: &[f64], off: &[f64], lambda: f64, b: &[f64], tiny: f64) -> Vec<f64> {
+    let n = diag.len();
+    let mut d: Vec<f64> = diag.iter().map(|x| x - lambda).collect();
+    let mut du = off.to_vec();
+    let mut dl = off.to_vec();
+    let mut du2 = vec![0.0; n.saturating_sub(2)];
+    let mut swapped = vec![false; n - 1];
+    let nudge = |x: f64| if x.abs() < tiny { tiny.copysign(x) } else { x };
+    for i in 0..n - 1 {
+        if d[i].abs() >= dl[i].abs() {
+            let f = dl[i] / nudge(d[i]);
+            dl[i] = f;
+            d[i + 1] -= f * du[i];
+        } else {
+            let f = d[i] / dl[i];
+            d[i] = dl[i];
+            dl[i] = f;
+            let t = du[i];
+            du[i] = d[i + 1];
+            d[i + 1] = t - f * d[i + 1];
+            if i + 2 < n {
+                du2[i] = du[i + 1];
+                du[i + 1] *= -f;
+            }
+            swapped[i] = true;
+        }
+    }
+    let mut x = b.to_vec();
+    for i in 0..n - 1 {
+        if swapped[i] {
+            let t = x[i];
+            x[i] = x[i + 1];
+            x[i + 1] = t - dl[i] * x[i];
+        } else {
+            x[i + 1] -= dl[i] * x[i];
+        }
+    }
+    for i in (0..n).rev() {
+        let mut r = x[i];
+        if i + 1 < n {
+            r -= du[i] * x[i + 1];
+        }
+        if i + 2 < n {
+            r -= du2[i] * x[i + 2];
+        }
+        x[i] = r / nudge(d[i]);
+    }
+    x
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -194,6 +967,41 @@ mod tests {
         assert!(max_idx >= k.saturating_sub(1) && max_idx <= k + 1, "peak {} not near {}", max_idx, k);
     }
 
+    #[test]
+    fn reassignment_gathers_an_off_bin_tone_into_one_bin() {
+        let (fs, n) = (48_000u32, 1024usize);
+        let f0 = fs as f32 * 20.4 / n as f32;
+        let x: Vec<f32> = (0..8 * n).map(|i| (std::f32::consts::TAU * f0 * i as f32 / fs as f32).sin()).collect();
+        let row = |reassign| {
+            let mut spec = SpectrogramBuilder::new(n, n, n / 4).sample_rate(fs).reassign(reassign).build();
+            spec.process_samples(&x).pop().unwrap()
+        };
+        let (plain, sharp) = (row(false), row(true));
+        // Two bins out is still inside the Hann main lobe
+        assert!(plain[22] > -20.0, "{}", plain[22]);
+        assert_eq!(crate::view::max_bin_in(&sharp, 0, sharp.len()).0, 20);
+        // All of it lands in one bin, at the tone's level
+        assert!(sharp[20].abs() < 0.3, "{}", sharp[20]);
+        assert!(sharp[18] < -60.0 && sharp[22] < -60.0, "{} {}", sharp[18], sharp[22]);
+    }
+
+    #[test]
+    fn reassignment_moves_a_click_into_its_frame() {
+        let n = 1024usize;
+        let mut spec = SpectrogramBuilder::new(n, n, n / 4).reassign(true).build();
+        // Centered in frame 3; frames 2 and 4 see it half a window off
+        let mut x = vec![0.0f32; 4 * n];
+        x[n / 2 + 3 * n / 4] = 1.0;
+        let rows = spec.process_samples(&x);
+        // 13 frames, the last two still open to later frames
+        assert_eq!(rows.len(), 11);
+        let energy = |r: &Vec<f32>| r.iter().map(|v| 10f32.powf(v / 10.0)).sum::<f32>();
+        let click = energy(&rows[3]);
+        for i in [2, 4, 5] {
+            assert!(energy(&rows[i]) < click * 1e-6, "frame {i}");
+        }
+    }
+
     #[test]
     fn full_scale_dc_reads_zero_dbfs() {
         let n = 1024usize;
@@ -204,10 +1012,24 @@ mod tests {
         assert!(dc.abs() < 0.5, "full-scale DC read {dc:.2} dB, expected ~0 dBFS");
     }
 
+    const ALL_WINDOWS: [WindowType; 9] = [
+        WindowType::Hann,
+        WindowType::Hamming,
+        WindowType::Blackman,
+        WindowType::BlackmanHarris,
+        WindowType::FlatTop,
+        WindowType::Kaiser(9.0),
+        WindowType::Gaussian(0.4),
+        WindowType::Tukey(0.5),
+        WindowType::Chebyshev(100.0),
+    ];
+
     #[test]
     fn full_scale_sine_reads_zero_dbfs() {
-        // Calibration must hold across different FFT sizes and windows
-        for &(n, w) in &[(1024usize, WindowType::Hann), (2048, WindowType::Hamming), (4096, WindowType::Blackman)] {
+        // Calibration must hold across different FFT sizes and every window
+        let sizes = [1024usize, 2048, 4096];
+        for (i, &w) in ALL_WINDOWS.iter().enumerate() {
+            let n = sizes[i % sizes.len()];
             let fs = 48_000u32;
             let k = n / 8; // bin-centered frequency, away from DC/Nyquist
             let f0 = (fs as f32) * (k as f32) / (n as f32);
@@ -215,8 +1037,238 @@ mod tests {
             let x: Vec<f32> = (0..n).map(|i| (2.0 * std::f32::consts::PI * f0 * (i as f32) / (fs as f32)).sin()).collect();
             let rows = spec.process_samples(&x);
             let peak = rows[0].iter().cloned().fold(f32::NEG_INFINITY, f32::max);
-            assert!(peak.abs() < 0.5, "N={n}: full-scale sine peak {peak:.2} dB, expected ~0 dBFS");
+            assert!(peak.abs() < 0.5, "N={n} {w:?}: full-scale sine peak {peak:.2} dB, expected ~0 dBFS");
+        }
+    }
+
+    #[test]
+    fn window_shapes_match_their_limits() {
+        let n = 256;
+        let close = |a: &[f32], b: &[f32]| a.iter().zip(b).all(|(x, y)| (x - y).abs() < 1e-5);
+        assert!(window_coeffs(WindowType::Kaiser(0.0), n).iter().all(|&v| (v - 1.0).abs() < 1e-6));
+        assert!(close(&window_coeffs(WindowType::Tukey(1.0), n), &hann(n)));
+        assert!(window_coeffs(WindowType::Tukey(0.0), n).iter().all(|&v| v == 1.0));
+        for &w in &ALL_WINDOWS {
+            let c = window_coeffs(w, n);
+            assert_eq!(c.len(), n);
+            // Periodic: symmetric about n/2, peaking there
+            assert!((1..n / 2).all(|i| (c[i] - c[n - i]).abs() < 1e-4), "{w:?} not symmetric");
+            assert!((c[n / 2] - c.iter().cloned().fold(f32::MIN, f32::max)).abs() < 1e-6, "{w:?} peak off center");
+        }
+    }
+
+    #[test]
+    fn low_sidelobe_windows_bury_a_tone_far_from_the_bin() {
+        // A full-scale tone, read 20 bins away: the window's sidelobe level
+        let n = 1024usize;
+        for &(w, max_db) in &[
+            (WindowType::Hann, -60.0f32),
+            (WindowType::BlackmanHarris, -90.0),
+            (WindowType::Kaiser(12.0), -90.0),
+            (WindowType::Chebyshev(100.0), -99.0),
+        ] {
+            let x: Vec<f32> = (0..n).map(|i| (2.0 * std::f64::consts::PI * 100.5 * i as f64 / n as f64).sin() as f32).collect();
+            let row = &SpectrogramBuilder::new(n, n, n).window(w).build().process_samples(&x)[0];
+            let far = row[120..].iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+            assert!(far < max_db, "{w:?}: leakage {far:.1} dB 20 bins out");
+        }
+    }
+
+    #[test]
+    fn white_noise_density_is_independent_of_fft_size_and_window() {
+        // -20 dB RMS white noise: one-sided density 2 * 0.01 / (fs/2) re a
+        // full-scale sine's power of 1/2, i.e. 4 * 0.01 / fs
+        let fs = 48_000u32;
+        let mut g = crate::gen::Generator::new(crate::gen::GenSpec::parse("gen:white").unwrap(), fs);
+        let mut x = vec![0.0f32; 4 * fs as usize];
+        g.fill(&mut x);
+        let expect = 10.0 * (4.0 * 0.01 / fs as f64).log10();
+        for &(n, l, w) in &[
+            (256usize, 256usize, WindowType::Hann),
+            (4096, 4096, WindowType::BlackmanHarris),
+            (2048, 1024, WindowType::FlatTop),
+            (1024, 1024, WindowType::Kaiser(9.0)),
+        ] {
+            for units in [SpectrumUnits::Psd, SpectrumUnits::Asd] {
+                let mut spec = SpectrogramBuilder::new(n, l, l / 2).window(w).units(units).sample_rate(fs).build();
+                let rows = spec.process_samples(&x);
+                // Average power, not dB: the mean of log-exponential bins is biased low
+                let (sum, count) = rows
+                    .iter()
+                    .flat_map(|r| &r[1..])
+                    .fold((0.0f64, 0usize), |(s, c), &v| (s + 10f64.powf(v as f64 / 10.0), c + 1));
+                let db = 10.0 * (sum / count as f64).log10();
+                assert!((db - expect).abs() < 0.3, "N={n} L={l} {w:?} {units:?}: floor {db:.2}, expected {expect:.2}");
+            }
+        }
+        // Tone units put the same floor wherever the bin width puts it
+        let floor = |n: usize| {
+            let rows = SpectrogramBuilder::new(n, n, n).build().process_samples(&x);
+            let p: f64 = rows.iter().flat_map(|r| &r[1..]).map(|&v| 10f64.powf(v as f64 / 10.0)).sum();
+            10.0 * (p / (rows.len() * (n / 2 - 1)) as f64).log10()
+        };
+        assert!(floor(256) - floor(4096) > 11.0);
+    }
+
+    #[test]
+    fn dpss_tapers_are_orthonormal_and_concentrated() {
+        let (n, nw, k) = (256usize, 4.0f64, 7usize);
+        let tapers = dpss(n, nw, k);
+        assert_eq!(tapers.len(), k);
+        // Fraction of each taper's energy within ±nw/n cycles per sample
+        let w = nw / n as f64;
+        let concentration = |v: &[f32]| {
+            let mut e = 0.0f64;
+            for (i, &a) in v.iter().enumerate() {
+                for (j, &b) in v.iter().enumerate() {
+                    let d = i as f64 - j as f64;
+                    let kern = if d == 0.0 { 2.0 * w } else { (2.0 * std::f64::consts::PI * w * d).sin() / (std::f64::consts::PI * d) };
+                    e += a as f64 * b as f64 * kern;
+                }
+            }
+            e
+        };
+        let mut last = 1.0;
+        for (a, ta) in tapers.iter().enumerate() {
+            for (b, tb) in tapers.iter().enumerate() {
+                let dot: f64 = ta.iter().zip(tb).map(|(&x, &y)| x as f64 * y as f64).sum();
+                assert!((dot - (a == b) as u8 as f64).abs() < 1e-4, "<{a},{b}> = {dot}");
+            }
+            // Even tapers symmetric, odd ones antisymmetric
+            let parity = if a % 2 == 0 { 1.0 } else { -1.0 };
+            assert!(ta.iter().zip(ta.iter().rev()).all(|(&x, &y)| (x - parity * y).abs() < 1e-4), "taper {a}");
+            let c = concentration(ta);
+            assert!(c <= last + 1e-6 && c > 0.9, "taper {a}: {c}");
+            last = c;
+        }
+        assert!(concentration(&tapers[0]) > 0.99999);
+    }
+
+    #[test]
+    fn multitaper_sine_reads_full_scale_on_and_between_bins() {
+        let (fs, n) = (48_000.0f64, 1024usize);
+        let mt = Multitaper { nw: 4.0, tapers: 7 };
+        for k in [100.0f64, 100.25, 100.5] {
+            let f0 = fs * k / n as f64;
+            let x: Vec<f32> = (0..n).map(|i| (2.0 * std::f64::consts::PI * f0 * i as f64 / fs).sin() as f32).collect();
+            let row = &SpectrogramBuilder::new(n, n, n).multitaper(Some(mt)).build().process_samples(&x)[0];
+            let (bin, db) = crate::view::max_bin_in(row, 0, row.len());
+            // The taper average is flat across ±nw bins: the peak may be
+            // anywhere in there, but scalloping is negligible
+            assert!((bin as f64 - k).abs() <= mt.nw as f64, "+{k}: peak in bin {bin}");
+            assert!(db.abs() < 0.2, "+{k}: read {db:.2} dBFS");
         }
+        let scallop = Scalloping::from_tapers(&analysis_windows(WindowType::Hann, Some(mt), n), n);
+        let hann = Scalloping::new(WindowType::Hann, n, n);
+        assert!(scallop.gain_db(0.5).abs() < hann.gain_db(0.5).abs() / 5.0, "{}", scallop.gain_db(0.5));
+    }
+
+    #[test]
+    fn multitaper_noise_floor_reads_the_same_with_less_scatter() {
+        let fs = 48_000u32;
+        let mut g = crate::gen::Generator::new(crate::gen::GenSpec::parse("gen:white").unwrap(), fs);
+        let mut x = vec![0.0f32; fs as usize];
+        g.fill(&mut x);
+        let expect = 10.0 * (4.0 * 0.01 / fs as f64).log10();
+        let n = 1024usize;
+        // Mean power in dB, and the spread of the dB values across bins
+        let floor = |mt: Option<Multitaper>| {
+            let rows = SpectrogramBuilder::new(n, n, n).units(SpectrumUnits::Psd).sample_rate(fs).multitaper(mt).build().process_samples(&x);
+            let vals: Vec<f64> = rows.iter().flat_map(|r| &r[8..n / 2 - 8]).map(|&v| v as f64).collect();
+            let mean_p = vals.iter().map(|v| 10f64.powf(v / 10.0)).sum::<f64>() / vals.len() as f64;
+            let mean_db = vals.iter().sum::<f64>() / vals.len() as f64;
+            let sd = (vals.iter().map(|v| (v - mean_db).powi(2)).sum::<f64>() / vals.len() as f64).sqrt();
+            (10.0 * mean_p.log10(), sd)
+        };
+        let (single, single_sd) = floor(None);
+        let (multi, multi_sd) = floor(Some(Multitaper { nw: 4.0, tapers: 7 }));
+        assert!((single - expect).abs() < 0.3, "{single:.2} vs {expect:.2}");
+        assert!((multi - expect).abs() < 0.3, "{multi:.2} vs {expect:.2}");
+        // 5.6 dB for one periodogram; about a seventh of the variance for 7 tapers
+        assert!(multi_sd < single_sd / 2.0, "{multi_sd:.2} dB vs {single_sd:.2} dB");
+    }
+
+    #[test]
+    fn multitaper_parses_nw_and_taper_count() {
+        assert_eq!(Multitaper::parse("4"), Some(Multitaper { nw: 4.0, tapers: 7 }));
+        assert_eq!(Multitaper::parse("2.5:3"), Some(Multitaper { nw: 2.5, tapers: 3 }));
+        assert_eq!(Multitaper::parse(&Multitaper { nw: 3.0, tapers: 6 }.name()[5..]), Some(Multitaper { nw: 3.0, tapers: 6 }));
+        assert_eq!(Multitaper::parse("4:9"), None);
+        assert_eq!(Multitaper::parse("4:0"), None);
+        assert_eq!(Multitaper::parse("0.5"), None);
+        assert_eq!(Multitaper::parse("x"), None);
+    }
+
+    #[test]
+    fn window_names_round_trip() {
+        for &w in &ALL_WINDOWS {
+            assert_eq!(WindowType::parse(&w.name()), Some(w));
+        }
+        assert_eq!(WindowType::parse("kaiser"), Some(WindowType::Kaiser(9.0)));
+        assert_eq!(WindowType::parse("Kaiser:6.5"), Some(WindowType::Kaiser(6.5)));
+        assert_eq!(WindowType::parse("hann:3"), None);
+        assert_eq!(WindowType::parse("tukey:2"), None);
+        assert_eq!(WindowType::parse("kaiser:x"), None);
+    }
+
+    #[test]
+    fn off_bin_sine_interpolates_frequency_and_level() {
+        // Between bins the raw peak is up to half a bin off and reads low by
+        // the scalloping loss; interpolation recovers both.
+        let fs = 48_000.0f64;
+        for &(n, l, w) in &[
+            (1024usize, 1024usize, WindowType::Hann),
+            (2048, 2048, WindowType::Hamming),
+            (4096, 4096, WindowType::Blackman),
+            (4096, 1024, WindowType::Hann), // zero-padded
+        ] {
+            let scallop = Scalloping::new(w, l, n);
+            for &frac in &[0.0f64, 0.13, 0.25, 0.37, 0.5] {
+                let k = n as f64 / 8.0 + frac;
+                let f0 = fs * k / n as f64;
+                let x: Vec<f32> = (0..l).map(|i| (2.0 * std::f64::consts::PI * f0 * i as f64 / fs).sin() as f32).collect();
+                let mut spec = SpectrogramBuilder::new(n, l, l).window(w).build();
+                let row = &spec.process_samples(&x)[0];
+                let (bin, _) = crate::view::max_bin_in(row, 0, row.len());
+                let pk = crate::view::interpolate_peak(row, bin, &scallop);
+                let err_hz = (pk.bin as f64 - k) * fs / n as f64;
+                // Within 2% of a bin (under 1 Hz at N=1024), down from up to 50%
+                assert!(err_hz.abs() < 0.02 * fs / n as f64, "N={n} L={l} {w:?} +{frac} bin: {err_hz:.2} Hz off");
+                assert!(pk.db.abs() < 0.1, "N={n} L={l} {w:?} +{frac} bin: read {:.2} dBFS", pk.db);
+            }
+        }
+    }
+
+    #[test]
+    fn complex_tone_lands_on_its_signed_bin() {
+        // e^{j2πft} has energy only at +f; e^{-j2πft} only at -f
+        let n = 1024usize;
+        let fs = 48_000.0f64;
+        let k = 64i32;
+        for &sign in &[1i32, -1] {
+            let f = sign as f64 * k as f64 * fs / n as f64;
+            let ph = |i: usize| 2.0 * std::f64::consts::PI * f * i as f64 / fs;
+            let re: Vec<f32> = (0..n).map(|i| ph(i).cos() as f32).collect();
+            let im: Vec<f32> = (0..n).map(|i| ph(i).sin() as f32).collect();
+            let mut spec = SpectrogramBuilder::new(n, n, n).window(WindowType::Hann).build();
+            let rows = spec.process_complex(&re, &im);
+            let row = &rows[0];
+            assert_eq!(row.len(), n);
+            let expect = (n as i32 / 2 + sign * k) as usize;
+            let mirror = (n as i32 / 2 - sign * k) as usize;
+            let (max_idx, &peak) = row.iter().enumerate().max_by(|a, b| a.1.partial_cmp(b.1).unwrap()).unwrap();
+            assert_eq!(max_idx, expect);
+            assert!(peak.abs() < 0.5, "complex tone read {peak:.2} dBFS, expected ~0");
+            assert!(row[mirror] < -100.0, "mirror image at {:.1} dB", row[mirror]);
+        }
+    }
+
+    #[test]
+    fn complex_dc_sits_in_center_bin() {
+        let n = 256usize;
+        let mut spec = SpectrogramBuilder::new(n, n, n).build();
+        let rows = spec.process_complex(&vec![1.0; n], &vec![0.0; n]);
+        assert!(rows[0][n / 2].abs() < 0.5);
     }
 
     #[test]
@@ -245,4 +1297,21 @@ mod tests {
         // we just ensure the max is at or near 0
         assert!(mx <= 1e-5);
     }
+
+    #[test]
+    fn weighting_curves_match_iec_61672_table() {
+        // (exact band center in Hz, A, C) from IEC 61672-1 Table 3
+        for (f, a, c) in [(31.623, -39.4, -3.0), (100.0, -19.1, -0.3), (1000.0, 0.0, 0.0), (3981.1, 1.0, -0.8), (10_000.0, -2.5, -4.4)] {
+            assert!((Weighting::A.gain_db(f) - a).abs() < 0.1, "A at {f} Hz: {:.2}", Weighting::A.gain_db(f));
+            assert!((Weighting::C.gain_db(f) - c).abs() < 0.1, "C at {f} Hz: {:.2}", Weighting::C.gain_db(f));
+        }
+        assert_eq!(Weighting::Z.gain_db(50.0), 0.0);
+        // Applied per bin: a 100 Hz bin-centered tone loses 19 dB under A
+        let (fs, n) = (48_000u32, 480usize);
+        let x: Vec<f32> = (0..n).map(|i| (2.0 * std::f32::consts::PI * 100.0 * i as f32 / fs as f32).sin()).collect();
+        let mut spec = SpectrogramBuilder::new(n, n, n).sample_rate(fs).weighting(Weighting::A).build();
+        let row = &spec.process_samples(&x)[0];
+        assert!((row[1] + 19.1).abs() < 0.2, "A-weighted 100 Hz read {:.2}", row[1]);
+        assert!(row[0] < -200.0, "DC is removed entirely");
+    }
 }
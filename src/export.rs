@@ -1,30 +1,59 @@
-use crate::app::{AnimationStyle, BinsMode, FreqScale};
+use crate::app::{AnimationStyle, BinsMode};
 use crate::colors::Palette;
 use crate::font;
+use crate::pitch::{self, Pitch};
 use crate::view::{self, FreqMap};
 use anyhow::Result;
 use image::{ImageBuffer, Rgb};
 use std::collections::VecDeque;
 use std::fs;
+use std::io::Write;
 use std::path::PathBuf;
 
 pub struct PngRequest<'a> {
-    pub buffer: &'a VecDeque<Vec<f32>>,
+    /// One history per analyzed channel, stacked top to bottom as panels
+    /// sharing the x axis. All histories cover the same rows.
+    pub buffers: &'a [VecDeque<Vec<f32>>],
+    /// Panel labels, drawn when there is more than one panel
+    pub labels: &'a [String],
     pub palette: &'a Palette,
     pub db_floor: f32,
     pub db_ceiling: f32,
-    /// Content (spectrogram) size in pixels; axis margins are added on top
-    /// when the content is large enough for a labeled figure.
+    /// Content (spectrogram) size of each panel in pixels; axis margins are
+    /// added on top when the content is large enough for a labeled figure.
     pub width: u32,
     pub height: u32,
     pub style: AnimationStyle,
-    pub freq_scale: FreqScale,
-    pub sample_rate: u32,
-    pub zoom: f32,
+    /// Frequency mapping (rate, zoom, scale, I/Q layout, center offset)
+    pub fmap: FreqMap,
     pub bins_mode: BinsMode,
     /// Hop size in samples; used to label the time axis.
     pub hop: usize,
+    /// Source time of the newest row (file inputs); time ticks then read as
+    /// absolute source time instead of seconds ago.
+    pub newest_time: Option<f64>,
     pub title: Option<String>,
+    /// Unit of the dB values, labelling the colorbar (e.g. "dBFS/Hz")
+    pub unit: &'a str,
+    /// Measurement markers drawn in every panel
+    pub markers: &'a [PngMarker],
+    /// Marker readout lines, boxed at the bottom-left of the first panel
+    pub legend: &'a [String],
+    /// f0 track per panel, aligned with `buffers`; empty draws no contour
+    pub pitch: &'a [VecDeque<Pitch>],
+    /// A4 reference in Hz to label the frequency axis in note names
+    pub notes: Option<f64>,
+}
+
+/// A marker to draw: a point on one history row, or a line across the time
+/// axis at `freq` for a live marker.
+#[derive(Clone, Debug)]
+pub struct PngMarker {
+    pub label: String,
+    /// Center-relative frequency in Hz
+    pub freq: f32,
+    /// History index (0 = newest row); `None` marks the frequency only
+    pub row: Option<usize>,
 }
 
 type Img = ImageBuffer<Rgb<u8>, Vec<u8>>;
@@ -38,10 +67,14 @@ const MT: u32 = 34; // top margin: title
 const MB: u32 = 44; // bottom margin: x-axis labels
 const FSC: u32 = 2; // font scale
 const TICK: u32 = 5; // tick mark length
+const GAP: u32 = 24; // vertical gap between stacked panels (room for edge tick labels)
+const FTICKS: u32 = 6; // frequency axis intervals
 
 const BG: Rgb<u8> = Rgb([16, 16, 20]);
 const FG: Rgb<u8> = Rgb([208, 208, 214]);
 const DIM: Rgb<u8> = Rgb([95, 95, 105]);
+const MARK: Rgb<u8> = Rgb([255, 220, 0]);
+const PITCH: Rgb<u8> = Rgb([255, 255, 255]);
 
 fn rgb_of(palette: &Palette, t: f32) -> Rgb<u8> {
     match palette.color_at(t) {
@@ -50,18 +83,41 @@ fn rgb_of(palette: &Palette, t: f32) -> Rgb<u8> {
     }
 }
 
-fn freq_label(hz: f32) -> String {
-    if hz >= 9999.5 {
+fn freq_label(hz: f64) -> String {
+    if let Some(rf) = view::format_rf(hz) {
+        rf
+    } else if hz.abs() >= 9999.5 {
         format!("{:.0}kHz", hz / 1000.0)
-    } else if hz >= 999.5 {
+    } else if hz.abs() >= 999.5 {
         format!("{:.1}kHz", hz / 1000.0)
     } else {
         format!("{:.0}Hz", hz)
     }
 }
 
-fn time_label(sec_ago: f32) -> String {
-    if sec_ago <= 0.005 {
+/// Frequency tick labels, `ticks + 1` of them from the bottom of the range.
+/// Narrow bands whose short labels would repeat switch to plain Hz with as
+/// many decimals as the tick spacing needs. With an A4 reference the ticks
+/// are note names instead (`view::note_ticks`).
+fn freq_ticks(fmap: &FreqMap, ticks: u32, notes: Option<f64>) -> Vec<(f32, String)> {
+    if let Some(a4) = notes {
+        return view::note_ticks(fmap, a4, ticks);
+    }
+    let fracs: Vec<f32> = (0..=ticks).map(|i| i as f32 / ticks as f32).collect();
+    let freqs: Vec<f64> = fracs.iter().map(|&t| fmap.absolute(fmap.frac_to_freq(t))).collect();
+    let mut labels: Vec<String> = freqs.iter().map(|&f| freq_label(f)).collect();
+    if labels.windows(2).any(|w| w[0] == w[1]) && view::format_rf(freqs[0]).is_none() {
+        let step = freqs.windows(2).map(|w| (w[1] - w[0]).abs()).fold(f64::INFINITY, f64::min);
+        let decimals = (-step.log10()).ceil().max(0.0) as usize;
+        labels = freqs.iter().map(|&f| format!("{f:.decimals$}Hz")).collect();
+    }
+    fracs.into_iter().zip(labels).collect()
+}
+
+fn time_label(sec_ago: f32, newest: Option<f64>) -> String {
+    if let Some(t) = newest {
+        view::format_clock(t - sec_ago as f64)
+    } else if sec_ago <= 0.005 {
         "0s".to_string()
     } else if sec_ago < 10.0 {
         format!("-{:.2}s", sec_ago)
@@ -85,32 +141,92 @@ fn vline(img: &mut Img, x: u32, y0: u32, y1: u32, c: Rgb<u8>) {
 }
 
 pub fn save_png(req: &PngRequest, path: PathBuf) -> Result<()> {
-    if req.buffer.is_empty() {
+    let Some(primary) = req.buffers.first().filter(|b| !b.is_empty()) else {
         return Err(anyhow::anyhow!("nothing to export: history is empty"));
-    }
+    };
     let cw = req.width.max(1);
     let ch = req.height.max(1);
+    let panels = req.buffers.len() as u32;
     let axes = cw >= AXES_MIN_W && ch >= AXES_MIN_H;
-    let (iw, ih) = if axes { (cw + ML + MR, ch + MT + MB) } else { (cw, ch) };
-    let (ox, oy) = if axes { (ML, MT) } else { (0, 0) };
+    let gap = if axes { GAP } else { 0 };
+    let stack_h = panels * ch + (panels - 1) * gap;
+    // RF labels (e.g. 433.92MHz) can outgrow the fixed left margin when the
+    // frequency axis is vertical
+    let ml = if matches!(req.style, AnimationStyle::Horizontal) {
+        let widest = freq_ticks(&req.fmap, FTICKS, req.notes).iter().map(|(_, l)| font::text_width(l, FSC)).max().unwrap_or(0);
+        ML.max(widest + TICK + 8)
+    } else {
+        ML
+    };
+    let (iw, ih) = if axes { (cw + ml + MR, stack_h + MT + MB) } else { (cw, stack_h) };
+    let (ox, oy) = if axes { (ml, MT) } else { (0, 0) };
     let mut img: Img = ImageBuffer::from_pixel(iw, ih, BG);
 
-    let bins = req.buffer.front().map(|r| r.len()).unwrap_or(1).max(1);
-    let total = req.buffer.len();
-    let fmap = FreqMap { sample_rate: req.sample_rate, zoom: req.zoom, scale: req.freq_scale };
-    let range = (req.db_ceiling - req.db_floor).max(1.0);
-    let total_sec = (total as f32) * (req.hop as f32) / (req.sample_rate as f32).max(1.0);
+    let fmap = req.fmap;
+    // Age of the oldest row: the far edge of the time axis
+    let total_sec = (primary.len().saturating_sub(1) as f32) * (req.hop as f32) / (fmap.sample_rate as f32).max(1.0);
 
-    // ---- content: the full history is always fitted to the content rect,
-    // max-pooling every covered bin/row so narrow features survive ----
+    for (i, buffer) in req.buffers.iter().enumerate() {
+        let py = oy + (i as u32) * (ch + gap);
+        draw_content(&mut img, req, buffer, &fmap, ox, py, cw, ch);
+        if axes {
+            draw_axes(&mut img, req, &fmap, ox, py, cw, ch, total_sec, i == 0, i + 1 == req.buffers.len());
+        }
+        if panels > 1 {
+            if let Some(label) = req.labels.get(i) {
+                draw_panel_label(&mut img, ox, py, label);
+            }
+        }
+        if let Some(track) = req.pitch.get(i) {
+            draw_pitch(&mut img, req, track, buffer.len(), ox, py, cw, ch);
+        }
+        draw_markers(&mut img, req, buffer.len(), ox, py, cw, ch);
+    }
+    draw_legend(&mut img, req.legend, ox, oy + ch);
+    if axes {
+        if let Some(title) = &req.title {
+            let glyph_h = font::text_height(FSC);
+            font::draw_text(&mut img, 8, ((MT - glyph_h) / 2) as i64, title, FG, FSC);
+        }
+    }
+
+    if let Some(parent) = path.parent() {
+        if !parent.as_os_str().is_empty() {
+            let _ = fs::create_dir_all(parent);
+        }
+    }
+    img.save(path)?;
+    Ok(())
+}
+
+/// Paint one panel's spectrogram into the content rect at (ox, oy). The full
+/// history is always fitted to the rect, max-pooling every covered bin/row so
+/// narrow features survive.
+#[allow(clippy::too_many_arguments)]
+fn draw_content(
+    img: &mut Img,
+    req: &PngRequest,
+    buffer: &VecDeque<Vec<f32>>,
+    fmap: &FreqMap,
+    ox: u32,
+    oy: u32,
+    cw: u32,
+    ch: u32,
+) {
+    let bins = buffer.front().map(|r| r.len()).unwrap_or(1).max(1);
+    let total = buffer.len();
+    if total == 0 {
+        return;
+    }
+    let range = (req.db_ceiling - req.db_floor).max(1.0);
     match req.style {
-        AnimationStyle::Waterfall | AnimationStyle::Spectrum => {
+        AnimationStyle::Waterfall | AnimationStyle::Spectrum | AnimationStyle::Octave => {
             // y = time (newest at top), x = frequency (low -> high)
             for py in 0..ch {
                 let (r0, r1) = view::overview_row_range(py as usize, ch as usize, total);
                 for px in 0..cw {
                     let (lo, hi) = fmap.cell_bin_range(px as usize, cw as usize, bins);
-                    let v = view::pool_cell(req.buffer, r0, r1, lo, hi, req.bins_mode);
+                    let v = view::pool_cell(buffer, r0, r1, lo, hi, req.bins_mode);
                     let t = ((v - req.db_floor) / range).clamp(0.0, 1.0);
                     img.put_pixel(ox + px, oy + py, rgb_of(req.palette, t));
                 }
@@ -128,25 +244,134 @@ pub fn save_png(req: &PngRequest, path: PathBuf) -> Result<()> {
                 for py in 0..ch {
                     let fy = (ch - 1 - py) as usize; // low freq at bottom
                     let (lo, hi) = fmap.cell_bin_range(fy, ch as usize, bins);
-                    let v = view::pool_cell(req.buffer, r0, r1, lo, hi, req.bins_mode);
+                    let v = view::pool_cell(buffer, r0, r1, lo, hi, req.bins_mode);
                     let t = ((v - req.db_floor) / range).clamp(0.0, 1.0);
                     img.put_pixel(ox + px, oy + py, rgb_of(req.palette, t));
                 }
             }
         }
     }
+}
 
-    if axes {
-        draw_axes(&mut img, req, &fmap, ox, oy, cw, ch, total_sec);
+/// f0 contour over one waterfall or horizontal panel: the median f0 of the
+/// rows behind each pixel step along the time axis, joined into a line
+/// wherever consecutive steps are voiced and less than an eighth of the
+/// axis apart (octave jumps and onsets stay broken).
+#[allow(clippy::too_many_arguments)]
+fn draw_pitch(img: &mut Img, req: &PngRequest, track: &VecDeque<Pitch>, total: usize, ox: u32, oy: u32, cw: u32, ch: u32) {
+    let freq_on_x = match req.style {
+        AnimationStyle::Waterfall => true,
+        AnimationStyle::Horizontal => false,
+        AnimationStyle::Spectrum | AnimationStyle::Octave => return,
+    };
+    if total == 0 {
+        return;
     }
+    let (steps, span) = if freq_on_x { (ch, cw) } else { (cw, ch) };
+    let mut prev: Option<u32> = None;
+    for s in 0..steps {
+        let (r0, r1) = if freq_on_x {
+            view::overview_row_range(s as usize, ch as usize, total)
+        } else {
+            let t0 = (s as usize) * total / (cw as usize);
+            let t1 = (((s as usize + 1) * total).div_ceil(cw as usize)).clamp(t0 + 1, total);
+            (total - t1, total - t0)
+        };
+        let frac = pitch::median_hz(track, r0, r1).map(|hz| req.fmap.freq_to_frac(hz)).filter(|f| (0.0..=1.0).contains(f));
+        let Some(frac) = frac else {
+            prev = None;
+            continue;
+        };
+        let along = if freq_on_x { frac * (span - 1) as f32 } else { (1.0 - frac) * (span - 1) as f32 } as u32;
+        // Join to the previous step, or a 2 px dot at a fresh start
+        let (a, b) = match prev {
+            Some(p) if p.abs_diff(along) < span / 8 => (p.min(along), p.max(along) + 1),
+            _ => (along, (along + 2).min(span)),
+        };
+        if freq_on_x {
+            hline(img, ox + a, ox + b, oy + s, PITCH);
+        } else {
+            vline(img, ox + s, oy + a, oy + b, PITCH);
+        }
+        prev = Some(along);
+    }
+}
 
-    if let Some(parent) = path.parent() {
-        if !parent.as_os_str().is_empty() {
-            let _ = fs::create_dir_all(parent);
+/// Markers over one panel: a crosshair on the marker's row, or a dashed
+/// line across the time axis for a live marker, each tagged with its label.
+fn draw_markers(img: &mut Img, req: &PngRequest, total: usize, ox: u32, oy: u32, cw: u32, ch: u32) {
+    let freq_on_x = !matches!(req.style, AnimationStyle::Horizontal);
+    let total = total.max(1);
+    for m in req.markers {
+        let frac = req.fmap.freq_to_frac(m.freq);
+        if !(0.0..=1.0).contains(&frac) {
+            continue;
         }
+        // Position along the frequency and time axes, in pixels from the origin
+        let along_f = if freq_on_x { frac * (cw - 1) as f32 } else { (1.0 - frac) * (ch - 1) as f32 } as u32;
+        let (x, y) = match m.row {
+            Some(idx) if idx < total => {
+                let (x, y) = if freq_on_x {
+                    (along_f, ((idx as f32 + 0.5) / total as f32 * ch as f32) as u32)
+                } else {
+                    ((((total - 1 - idx) as f32 + 0.5) / total as f32 * cw as f32) as u32, along_f)
+                };
+                let (x, y) = (ox + x.min(cw - 1), oy + y.min(ch - 1));
+                hline(img, x.saturating_sub(6).max(ox), (x + 7).min(ox + cw), y, MARK);
+                vline(img, x, y.saturating_sub(6).max(oy), (y + 7).min(oy + ch), MARK);
+                (x, y)
+            }
+            Some(_) => continue,
+            None if freq_on_x => {
+                let x = ox + along_f;
+                for y in (oy..oy + ch).step_by(6) {
+                    vline(img, x, y, (y + 3).min(oy + ch), MARK);
+                }
+                (x, oy + 8)
+            }
+            None => {
+                let y = oy + along_f;
+                for x in (ox..ox + cw).step_by(6) {
+                    hline(img, x, (x + 3).min(ox + cw), y, MARK);
+                }
+                (ox + cw - 8, y)
+            }
+        };
+        let w = font::text_width(&m.label, FSC) + 6;
+        let h = font::text_height(FSC) + 4;
+        // Tag above-right of the point, flipped inward at the panel edges
+        let tx = if x + 4 + w > ox + cw { x.saturating_sub(4 + w) } else { x + 4 };
+        let ty = if y < oy + h + 4 { y + 4 } else { y - h - 4 };
+        for yy in ty..ty + h {
+            hline(img, tx, tx + w, yy, BG);
+        }
+        font::draw_text(img, (tx + 3) as i64, (ty + 2) as i64, &m.label, MARK, FSC);
     }
-    img.save(path)?;
-    Ok(())
+}
+
+/// Marker readouts stacked upward from `bottom`, on a background box.
+fn draw_legend(img: &mut Img, lines: &[String], ox: u32, bottom: u32) {
+    let lh = font::text_height(FSC) + 6;
+    let w = lines.iter().map(|l| font::text_width(l, FSC)).max().unwrap_or(0) + 8;
+    let top = bottom.saturating_sub(lh * lines.len() as u32);
+    for (i, line) in lines.iter().enumerate() {
+        let y = top + i as u32 * lh;
+        for yy in y..y + lh {
+            hline(img, ox, ox + w, yy, BG);
+        }
+        font::draw_text(img, (ox + 4) as i64, (y + 3) as i64, line, MARK, FSC);
+    }
+}
+
+/// Channel name in the panel's top-left corner, on a background box so it
+/// stays readable over bright content.
+fn draw_panel_label(img: &mut Img, ox: u32, oy: u32, label: &str) {
+    let w = font::text_width(label, FSC) + 8;
+    let h = font::text_height(FSC) + 6;
+    for y in oy..(oy + h).min(img.height()) {
+        hline(img, ox, ox + w, y, BG);
+    }
+    font::draw_text(img, (ox + 4) as i64, (oy + 3) as i64, label, FG, FSC);
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -159,6 +384,8 @@ fn draw_axes(
     cw: u32,
     ch: u32,
     total_sec: f32,
+    first: bool,
+    last: bool,
 ) {
     let glyph_h = font::text_height(FSC);
 
@@ -168,19 +395,14 @@ fn draw_axes(
     vline(img, ox - 1, oy - 1, oy + ch + 1, DIM);
     vline(img, ox + cw, oy - 1, oy + ch + 1, DIM);
 
-    // title
-    if let Some(title) = &req.title {
-        font::draw_text(img, 8, ((MT - glyph_h) / 2) as i64, title, FG, FSC);
-    }
-
+    // stacked panels share the x axis: only the bottom one is labeled
+    let x_labels = last;
     let freq_on_x = !matches!(req.style, AnimationStyle::Horizontal);
 
     // frequency axis (ticks follow the active scale via frac_to_freq)
-    let fticks = 6u32;
-    for i in 0..=fticks {
-        let frac = i as f32 / fticks as f32;
-        let label = freq_label(fmap.frac_to_freq(frac));
+    for (frac, label) in freq_ticks(fmap, FTICKS, req.notes) {
         if freq_on_x {
+            if !x_labels { continue; }
             let x = ox + ((frac * (cw - 1) as f32) as u32);
             vline(img, x, oy + ch, oy + ch + TICK, FG);
             let lw = font::text_width(&label, FSC) as i64;
@@ -207,14 +429,14 @@ fn draw_axes(
         }
     }
 
-    // time axis (0 = newest row)
+    // time axis (0 = newest row, or absolute source time)
     let tticks = 4u32;
     for i in 0..=tticks {
         let frac = i as f32 / tticks as f32;
         if freq_on_x {
             // waterfall: time runs down the y axis, newest at top
             let y = oy + ((frac * (ch - 1) as f32) as u32);
-            let label = time_label(frac * total_sec);
+            let label = time_label(frac * total_sec, req.newest_time);
             hline(img, ox - TICK, ox, y, FG);
             let lw = font::text_width(&label, FSC) as i64;
             font::draw_text(
@@ -225,10 +447,10 @@ fn draw_axes(
                 FG,
                 FSC,
             );
-        } else {
+        } else if x_labels {
             // horizontal: time runs along x, newest at the right edge
             let x = ox + ((frac * (cw - 1) as f32) as u32);
-            let label = time_label((1.0 - frac) * total_sec);
+            let label = time_label((1.0 - frac) * total_sec, req.newest_time);
             vline(img, x, oy + ch, oy + ch + TICK, FG);
             let lw = font::text_width(&label, FSC) as i64;
             font::draw_text(
@@ -245,7 +467,12 @@ fn draw_axes(
     // colorbar: absolute dB reference for the palette
     let bar_x = ox + cw + 22;
     let bar_w = 16u32;
-    font::draw_text(img, bar_x as i64, (oy as i64) - (glyph_h as i64) - 6, "dB", FG, FSC);
+    if first {
+        // Shifted left when a long unit would run off the image
+        let uw = font::text_width(req.unit, FSC);
+        let ux = bar_x.min(img.width().saturating_sub(uw + 4));
+        font::draw_text(img, ux as i64, (oy as i64) - (glyph_h as i64) - 6, req.unit, FG, FSC);
+    }
     for py in 0..ch {
         let t = 1.0 - (py as f32) / ((ch - 1).max(1) as f32);
         let c = rgb_of(req.palette, t);
@@ -275,7 +502,198 @@ fn draw_axes(
     }
 }
 
-pub fn save_csv(buffer: &VecDeque<Vec<f32>>, path: PathBuf) -> Result<()> {
+/// A spectrum line plot: traces (dB per bin) over the displayed band.
+pub struct PlotRequest<'a> {
+    pub traces: &'a [PlotTrace],
+    /// Frequency mapping (rate, zoom, scale, I/Q layout, center offset)
+    pub fmap: FreqMap,
+    pub db_floor: f32,
+    pub db_ceiling: f32,
+    /// Plot area in pixels; axis margins are added on top
+    pub width: u32,
+    pub height: u32,
+    pub title: Option<String>,
+    /// Unit of the dB axis (e.g. "dBFS/Hz")
+    pub unit: &'a str,
+    /// A4 reference in Hz to label the frequency axis in note names
+    pub notes: Option<f64>,
+}
+
+#[derive(Clone, Debug)]
+pub struct PlotTrace {
+    pub label: String,
+    pub color: [u8; 3],
+    /// dB per bin, the full row
+    pub values: Vec<f32>,
+}
+
+/// Draw traces as lines over frequency, each pixel column max-pooling the
+/// bins it covers (so narrow peaks survive), with a dB grid and a legend.
+pub fn save_spectrum_png(req: &PlotRequest, path: PathBuf) -> Result<()> {
+    if req.traces.is_empty() {
+        return Err(anyhow::anyhow!("nothing to export: no traces"));
+    }
+    let (cw, ch) = (req.width.max(AXES_MIN_W), req.height.max(AXES_MIN_H));
+    let (ox, oy) = (ML, MT);
+    let mut img: Img = ImageBuffer::from_pixel(cw + ML + MB, ch + MT + MB, BG);
+    let fmap = req.fmap;
+    let glyph_h = font::text_height(FSC);
+    let range = (req.db_ceiling - req.db_floor).max(1.0);
+    let y_of = |db: f32| oy + (((req.db_ceiling - db) / range).clamp(0.0, 1.0) * (ch - 1) as f32).round() as u32;
+
+    // Grid, frame and axis labels
+    for (frac, label) in freq_ticks(&fmap, FTICKS, req.notes) {
+        let x = ox + (frac * (cw - 1) as f32) as u32;
+        vline(&mut img, x, oy, oy + ch, DIM);
+        vline(&mut img, x, oy + ch, oy + ch + TICK, FG);
+        let lw = font::text_width(&label, FSC) as i64;
+        font::draw_text(&mut img, (x as i64 - lw / 2).max(2), (oy + ch + TICK + 3) as i64, &label, FG, FSC);
+    }
+    let dticks = 8u32;
+    for i in 0..=dticks {
+        let db = req.db_ceiling - i as f32 / dticks as f32 * range;
+        let y = y_of(db);
+        hline(&mut img, ox, ox + cw, y, DIM);
+        hline(&mut img, ox - TICK, ox, y, FG);
+        let label = format!("{:.0}", db);
+        let lw = font::text_width(&label, FSC) as i64;
+        font::draw_text(&mut img, (ox - TICK) as i64 - lw - 4, y as i64 - (glyph_h / 2) as i64, &label, FG, FSC);
+    }
+    hline(&mut img, ox - 1, ox + cw + 1, oy - 1, FG);
+    hline(&mut img, ox - 1, ox + cw + 1, oy + ch, FG);
+    vline(&mut img, ox - 1, oy - 1, oy + ch + 1, FG);
+    vline(&mut img, ox + cw, oy - 1, oy + ch + 1, FG);
+
+    // Traces, joined column to column by vertical runs; the first listed
+    // ends up on top
+    for t in req.traces.iter().rev() {
+        let color = Rgb(t.color);
+        let bins = t.values.len().max(1);
+        let mut prev: Option<u32> = None;
+        for px in 0..cw {
+            let (lo, hi) = fmap.cell_bin_range(px as usize, cw as usize, bins);
+            let (_, v) = view::max_bin_in(&t.values, lo, hi);
+            if !v.is_finite() {
+                prev = None;
+                continue;
+            }
+            let y = y_of(v);
+            let (a, b) = match prev {
+                Some(p) => (p.min(y), p.max(y)),
+                None => (y, y),
+            };
+            vline(&mut img, ox + px, a, b + 1, color);
+            prev = Some(y);
+        }
+    }
+
+    // Legend, top-right inside the plot
+    let lh = glyph_h + 6;
+    let lw = req.traces.iter().map(|t| font::text_width(&t.label, FSC)).max().unwrap_or(0) + 30;
+    let lx = ox + cw.saturating_sub(lw + 6);
+    for (i, t) in req.traces.iter().enumerate() {
+        let y = oy + 6 + i as u32 * lh;
+        for yy in y..y + lh {
+            hline(&mut img, lx, lx + lw, yy, BG);
+        }
+        for yy in y + lh / 2 - 1..y + lh / 2 + 1 {
+            hline(&mut img, lx + 4, lx + 20, yy, Rgb(t.color));
+        }
+        font::draw_text(&mut img, (lx + 26) as i64, (y + 3) as i64, &t.label, FG, FSC);
+    }
+    // dB unit in the top-left corner of the plot
+    let uw = font::text_width(req.unit, FSC) + 8;
+    for yy in oy + 6..oy + 6 + lh {
+        hline(&mut img, ox + 6, ox + 6 + uw, yy, BG);
+    }
+    font::draw_text(&mut img, (ox + 10) as i64, (oy + 9) as i64, req.unit, FG, FSC);
+    if let Some(title) = &req.title {
+        font::draw_text(&mut img, 8, ((MT - glyph_h) / 2) as i64, title, FG, FSC);
+    }
+
+    if let Some(parent) = path.parent() {
+        if !parent.as_os_str().is_empty() {
+            let _ = fs::create_dir_all(parent);
+        }
+    }
+    img.save(path)?;
+    Ok(())
+}
+
+/// Write traces as CSV columns, one row per bin: `freq_hz` (displayed
+/// frequency) then one column per trace, under an optional `#` meta line.
+pub fn save_spectrum_csv(traces: &[PlotTrace], fmap: &FreqMap, meta: Option<&str>, path: PathBuf) -> Result<()> {
+    let Some(bins) = traces.first().map(|t| t.values.len()) else {
+        return Err(anyhow::anyhow!("nothing to export: no traces"));
+    };
+    if let Some(parent) = path.parent() {
+        if !parent.as_os_str().is_empty() {
+            let _ = fs::create_dir_all(parent);
+        }
+    }
+    let mut file = fs::File::create(path)?;
+    if let Some(meta) = meta {
+        writeln!(file, "# {meta}")?;
+    }
+    let mut wtr = csv::Writer::from_writer(file);
+    wtr.write_record(std::iter::once("freq_hz".to_string()).chain(traces.iter().map(|t| t.label.clone())))?;
+    for k in 0..bins {
+        let f = fmap.absolute(fmap.bin_to_freq(k as f32, bins));
+        wtr.write_record(std::iter::once(format!("{f:.3}")).chain(traces.iter().map(|t| format!("{:.6}", t.values[k]))))?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+/// Write f0 tracks as CSV, one row per frame, oldest first: `time_s` (the
+/// row's time from `row_time`), then `f0_hz` and `confidence` per channel,
+/// suffixed with the channel label when there are several. Unvoiced frames
+/// leave `f0_hz` empty.
+pub fn save_pitch_csv(
+    tracks: &[VecDeque<Pitch>],
+    labels: &[String],
+    row_time: impl Fn(usize) -> f64,
+    meta: Option<&str>,
+    path: PathBuf,
+) -> Result<()> {
+    let rows = tracks.first().map(VecDeque::len).unwrap_or(0);
+    if rows == 0 {
+        return Err(anyhow::anyhow!("nothing to export: no pitch estimates"));
+    }
+    if let Some(parent) = path.parent() {
+        if !parent.as_os_str().is_empty() {
+            let _ = fs::create_dir_all(parent);
+        }
+    }
+    let mut file = fs::File::create(path)?;
+    if let Some(meta) = meta {
+        writeln!(file, "# {meta}")?;
+    }
+    let mut wtr = csv::Writer::from_writer(file);
+    let mut header = vec!["time_s".to_string()];
+    for (i, _) in tracks.iter().enumerate() {
+        match labels.get(i).filter(|_| tracks.len() > 1) {
+            Some(l) => header.extend([format!("f0_hz_{l}"), format!("confidence_{l}")]),
+            None => header.extend(["f0_hz".to_string(), "confidence".to_string()]),
+        }
+    }
+    wtr.write_record(&header)?;
+    for idx in (0..rows).rev() {
+        let mut record = vec![format!("{:.6}", row_time(idx))];
+        for track in tracks {
+            let p = track.get(idx).copied().unwrap_or_default();
+            record.push(p.hz.map(|hz| format!("{hz:.3}")).unwrap_or_default());
+            record.push(format!("{:.4}", p.confidence));
+        }
+        wtr.write_record(&record)?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+/// Write `buffer` as CSV, one row per frame, oldest first. A `meta` line
+/// (fs, N, window, units, ...) goes first as a `#` comment.
+pub fn save_csv(buffer: &VecDeque<Vec<f32>>, meta: Option<&str>, path: PathBuf) -> Result<()> {
     if buffer.is_empty() {
         return Err(anyhow::anyhow!("nothing to export: history is empty"));
     }
@@ -284,7 +702,11 @@ pub fn save_csv(buffer: &VecDeque<Vec<f32>>, path: PathBuf) -> Result<()> {
             let _ = fs::create_dir_all(parent);
         }
     }
-    let mut wtr = csv::Writer::from_path(path)?;
+    let mut file = fs::File::create(path)?;
+    if let Some(meta) = meta {
+        writeln!(file, "# {meta}")?;
+    }
+    let mut wtr = csv::Writer::from_writer(file);
     for row in buffer.iter().rev() {
         // oldest to newest
         wtr.write_record(row.iter().map(|v| format!("{:.6}", v)))?;
@@ -296,6 +718,7 @@ pub fn save_csv(buffer: &VecDeque<Vec<f32>>, path: PathBuf) -> Result<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::app::FreqScale;
 
     fn tmp_path(name: &str) -> PathBuf {
         let mut p = std::env::temp_dir();
@@ -310,19 +733,24 @@ mod tests {
         height: u32,
     ) -> PngRequest<'a> {
         PngRequest {
-            buffer,
+            buffers: std::slice::from_ref(buffer),
+            labels: &[],
             palette,
             db_floor: -80.0,
             db_ceiling: 0.0,
             width,
             height,
             style: AnimationStyle::Waterfall,
-            freq_scale: FreqScale::Linear,
-            sample_rate: 48000,
-            zoom: 1.0,
+            fmap: FreqMap { sample_rate: 48000, zoom: 1.0, fmin: None, scale: FreqScale::Linear, two_sided: false, center_freq: 0.0, cqt: None },
             bins_mode: BinsMode::All,
             hop: 256,
+            newest_time: None,
             title: Some("fs=48000Hz N=1024".to_string()),
+            unit: "dBFS",
+            markers: &[],
+            legend: &[],
+            pitch: &[],
+            notes: None,
         }
     }
 
@@ -332,12 +760,28 @@ mod tests {
         buf.push_front(vec![-20.0, -30.0]);
         buf.push_front(vec![0.0, -10.0]);
         let path = tmp_path("csv");
-        save_csv(&buf, path.clone()).unwrap();
+        save_csv(&buf, None, path.clone()).unwrap();
         let content = std::fs::read_to_string(&path).unwrap();
         let lines: Vec<&str> = content.trim().split('\n').collect();
         assert_eq!(lines.len(), 2);
         assert_eq!(lines[0], "-20.000000,-30.000000");
         assert_eq!(lines[1], "0.000000,-10.000000");
+        save_csv(&buf, Some("units=dBFS/Hz"), path.clone()).unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.starts_with("# units=dBFS/Hz\n-20.000000,"), "{content}");
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn pitch_csv_leaves_unvoiced_frames_empty() {
+        let mut track: VecDeque<Pitch> = VecDeque::new();
+        track.push_front(Pitch { hz: Some(220.0), confidence: 0.95 });
+        track.push_front(Pitch { hz: None, confidence: 0.2 });
+        let path = tmp_path("pitch_csv");
+        save_pitch_csv(&[track], &["mono".to_string()], |idx| 1.0 - idx as f64 * 0.5, None, path.clone()).unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = content.trim().split('\n').collect();
+        assert_eq!(lines, ["time_s,f0_hz,confidence", "0.500000,220.000,0.9500", "1.000000,,0.2000"]);
         let _ = std::fs::remove_file(path);
     }
 
@@ -376,6 +820,31 @@ mod tests {
         let _ = std::fs::remove_file(path);
     }
 
+    #[test]
+    fn png_stacks_one_panel_per_channel() {
+        let mut left: VecDeque<Vec<f32>> = VecDeque::new();
+        let mut right: VecDeque<Vec<f32>> = VecDeque::new();
+        for _ in 0..32 {
+            left.push_front(vec![0.0f32; 64]);
+            right.push_front(vec![-80.0f32; 64]);
+        }
+        let buffers = vec![left, right];
+        let labels = vec!["left".to_string(), "right".to_string()];
+        let palette = Palette::grayscale();
+        let path = tmp_path("png_stack").with_extension("png");
+        let mut r = req(&buffers[0], &palette, 480, 320);
+        r.buffers = &buffers;
+        r.labels = &labels;
+        save_png(&r, path.clone()).unwrap();
+        let img = image::open(&path).unwrap().to_rgb8();
+        assert_eq!(img.height(), 2 * 320 + GAP + MT + MB);
+        // top panel is the loud channel, bottom panel the quiet one
+        let probe_x = ML + 240;
+        assert!(img.get_pixel(probe_x, MT + 160).0[0] > 200);
+        assert!(img.get_pixel(probe_x, MT + 320 + GAP + 160).0[0] < 50);
+        let _ = std::fs::remove_file(path);
+    }
+
     #[test]
     fn png_narrow_peak_survives_pooling() {
         // 1 loud bin out of 2048 must still be visible in a 480px-wide export
@@ -392,4 +861,41 @@ mod tests {
         assert!(bright >= 1, "narrow peak lost in export");
         let _ = std::fs::remove_file(path);
     }
+
+    #[test]
+    fn rf_axis_labels_widen_left_margin() {
+        let mut buf: VecDeque<Vec<f32>> = VecDeque::new();
+        for _ in 0..32 {
+            buf.push_front(vec![-40.0f32; 256]);
+        }
+        let palette = Palette::grayscale();
+        let path = tmp_path("png_rf").with_extension("png");
+        let mut r = req(&buf, &palette, 480, 320);
+        r.style = AnimationStyle::Horizontal;
+        r.fmap = FreqMap { two_sided: true, center_freq: 433.92e6, ..r.fmap };
+        save_png(&r, path.clone()).unwrap();
+        let img = image::open(&path).unwrap().to_rgb8();
+        let widest = font::text_width(&freq_label(433.944e6), FSC);
+        assert!(img.width() >= 480 + MR + widest, "RF labels clipped: width {}", img.width());
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn freq_labels_cover_negative_and_rf() {
+        assert_eq!(freq_label(-12_000.0), "-12kHz");
+        assert_eq!(freq_label(-1_500.0), "-1.5kHz");
+        assert_eq!(freq_label(433.92e6), "433.92MHz");
+    }
+
+    #[test]
+    fn narrow_band_ticks_stay_distinct() {
+        let fmap = FreqMap { sample_rate: 48_000, zoom: 60.0, fmin: Some(3_800.0), scale: FreqScale::Linear, two_sided: false, center_freq: 0.0, cqt: None };
+        let labels = |fmap: &FreqMap, ticks| freq_ticks(fmap, ticks, None).into_iter().map(|(_, l)| l).collect::<Vec<_>>();
+        // Short labels are kept while they differ
+        assert_eq!(labels(&fmap, 4), ["3.8kHz", "3.9kHz", "4.0kHz", "4.1kHz", "4.2kHz"]);
+        assert_eq!(labels(&fmap, 6), ["3800Hz", "3867Hz", "3933Hz", "4000Hz", "4067Hz", "4133Hz", "4200Hz"]);
+        // Sub-hertz spacing gets decimals
+        let fine = FreqMap { zoom: 24_000.0, fmin: Some(1_000.0), ..fmap };
+        assert_eq!(labels(&fine, 3), ["1000.0Hz", "1000.3Hz", "1000.7Hz", "1001.0Hz"]);
+    }
 }
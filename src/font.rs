@@ -26,11 +26,23 @@ fn glyph(c: char) -> Option<[u8; 5]> {
         ':' => [0x00, 0x36, 0x36, 0x00, 0x00],
         '=' => [0x14, 0x14, 0x14, 0x14, 0x14],
         '/' => [0x20, 0x10, 0x08, 0x04, 0x02],
+        '(' => [0x00, 0x1C, 0x22, 0x41, 0x00],
+        ')' => [0x00, 0x41, 0x22, 0x1C, 0x00],
+        '#' => [0x14, 0x7F, 0x14, 0x7F, 0x14],
         ' ' => [0x00, 0x00, 0x00, 0x00, 0x00],
+        'A' => [0x7E, 0x11, 0x11, 0x11, 0x7E],
         'B' => [0x7F, 0x49, 0x49, 0x49, 0x36],
+        'C' => [0x3E, 0x41, 0x41, 0x41, 0x22],
+        'D' => [0x7F, 0x41, 0x41, 0x22, 0x1C],
+        'E' => [0x7F, 0x49, 0x49, 0x49, 0x41],
+        'F' => [0x7F, 0x09, 0x09, 0x09, 0x01],
+        'G' => [0x3E, 0x41, 0x49, 0x49, 0x7A],
         'H' => [0x7F, 0x08, 0x08, 0x08, 0x7F],
+        'I' => [0x00, 0x41, 0x7F, 0x41, 0x00],
         'L' => [0x7F, 0x40, 0x40, 0x40, 0x40],
+        'M' => [0x7F, 0x02, 0x0C, 0x02, 0x7F],
         'N' => [0x7F, 0x04, 0x08, 0x10, 0x7F],
+        'Q' => [0x3E, 0x41, 0x51, 0x21, 0x5E],
         'S' => [0x46, 0x49, 0x49, 0x49, 0x31],
         'W' => [0x3F, 0x40, 0x38, 0x40, 0x3F],
         'a' => [0x20, 0x54, 0x54, 0x54, 0x78],
@@ -48,6 +60,7 @@ fn glyph(c: char) -> Option<[u8; 5]> {
         'n' => [0x7C, 0x08, 0x04, 0x04, 0x78],
         'o' => [0x38, 0x44, 0x44, 0x44, 0x38],
         'p' => [0x7C, 0x14, 0x14, 0x14, 0x08],
+        'q' => [0x08, 0x14, 0x14, 0x18, 0x7C],
         'r' => [0x7C, 0x08, 0x04, 0x04, 0x08],
         's' => [0x48, 0x54, 0x54, 0x54, 0x20],
         't' => [0x04, 0x3F, 0x44, 0x40, 0x20],
@@ -55,7 +68,10 @@ fn glyph(c: char) -> Option<[u8; 5]> {
         'v' => [0x1C, 0x20, 0x40, 0x20, 0x1C],
         'w' => [0x3C, 0x40, 0x30, 0x40, 0x3C],
         'x' => [0x44, 0x28, 0x10, 0x28, 0x44],
+        'y' => [0x0C, 0x50, 0x50, 0x50, 0x3C],
         'z' => [0x44, 0x64, 0x54, 0x4C, 0x44],
+        '√' => [0x08, 0x70, 0x0F, 0x01, 0x01],
+        'Δ' => [0x70, 0x4C, 0x43, 0x4C, 0x70],
         _ => return None,
     })
 }
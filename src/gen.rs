@@ -0,0 +1,281 @@
+//! Built-in test signals for the `gen:` source family, e.g. `gen:sine:1000`,
+//! `gen:sweep:20:20000:10s`, `gen:white`, `gen:pink`, `gen:impulse:1s` or
+//! `gen:multitone:100,1k,5k`. Trailing `level=<dB>` and `len=<duration>`
+//! options set the amplitude and make the source finite.
+//!
+//! Levels follow the analyzer's dBFS convention: a tone at `level=0` is a
+//! full-scale sine and reads 0 dBFS. Noise levels are RMS relative to 1.0.
+//! Noise comes from a fixed-seed PRNG, so every run is sample-identical.
+
+use crate::view::parse_freq;
+use anyhow::{anyhow, Result};
+use std::f64::consts::PI;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Signal {
+    Sine { freq: f64 },
+    /// Logarithmic sweep from `f0` to `f1` over `period` seconds, repeating
+    Sweep { f0: f64, f1: f64, period: f64 },
+    White,
+    Pink,
+    /// Single-sample click every `period` seconds, the first at t = 0
+    Impulse { period: f64 },
+    Multitone { freqs: Vec<f64> },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct GenSpec {
+    pub signal: Signal,
+    /// Peak amplitude per tone (or RMS for noise) in dB re full scale
+    pub level_db: f64,
+    /// Length in seconds; `None` runs until the app exits
+    pub len: Option<f64>,
+}
+
+impl GenSpec {
+    /// Parse a `gen:` source string.
+    pub fn parse(src: &str) -> Result<Self> {
+        let body = src.strip_prefix("gen:").ok_or_else(|| anyhow!("generator sources start with 'gen:'"))?;
+        let mut level_db = None;
+        let mut len = None;
+        let mut args = Vec::new();
+        for tok in body.split(':') {
+            if let Some(v) = tok.strip_prefix("level=") {
+                level_db = Some(v.trim_end_matches("dB").parse::<f64>().map_err(|_| anyhow!("bad level '{v}' in {src}"))?);
+            } else if let Some(v) = tok.strip_prefix("len=") {
+                len = Some(parse_duration(v).ok_or_else(|| anyhow!("bad length '{v}' in {src}"))?);
+            } else {
+                args.push(tok);
+            }
+        }
+        let (kind, params) = args.split_first().ok_or_else(|| anyhow!("empty generator in {src}"))?;
+        let freq = |i: usize, default: f64| -> Result<f64> {
+            match params.get(i) {
+                Some(p) => parse_freq(p).ok_or_else(|| anyhow!("bad frequency '{p}' in {src}")),
+                None => Ok(default),
+            }
+        };
+        let secs = |i: usize, default: f64| -> Result<f64> {
+            match params.get(i) {
+                Some(p) => parse_duration(p).ok_or_else(|| anyhow!("bad duration '{p}' in {src}")),
+                None => Ok(default),
+            }
+        };
+        let signal = match *kind {
+            "sine" => Signal::Sine { freq: freq(0, 1000.0)? },
+            "sweep" => Signal::Sweep { f0: freq(0, 20.0)?, f1: freq(1, 20_000.0)?, period: secs(2, 10.0)? },
+            "white" => Signal::White,
+            "pink" => Signal::Pink,
+            "impulse" => Signal::Impulse { period: secs(0, 1.0)? },
+            "multitone" => {
+                let list = params.first().copied().unwrap_or("100,1k,10k");
+                let freqs = list
+                    .split(',')
+                    .map(|f| parse_freq(f).ok_or_else(|| anyhow!("bad frequency '{f}' in {src}")))
+                    .collect::<Result<Vec<_>>>()?;
+                Signal::Multitone { freqs }
+            }
+            other => {
+                return Err(anyhow!("unknown generator '{other}' (sine, sweep, white, pink, impulse, multitone)"))
+            }
+        };
+        if let Signal::Sweep { f0, f1, period } = signal {
+            if f0 <= 0.0 || f1 <= 0.0 || period <= 0.0 {
+                return Err(anyhow!("sweep needs positive frequencies and period in {src}"));
+            }
+        }
+        let level_db = level_db.unwrap_or(match &signal {
+            Signal::White | Signal::Pink => -20.0,
+            // Tones share full scale so their sum never clips
+            Signal::Multitone { freqs } => -20.0 * (freqs.len().max(1) as f64).log10(),
+            _ => 0.0,
+        });
+        Ok(Self { signal, level_db, len })
+    }
+}
+
+/// "10s", "500ms", "1.5" -> seconds
+fn parse_duration(s: &str) -> Option<f64> {
+    let s = s.trim();
+    let (num, mult) = if let Some(n) = s.strip_suffix("ms") {
+        (n, 0.001)
+    } else if let Some(n) = s.strip_suffix('s') {
+        (n, 1.0)
+    } else {
+        (s, 1.0)
+    };
+    num.parse::<f64>().ok().filter(|v| v.is_finite() && *v > 0.0).map(|v| v * mult)
+}
+
+/// Streaming sample source for a `GenSpec` at a fixed rate.
+pub struct Generator {
+    spec: GenSpec,
+    sr: f64,
+    amp: f64,
+    /// Samples produced so far
+    n: u64,
+    /// Samples left for a finite source
+    remaining: Option<u64>,
+    rng: u64,
+    /// Pink filter state (Paul Kellet's refined method)
+    pink: [f64; 7],
+    pink_gain: f64,
+}
+
+impl Generator {
+    pub fn new(spec: GenSpec, sample_rate: u32) -> Self {
+        let sr = sample_rate.max(1) as f64;
+        let amp = 10f64.powf(spec.level_db / 20.0);
+        let remaining = spec.len.map(|s| (s * sr).round() as u64);
+        let mut g = Self { spec, sr, amp, n: 0, remaining, rng: 0x9E37_79B9_7F4A_7C15, pink: [0.0; 7], pink_gain: 1.0 };
+        if g.spec.signal == Signal::Pink {
+            // Normalize the filter's output to unit RMS, then restart so
+            // the stream itself is unaffected by the calibration run
+            let cal = 1 << 16;
+            let ms = (0..cal).map(|_| g.pink_raw().powi(2)).sum::<f64>() / cal as f64;
+            g.pink_gain = 1.0 / ms.sqrt().max(1e-12);
+            g.pink = [0.0; 7];
+            g.rng = 0x9E37_79B9_7F4A_7C15;
+        }
+        g
+    }
+
+    /// Fill `out` with the next samples; returns how many were written,
+    /// fewer than `out.len()` only at the end of a finite source.
+    pub fn fill(&mut self, out: &mut [f32]) -> usize {
+        let n = match self.remaining {
+            Some(r) => (r.min(out.len() as u64)) as usize,
+            None => out.len(),
+        };
+        for v in &mut out[..n] {
+            *v = self.next_sample() as f32;
+        }
+        if let Some(r) = self.remaining.as_mut() {
+            *r -= n as u64;
+        }
+        n
+    }
+
+    fn next_sample(&mut self) -> f64 {
+        let t = self.n as f64 / self.sr;
+        let i = self.n;
+        self.n += 1;
+        match &self.spec.signal {
+            Signal::Sine { freq } => self.amp * (2.0 * PI * freq * t).sin(),
+            Signal::Sweep { f0, f1, period } => {
+                // Exponential sweep: phase = 2π f0 T / ln(k) * (k^(t/T) - 1)
+                let tp = t % period;
+                let k = f1 / f0;
+                let phase = if (k - 1.0).abs() < 1e-12 {
+                    2.0 * PI * f0 * tp
+                } else {
+                    2.0 * PI * f0 * period / k.ln() * (k.powf(tp / period) - 1.0)
+                };
+                self.amp * phase.sin()
+            }
+            Signal::White => self.amp * self.gaussian(),
+            Signal::Pink => self.amp * self.pink_gain * self.pink_raw(),
+            Signal::Impulse { period } => {
+                let every = (period * self.sr).round().max(1.0) as u64;
+                if i.is_multiple_of(every) { self.amp } else { 0.0 }
+            }
+            Signal::Multitone { freqs } => freqs.iter().map(|f| self.amp * (2.0 * PI * f * t).sin()).sum(),
+        }
+    }
+
+    /// xorshift64* -> uniform in (0, 1)
+    fn uniform(&mut self) -> f64 {
+        self.rng ^= self.rng >> 12;
+        self.rng ^= self.rng << 25;
+        self.rng ^= self.rng >> 27;
+        let v = self.rng.wrapping_mul(0x2545_F491_4F6C_DD1D);
+        ((v >> 11) as f64 + 0.5) / (1u64 << 53) as f64
+    }
+
+    /// Standard normal via Box-Muller (unit RMS).
+    fn gaussian(&mut self) -> f64 {
+        let (u1, u2) = (self.uniform(), self.uniform());
+        (-2.0 * u1.ln()).sqrt() * (2.0 * PI * u2).cos()
+    }
+
+    fn pink_raw(&mut self) -> f64 {
+        let w = self.gaussian();
+        let b = &mut self.pink;
+        b[0] = 0.99886 * b[0] + w * 0.0555179;
+        b[1] = 0.99332 * b[1] + w * 0.0750759;
+        b[2] = 0.96900 * b[2] + w * 0.1538520;
+        b[3] = 0.86650 * b[3] + w * 0.3104856;
+        b[4] = 0.55000 * b[4] + w * 0.5329522;
+        b[5] = -0.7616 * b[5] - w * 0.0168980;
+        let out = b[0] + b[1] + b[2] + b[3] + b[4] + b[5] + b[6] + w * 0.5362;
+        b[6] = w * 0.115926;
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn take(spec: &str, sr: u32, n: usize) -> Vec<f32> {
+        let mut g = Generator::new(GenSpec::parse(spec).unwrap(), sr);
+        let mut out = vec![0.0; n];
+        let got = g.fill(&mut out);
+        out.truncate(got);
+        out
+    }
+
+    fn rms(x: &[f32]) -> f64 {
+        (x.iter().map(|&v| (v as f64).powi(2)).sum::<f64>() / x.len() as f64).sqrt()
+    }
+
+    #[test]
+    fn parses_family() {
+        assert_eq!(GenSpec::parse("gen:sine:1k").unwrap().signal, Signal::Sine { freq: 1000.0 });
+        let sweep = GenSpec::parse("gen:sweep:20:20000:10s").unwrap();
+        assert_eq!(sweep.signal, Signal::Sweep { f0: 20.0, f1: 20_000.0, period: 10.0 });
+        assert_eq!(GenSpec::parse("gen:impulse:500ms").unwrap().signal, Signal::Impulse { period: 0.5 });
+        let mt = GenSpec::parse("gen:multitone:100,1k,5k:level=-12:len=2s").unwrap();
+        assert_eq!(mt.signal, Signal::Multitone { freqs: vec![100.0, 1000.0, 5000.0] });
+        assert_eq!((mt.level_db, mt.len), (-12.0, Some(2.0)));
+        assert!(GenSpec::parse("gen:square:1000").is_err());
+        assert!(GenSpec::parse("gen:sine:abc").is_err());
+    }
+
+    #[test]
+    fn finite_length_is_exact() {
+        assert_eq!(take("gen:sine:1000:len=250ms", 48_000, 20_000).len(), 12_000);
+    }
+
+    #[test]
+    fn sine_level_follows_dbfs() {
+        let x = take("gen:sine:1000:level=-6", 48_000, 48_000);
+        // RMS of a sine is peak / sqrt(2)
+        let peak_db = 20.0 * (rms(&x) * 2f64.sqrt()).log10();
+        assert!((peak_db + 6.0).abs() < 0.01, "{peak_db}");
+    }
+
+    #[test]
+    fn noise_is_deterministic_with_rms_level() {
+        for kind in ["white", "pink"] {
+            let a = take(&format!("gen:{kind}"), 48_000, 96_000);
+            let b = take(&format!("gen:{kind}"), 48_000, 96_000);
+            assert_eq!(a, b, "{kind} must be reproducible");
+            let db = 20.0 * rms(&a).log10();
+            assert!((db + 20.0).abs() < 0.5, "{kind} RMS {db:.2} dB");
+        }
+    }
+
+    #[test]
+    fn impulses_repeat_at_period() {
+        let x = take("gen:impulse:100ms", 1_000, 1_000);
+        let hits: Vec<usize> = x.iter().enumerate().filter(|(_, &v)| v != 0.0).map(|(i, _)| i).collect();
+        assert_eq!(hits, (0..10).map(|k| k * 100).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn multitone_default_never_clips() {
+        let x = take("gen:multitone:100,1k,10k", 48_000, 48_000);
+        assert!(x.iter().all(|v| v.abs() <= 1.0));
+    }
+}
@@ -1,248 +1,1063 @@
+use crate::gen::{GenSpec, Generator};
+use crate::resample::{ResampleQuality, Resampler};
 use anyhow::{anyhow, Context, Result};
-use crossbeam_channel::bounded;
-use std::path::PathBuf;
+use crossbeam_channel::Receiver;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 #[derive(Clone)]
-pub enum AudioInputKind { Mic { device: Option<String> }, Wav(PathBuf) }
+pub enum AudioInputKind {
+    Mic { device: Option<String> },
+    Wav(PathBuf),
+    /// Complex baseband: a stereo WAV (I left, Q right) or, with `raw`, a
+    /// headerless file of interleaved I/Q samples.
+    Iq { path: PathBuf, raw: Option<RawSpec> },
+    /// Headerless PCM from a file or named pipe, or from stdin when `path`
+    /// is `None`
+    Raw { path: Option<PathBuf>, spec: RawSpec },
+    /// Headerless PCM from a TCP server at `addr` (host:port). With
+    /// `persistent`, drops are retried with backoff; otherwise the stream
+    /// ends at the first disconnect.
+    Tcp { addr: String, spec: RawSpec, persistent: bool },
+    /// Headerless PCM datagrams received on `addr` (bind address). With
+    /// `seq`, each datagram starts with a little-endian u32 sequence number
+    /// used to count lost packets. Without `persistent`, the stream ends
+    /// after `UDP_IDLE_END` of silence.
+    Udp { addr: String, spec: RawSpec, seq: bool, persistent: bool },
+    /// Built-in test signal (see `gen.rs`), mono at `rate`
+    Gen { spec: GenSpec, rate: u32 },
+}
+
+/// Counters the input thread publishes for the status bar.
+#[derive(Default)]
+pub struct InputStats {
+    /// Datagrams missing from the UDP sequence
+    pub lost_packets: AtomicU64,
+    /// TCP connections re-established after a drop
+    pub reconnects: AtomicU64,
+    /// A network source currently has a connection (TCP) or traffic (UDP)
+    pub connected: AtomicBool,
+    /// Source time (f64 bits) a file resumed at after the latest seek
+    resumed_at: AtomicU64,
+}
+
+impl InputStats {
+    pub fn resumed_at(&self) -> f64 {
+        f64::from_bits(self.resumed_at.load(Ordering::Acquire))
+    }
+
+    fn set_resumed_at(&self, secs: f64) {
+        self.resumed_at.store(secs.to_bits(), Ordering::Release);
+    }
+}
+
+/// Commands from the UI to the decoder thread of a file source.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Transport {
+    /// Jump by this many seconds (negative = back), within the range or loop
+    SeekBy(f64),
+    /// Back to the start of the range, or of the loop when one is set
+    Restart,
+    /// Pace playback at this multiple of real time (0.25..8); turns pacing on
+    Speed(f64),
+    /// Repeat [start, end) of the file in seconds; `None` plays through
+    Loop(Option<(f64, f64)>),
+}
+
+/// Sample encoding of headerless input (names follow sox/ffmpeg, so every
+/// variant carries its byte order).
+#[allow(clippy::enum_variant_names)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RawFormat {
+    /// Signed 16-bit little-endian, scaled by 1/32768
+    S16Le,
+    /// Packed signed 24-bit little-endian (3 bytes per sample)
+    S24Le,
+    /// Signed 32-bit little-endian
+    S32Le,
+    /// IEEE float32 little-endian, already in -1..1
+    F32Le,
+    /// IEEE float64 little-endian
+    F64Le,
+}
+
+impl RawFormat {
+    pub fn name(self) -> &'static str {
+        match self {
+            RawFormat::S16Le => "s16le",
+            RawFormat::S24Le => "s24le",
+            RawFormat::S32Le => "s32le",
+            RawFormat::F32Le => "f32le",
+            RawFormat::F64Le => "f64le",
+        }
+    }
+
+    /// Bytes per sample
+    pub fn width(self) -> usize {
+        match self {
+            RawFormat::S16Le => 2,
+            RawFormat::S24Le => 3,
+            RawFormat::S32Le | RawFormat::F32Le => 4,
+            RawFormat::F64Le => 8,
+        }
+    }
+
+    fn decode(self, b: &[u8]) -> f32 {
+        match self {
+            RawFormat::S16Le => i16::from_le_bytes([b[0], b[1]]) as f32 / 32768.0,
+            // Place the 3 bytes in the top of an i32; the shift sign-extends
+            RawFormat::S24Le => (i32::from_le_bytes([0, b[0], b[1], b[2]]) >> 8) as f32 / 8_388_608.0,
+            RawFormat::S32Le => (i32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64 / 2_147_483_648.0) as f32,
+            RawFormat::F32Le => f32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+            RawFormat::F64Le => f64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]]) as f32,
+        }
+    }
+}
+
+/// Layout of a headerless sample stream.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct RawSpec {
+    pub format: RawFormat,
+    pub channels: usize,
+    pub rate: u32,
+}
+
+impl RawSpec {
+    /// 16-bit mono at 48 kHz, assumed for stdin when no layout is given
+    pub const DEFAULT: RawSpec = RawSpec { format: RawFormat::S16Le, channels: 1, rate: 48_000 };
+}
+
+/// Resolve an I/Q capture: `.wav` is read as stereo WAV; anything else is
+/// raw interleaved I/Q whose format comes from `format` or the extension
+/// (`.cf32`/`.fc32`/`.f32` float, `.cs16`/`.sc16`/`.s16` int16) and whose
+/// rate must be given, since raw files carry no header.
+pub fn iq_source(path: PathBuf, format: Option<RawFormat>, rate: Option<u32>) -> Result<AudioInputKind> {
+    let ext = extension(&path);
+    if format.is_none() && ext == "wav" {
+        return Ok(AudioInputKind::Iq { path, raw: None });
+    }
+    let format = match format {
+        Some(f) => f,
+        None => match ext.as_str() {
+            "cf32" | "fc32" | "f32" | "cfile" => RawFormat::F32Le,
+            "cs16" | "sc16" | "s16" | "i16" => RawFormat::S16Le,
+            _ => return Err(anyhow!("can't tell the I/Q sample format of {}; pass --iq-format", path.display())),
+        },
+    };
+    let rate = rate.ok_or_else(|| anyhow!("raw I/Q input has no header; pass --iq-rate"))?;
+    Ok(AudioInputKind::Iq { path, raw: Some(RawSpec { format, channels: 2, rate }) })
+}
+
+fn extension(path: &Path) -> String {
+    path.extension().map(|e| e.to_string_lossy().to_ascii_lowercase()).unwrap_or_default()
+}
+
+/// Which channel(s) of a multichannel source are analyzed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ChannelSelect {
+    /// Average of all channels (the classic mono downmix)
+    Mix,
+    /// One channel by zero-based index (left = 0, right = 1)
+    Index(usize),
+    /// (L + R) / 2
+    Mid,
+    /// (L - R) / 2: out-of-phase content that a downmix cancels
+    Side,
+    /// Every channel analyzed separately, one spectrogram each
+    All,
+    /// Channels 1 and 2 are the in-phase and quadrature parts of one complex
+    /// signal; both are passed through for a single complex analysis.
+    Iq,
+}
+
+impl ChannelSelect {
+    /// Number of analyzed channels for a source with `channels` channels.
+    pub fn outputs(self, channels: usize) -> usize {
+        match self {
+            ChannelSelect::All => channels.max(1),
+            ChannelSelect::Iq => 2,
+            _ => 1,
+        }
+    }
+
+    /// Display label per analyzed channel, given the analyzed channel count.
+    pub fn labels(self, outputs: usize) -> Vec<String> {
+        let name = |i: usize| match i {
+            0 => "left".to_string(),
+            1 => "right".to_string(),
+            _ => format!("ch{}", i + 1),
+        };
+        match self {
+            ChannelSelect::Mix => vec!["mix".to_string()],
+            ChannelSelect::Index(i) => vec![name(i)],
+            ChannelSelect::Mid => vec!["mid".to_string()],
+            ChannelSelect::Side => vec!["side".to_string()],
+            ChannelSelect::All if outputs == 2 => (0..2).map(name).collect(),
+            ChannelSelect::All => (0..outputs.max(1)).map(|i| format!("ch{}", i + 1)).collect(),
+            ChannelSelect::Iq => vec!["I/Q".to_string()],
+        }
+    }
+
+    fn validate(self, channels: usize) -> Result<()> {
+        match self {
+            ChannelSelect::Index(i) if i >= channels => {
+                Err(anyhow!("channel {} requested but the source has {channels}", i + 1))
+            }
+            ChannelSelect::Side if channels < 2 => Err(anyhow!("side channel needs a stereo source")),
+            ChannelSelect::Iq if channels != 2 => {
+                Err(anyhow!("I/Q input needs exactly 2 channels (I, Q) but the source has {channels}"))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Append one interleaved source frame to the per-output channel buffers.
+    fn split(self, frame: &[f32], outs: &mut [Vec<f32>]) {
+        match self {
+            ChannelSelect::Mix => outs[0].push(frame.iter().sum::<f32>() / (frame.len() as f32)),
+            ChannelSelect::Index(i) => outs[0].push(frame[i]),
+            ChannelSelect::Mid if frame.len() < 2 => outs[0].push(frame[0]),
+            ChannelSelect::Mid => outs[0].push((frame[0] + frame[1]) * 0.5),
+            ChannelSelect::Side => outs[0].push((frame[0] - frame[1]) * 0.5),
+            ChannelSelect::All | ChannelSelect::Iq => {
+                for (out, &v) in outs.iter_mut().zip(frame) {
+                    out.push(v);
+                }
+            }
+        }
+    }
+}
+
+/// Window of a source to analyze, in seconds of source time.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct TimeRange {
+    pub start: f64,
+    /// Seconds to analyze from `start`; `None` runs to the end
+    pub duration: Option<f64>,
+}
+
+impl TimeRange {
+    pub fn is_full(&self) -> bool {
+        self.start == 0.0 && self.duration.is_none()
+    }
+}
+
+/// How a source is converted into analysis-rate blocks.
+#[derive(Clone)]
+pub struct PipelineOptions {
+    /// Analysis rate; `None` analyzes at the source's native rate (no resampling)
+    pub target_sr: Option<u32>,
+    pub quality: ResampleQuality,
+    pub realtime: bool,
+    pub channel: ChannelSelect,
+    /// Effective analysis rate, published before the first block is emitted
+    /// (0 until the source rate is known).
+    pub rate: Arc<AtomicU32>,
+    pub stats: Arc<InputStats>,
+    /// Part of the source to analyze; files seek to `start` where the format
+    /// allows, other sources decode and discard up to it
+    pub range: TimeRange,
+    /// Transport commands for file sources; `None` plays the range once
+    pub control: Option<Receiver<Transport>>,
+}
+
+/// Samples handed to `on_block` per call (small for low latency).
+const BLOCK: usize = 1024;
 
-pub fn run_input_pipeline<F: FnMut(&[f32]) + Send + 'static>(kind: AudioInputKind, target_sr: u32, realtime: bool, on_block: F) -> Result<()> {
+/// Decode `kind` and call `on_block` with one equal-length slice per
+/// analyzed channel (see `ChannelSelect`) until the source ends. A call with
+/// no slices marks a seek: the source jumped to `InputStats::resumed_at`, so
+/// analysis state carried across it must be reset.
+pub fn run_input_pipeline<F: FnMut(&[&[f32]]) + Send + 'static>(
+    kind: AudioInputKind,
+    opts: &PipelineOptions,
+    on_block: F,
+) -> Result<()> {
     match kind {
         AudioInputKind::Wav(path) => {
-            let is_wav = path
-                .extension()
-                .map(|e| e.to_string_lossy().eq_ignore_ascii_case("wav"))
-                .unwrap_or(false);
-            if is_wav {
-                run_wav(path, target_sr, realtime, on_block)
+            if extension(&path) == "wav" {
+                play(WavSource::open(&path)?, &path, opts, on_block)
             } else {
-                run_symphonia(path, target_sr, realtime, on_block)
-            }
-        }
-        AudioInputKind::Mic { device } => run_mic(target_sr, device, on_block),
-    }
-}
-
-/// Decode any symphonia-supported container/codec (mp3, flac, ogg/vorbis,
-/// m4a/aac, ...) streaming: downmix to mono, resample to target_sr, emit blocks.
-fn run_symphonia<F: FnMut(&[f32]) + Send + 'static>(path: PathBuf, target_sr: u32, realtime: bool, mut on_block: F) -> Result<()> {
-    use symphonia::core::audio::SampleBuffer;
-    use symphonia::core::codecs::{DecoderOptions, CODEC_TYPE_NULL};
-    use symphonia::core::errors::Error as SymError;
-    use symphonia::core::formats::FormatOptions;
-    use symphonia::core::io::MediaSourceStream;
-    use symphonia::core::meta::MetadataOptions;
-    use symphonia::core::probe::Hint;
-
-    let file = std::fs::File::open(&path).with_context(|| format!("Opening {}", path.display()))?;
-    let mss = MediaSourceStream::new(Box::new(file), Default::default());
-    let mut hint = Hint::new();
-    if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
-        hint.with_extension(ext);
-    }
-    let probed = symphonia::default::get_probe()
-        .format(&hint, mss, &FormatOptions::default(), &MetadataOptions::default())
-        .with_context(|| format!("Probing {}", path.display()))?;
-    let mut format = probed.format;
-    let track = format
-        .tracks()
-        .iter()
-        .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
-        .ok_or_else(|| anyhow!("No decodable audio track in {}", path.display()))?;
-    let track_id = track.id;
-    let mut decoder = symphonia::default::get_codecs()
-        .make(&track.codec_params, &DecoderOptions::default())
-        .with_context(|| "Creating decoder")?;
-
-    let mut sample_buf: Option<SampleBuffer<f32>> = None;
-    let mut src_buf: Vec<f32> = Vec::with_capacity(8192);
-    let mut out_buf: Vec<f32> = Vec::with_capacity(8192);
-    let mut src_pos = 0.0f32;
-    let block = 1024usize;
-    let start = std::time::Instant::now();
-    let mut emitted_samples: usize = 0;
+                play(SymphoniaSource::open(&path)?, &path, opts, on_block)
+            }
+        }
+        AudioInputKind::Iq { path, raw } => {
+            let opts = PipelineOptions { channel: ChannelSelect::Iq, ..opts.clone() };
+            match raw {
+                Some(spec) => play(RawSource::open(&path, spec)?, &path, &opts, on_block),
+                None => play(WavSource::open(&path)?, &path, &opts, on_block),
+            }
+        }
+        AudioInputKind::Raw { path: Some(path), spec } => play(RawSource::open(&path, spec)?, &path, opts, on_block),
+        AudioInputKind::Raw { path: None, spec } => run_raw(std::io::stdin().lock(), spec, opts, on_block),
+        // The sender paces network streams; never throttle them
+        AudioInputKind::Tcp { addr, spec, persistent } => {
+            run_tcp(&addr, spec, persistent, &PipelineOptions { realtime: false, ..opts.clone() }, on_block)
+        }
+        AudioInputKind::Udp { addr, spec, seq, persistent } => {
+            run_udp(&addr, spec, seq, persistent, &PipelineOptions { realtime: false, ..opts.clone() }, on_block)
+        }
+        AudioInputKind::Gen { spec, rate } => run_gen(spec, rate, opts, on_block),
+        AudioInputKind::Mic { device } => run_mic(opts, device, on_block),
+    }
+}
 
-    loop {
-        let packet = match format.next_packet() {
-            Ok(p) => p,
-            Err(SymError::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
-            Err(SymError::ResetRequired) => break,
-            Err(e) => return Err(anyhow!("Demux error: {e}")),
-        };
-        if packet.track_id() != track_id {
-            continue;
+/// Splits interleaved source frames into the selected channels, resamples
+/// each to the analysis rate and hands them to `on_block` in `BLOCK`-sized
+/// chunks, optionally throttled to wall-clock time. Every input path funnels
+/// through one of these.
+struct BlockEmitter {
+    select: ChannelSelect,
+    src_channels: usize,
+    resamplers: Vec<Resampler>,
+    /// Per-output-channel scratch at the source rate
+    split: Vec<Vec<f32>>,
+    outs: Vec<Vec<f32>>,
+    src_sr: u32,
+    sr: u32,
+    quality: ResampleQuality,
+    realtime: bool,
+    /// Playback speed when paced in real time
+    speed: f64,
+    start: Instant,
+    emitted: usize,
+    /// Source frames still to discard before `range.start`
+    skip: u64,
+    /// Source frames left in `range`; `None` = until the source ends
+    remaining: Option<u64>,
+}
+
+impl BlockEmitter {
+    /// Build for a source at `src_sr` and publish the effective analysis rate.
+    fn new(src_sr: u32, src_channels: usize, opts: &PipelineOptions) -> Result<Self> {
+        let src_channels = src_channels.max(1);
+        opts.channel.validate(src_channels)?;
+        let src_sr = src_sr.max(1);
+        let dst_sr = opts.target_sr.unwrap_or(src_sr);
+        opts.rate.store(dst_sr, Ordering::Release);
+        let n = opts.channel.outputs(src_channels);
+        Ok(Self {
+            select: opts.channel,
+            src_channels,
+            resamplers: (0..n).map(|_| Resampler::new(src_sr, dst_sr, opts.quality)).collect(),
+            split: vec![Vec::with_capacity(BLOCK); n],
+            outs: vec![Vec::with_capacity(8192); n],
+            src_sr,
+            sr: dst_sr,
+            quality: opts.quality,
+            realtime: opts.realtime,
+            speed: 1.0,
+            start: Instant::now(),
+            emitted: 0,
+            skip: (opts.range.start * src_sr as f64).round() as u64,
+            remaining: opts.range.duration.map(|d| (d * src_sr as f64).round() as u64),
+        })
+    }
+
+    /// The source was positioned at `frame` before decoding, so only the
+    /// frames between it and `range.start` remain to be discarded.
+    fn seeked_to(&mut self, frame: u64) {
+        self.skip = self.skip.saturating_sub(frame);
+    }
+
+    /// The source jumped: decoding resumes at frame `at`, playback at
+    /// `target` and runs to `end`. Buffered audio and filter history belong
+    /// to the old position and are dropped.
+    fn restart(&mut self, at: u64, target: u64, end: Option<u64>) {
+        let dst_sr = self.sr;
+        for r in &mut self.resamplers {
+            *r = Resampler::new(self.src_sr, dst_sr, self.quality);
         }
-        let decoded = match decoder.decode(&packet) {
-            Ok(d) => d,
-            Err(SymError::DecodeError(_)) => continue, // skip corrupt packets
-            Err(e) => return Err(anyhow!("Decode error: {e}")),
-        };
-        let spec = *decoded.spec();
-        let channels = spec.channels.count().max(1);
-        let src_sr = spec.rate.max(1);
-        let ratio = (target_sr as f32) / (src_sr as f32);
-        let buf = sample_buf.get_or_insert_with(|| {
-            SampleBuffer::<f32>::new(decoded.capacity() as u64, spec)
-        });
-        if buf.capacity() < decoded.capacity() * channels {
-            *buf = SampleBuffer::<f32>::new(decoded.capacity() as u64, spec);
+        self.split.iter_mut().chain(&mut self.outs).for_each(Vec::clear);
+        self.skip = target.saturating_sub(at);
+        self.remaining = end.map(|e| e.saturating_sub(target));
+        self.start = Instant::now();
+        self.emitted = 0;
+    }
+
+    /// Move the end of playback to source frame `end`, given that frames up
+    /// to `pos` have been fed (or are still to be skipped).
+    fn end_at(&mut self, pos: u64, end: Option<u64>) {
+        self.remaining = end.map(|e| e.saturating_sub(pos + self.skip));
+    }
+
+    /// Pace at `speed` times real time from now on.
+    fn set_speed(&mut self, speed: f64) {
+        self.speed = speed.clamp(0.25, 8.0);
+        self.realtime = true;
+        self.start = Instant::now();
+        self.emitted = 0;
+    }
+
+    /// Every frame of the requested range has been fed.
+    fn done(&self) -> bool {
+        self.remaining == Some(0)
+    }
+
+    /// Split and resample interleaved `samples`, then emit every complete block.
+    fn feed<F: FnMut(&[&[f32]])>(&mut self, samples: &[f32], on_block: &mut F) {
+        let ch = self.src_channels;
+        let mut frames = samples.len() / ch;
+        let skipped = self.skip.min(frames as u64) as usize;
+        self.skip -= skipped as u64;
+        frames -= skipped;
+        if let Some(left) = self.remaining.as_mut() {
+            frames = (frames as u64).min(*left) as usize;
+            *left -= frames as u64;
         }
-        buf.copy_interleaved_ref(decoded);
-        for frame in buf.samples().chunks_exact(channels) {
-            let mono: f32 = frame.iter().sum::<f32>() / (channels as f32);
-            src_buf.push(mono);
+        let samples = &samples[skipped * ch..(skipped + frames) * ch];
+        for frame in samples.chunks_exact(ch) {
+            self.select.split(frame, &mut self.split);
         }
-        resample_drain(ratio, &mut src_buf, &mut src_pos, &mut out_buf);
-        while out_buf.len() >= block {
-            let chunk = &out_buf[..block];
-            on_block(chunk);
-            if realtime {
-                throttle_realtime(chunk.len(), target_sr, start, &mut emitted_samples);
-            }
-            out_buf.drain(0..block);
+        for ((r, split), out) in self.resamplers.iter_mut().zip(&mut self.split).zip(&mut self.outs) {
+            r.process(split, out);
+            split.clear();
         }
+        self.emit(BLOCK, on_block);
+    }
+
+    /// Emit whatever is buffered, including a short final block.
+    fn emit_partial<F: FnMut(&[&[f32]])>(&mut self, on_block: &mut F) {
+        self.emit(1, on_block);
     }
-    // Flush remaining
-    while !out_buf.is_empty() {
-        let n = out_buf.len().min(block);
-        on_block(&out_buf[..n]);
-        if realtime {
-            throttle_realtime(n, target_sr, start, &mut emitted_samples);
+
+    /// End of stream: drain the resampler tails and emit everything.
+    fn finish<F: FnMut(&[&[f32]])>(&mut self, on_block: &mut F) {
+        for (r, out) in self.resamplers.iter_mut().zip(&mut self.outs) {
+            r.flush(out);
         }
-        out_buf.drain(0..n);
+        self.emit_partial(on_block);
+    }
+
+    fn emit<F: FnMut(&[&[f32]])>(&mut self, min_len: usize, on_block: &mut F) {
+        // Identical resamplers fed identical lengths stay in lockstep
+        let avail = self.outs.iter().map(Vec::len).min().unwrap_or(0);
+        let mut offset = 0;
+        while avail - offset >= min_len.max(1) {
+            let n = (avail - offset).min(BLOCK);
+            let blocks: Vec<&[f32]> = self.outs.iter().map(|o| &o[offset..offset + n]).collect();
+            on_block(&blocks);
+            if self.realtime {
+                throttle_realtime(n, self.sr as f64 * self.speed, self.start, &mut self.emitted);
+            }
+            offset += n;
+        }
+        for out in &mut self.outs {
+            out.drain(0..offset);
+        }
+    }
+}
+
+/// A seekable file decoder driven by `play`.
+trait FileSource {
+    fn rate(&self) -> u32;
+    fn channels(&self) -> usize;
+    /// Length in frames, when the container records it
+    fn frames(&self) -> Option<u64>;
+    /// Reposition at or just before `frame`; returns the frame decoding
+    /// resumes at, or `None` when the source can't seek (a named pipe).
+    fn seek(&mut self, frame: u64) -> Result<Option<u64>>;
+    /// Append the next interleaved samples to `out`; false at end of file.
+    fn read(&mut self, out: &mut Vec<f32>) -> Result<bool>;
+}
+
+/// WAV through hound, which seeks by frame.
+struct WavSource {
+    reader: hound::WavReader<std::io::BufReader<std::fs::File>>,
+    spec: hound::WavSpec,
+}
+
+impl WavSource {
+    fn open(path: &Path) -> Result<Self> {
+        let reader = hound::WavReader::open(path).with_context(|| format!("Opening {}", path.display()))?;
+        let spec = reader.spec();
+        Ok(Self { reader, spec })
     }
-    Ok(())
 }
 
-fn run_wav<F: FnMut(&[f32]) + Send + 'static>(path: PathBuf, target_sr: u32, realtime: bool, mut on_block: F) -> Result<()> {
-    let mut reader = hound::WavReader::open(&path).with_context(|| format!("Opening {}", path.display()))?;
-    let spec = reader.spec();
-    let src_sr = spec.sample_rate as f32;
-    let dst_sr = target_sr as f32;
-    let channels = spec.channels.max(1) as usize;
-    let ratio = dst_sr / src_sr;
-
-    // Streaming downmix + linear resampler state
-    let mut sum = 0.0f32;
-    let mut cnt = 0usize;
-    let mut src_buf: Vec<f32> = Vec::with_capacity(8192);
-    let mut src_pos = 0.0f32; // fractional index into src_buf
-    let mut out_buf: Vec<f32> = Vec::with_capacity(8192);
-    let block = 1024usize; // smaller block for lower latency
-
-    let start = std::time::Instant::now();
-    let mut emitted_samples: usize = 0;
-    match spec.sample_format {
-        hound::SampleFormat::Float => {
-            for s in reader.samples::<f32>() {
-                let v = s?;
-                sum += v; cnt += 1;
-                if cnt == channels { src_buf.push(sum / (channels as f32)); sum = 0.0; cnt = 0; }
-
-                // Resample when enough source is buffered
-                resample_drain(ratio, &mut src_buf, &mut src_pos, &mut out_buf);
-                while out_buf.len() >= block {
-                    let chunk = &out_buf[..block];
-                    on_block(chunk);
-                    if realtime { throttle_realtime(chunk.len(), target_sr, start, &mut emitted_samples); }
-                    out_buf.drain(0..block);
+impl FileSource for WavSource {
+    fn rate(&self) -> u32 {
+        self.spec.sample_rate
+    }
+
+    fn channels(&self) -> usize {
+        self.spec.channels.max(1) as usize
+    }
+
+    fn frames(&self) -> Option<u64> {
+        Some(self.reader.duration() as u64)
+    }
+
+    fn seek(&mut self, frame: u64) -> Result<Option<u64>> {
+        self.reader.seek(frame as u32).context("Seeking WAV")?;
+        Ok(Some(frame))
+    }
+
+    fn read(&mut self, out: &mut Vec<f32>) -> Result<bool> {
+        let n = BLOCK * self.channels();
+        let start = out.len();
+        match self.spec.sample_format {
+            hound::SampleFormat::Float => {
+                for s in self.reader.samples::<f32>().take(n) {
+                    out.push(s?);
                 }
             }
+            // 8-bit PCM (WAV) is unsigned on disk; hound exposes it as i8
+            hound::SampleFormat::Int if self.spec.bits_per_sample == 8 => {
+                for s in self.reader.samples::<i8>().take(n) {
+                    out.push(s? as f32 / 128.0);
+                }
+            }
+            hound::SampleFormat::Int => {
+                let max = (1i64 << (self.spec.bits_per_sample - 1)) as f32;
+                for s in self.reader.samples::<i32>().take(n) {
+                    out.push(s? as f32 / max);
+                }
+            }
+        }
+        Ok(out.len() > start)
+    }
+}
+
+/// Any symphonia-supported container/codec (mp3, flac, ogg/vorbis,
+/// m4a/aac, ...), decoded packet by packet.
+struct SymphoniaSource {
+    format: Box<dyn symphonia::core::formats::FormatReader>,
+    decoder: Box<dyn symphonia::core::codecs::Decoder>,
+    track_id: u32,
+    time_base: Option<symphonia::core::units::TimeBase>,
+    rate: u32,
+    channels: usize,
+    frames: Option<u64>,
+    sample_buf: Option<symphonia::core::audio::SampleBuffer<f32>>,
+    /// Samples decoded while probing for the rate, returned by the next read
+    pending: Vec<f32>,
+}
+
+impl SymphoniaSource {
+    fn open(path: &Path) -> Result<Self> {
+        use symphonia::core::codecs::{DecoderOptions, CODEC_TYPE_NULL};
+        use symphonia::core::formats::FormatOptions;
+        use symphonia::core::io::MediaSourceStream;
+        use symphonia::core::meta::MetadataOptions;
+        use symphonia::core::probe::Hint;
+
+        let file = std::fs::File::open(path).with_context(|| format!("Opening {}", path.display()))?;
+        let mss = MediaSourceStream::new(Box::new(file), Default::default());
+        let mut hint = Hint::new();
+        if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
+            hint.with_extension(ext);
         }
-        hound::SampleFormat::Int => {
-            if spec.bits_per_sample == 8 {
-                // 8-bit PCM (WAV) is unsigned on disk; hound exposes it as i8
-                for s in reader.samples::<i8>() {
-                    let v = (s? as f32) / 128.0;
-                    sum += v; cnt += 1;
-                    if cnt == channels { src_buf.push(sum / (channels as f32)); sum = 0.0; cnt = 0; }
-
-                    resample_drain(ratio, &mut src_buf, &mut src_pos, &mut out_buf);
-                    while out_buf.len() >= block {
-                        let chunk = &out_buf[..block];
-                        on_block(chunk);
-                        if realtime { throttle_realtime(chunk.len(), target_sr, start, &mut emitted_samples); }
-                        out_buf.drain(0..block);
+        let probed = symphonia::default::get_probe()
+            .format(&hint, mss, &FormatOptions::default(), &MetadataOptions::default())
+            .with_context(|| format!("Probing {}", path.display()))?;
+        let format = probed.format;
+        let track = format
+            .tracks()
+            .iter()
+            .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
+            .ok_or_else(|| anyhow!("No decodable audio track in {}", path.display()))?;
+        let params = &track.codec_params;
+        let decoder = symphonia::default::get_codecs()
+            .make(params, &DecoderOptions::default())
+            .with_context(|| "Creating decoder")?;
+        let mut src = Self {
+            track_id: track.id,
+            time_base: params.time_base,
+            rate: params.sample_rate.unwrap_or(0),
+            channels: params.channels.map(|c| c.count()).unwrap_or(0),
+            frames: params.n_frames,
+            format,
+            decoder,
+            sample_buf: None,
+            pending: Vec::new(),
+        };
+        if src.rate == 0 || src.channels == 0 {
+            // The container doesn't say; the first decoded packet does
+            let mut first = Vec::new();
+            src.read(&mut first)?;
+            src.pending = first;
+        }
+        Ok(src)
+    }
+}
+
+impl FileSource for SymphoniaSource {
+    fn rate(&self) -> u32 {
+        self.rate
+    }
+
+    fn channels(&self) -> usize {
+        self.channels
+    }
+
+    fn frames(&self) -> Option<u64> {
+        self.frames
+    }
+
+    fn seek(&mut self, frame: u64) -> Result<Option<u64>> {
+        use symphonia::core::errors::{Error as SymError, SeekErrorKind};
+        use symphonia::core::formats::{SeekMode, SeekTo};
+        use symphonia::core::units::Time;
+
+        let rate = self.rate.max(1) as f64;
+        let to = SeekTo::Time { time: Time::from(frame as f64 / rate), track_id: Some(self.track_id) };
+        match self.format.seek(SeekMode::Accurate, to) {
+            Ok(seeked) => {
+                self.decoder.reset();
+                self.pending.clear();
+                // Accurate seeks land on the packet holding `frame`; the
+                // emitter discards the rest up to it
+                let at = match self.time_base {
+                    Some(tb) => {
+                        let t = tb.calc_time(seeked.actual_ts);
+                        ((t.seconds as f64 + t.frac) * rate).round() as u64
                     }
+                    None => seeked.actual_ts,
+                };
+                Ok(Some(at.min(frame)))
+            }
+            Err(SymError::SeekError(SeekErrorKind::OutOfRange)) => Err(anyhow!("seek past the end of the stream")),
+            Err(SymError::SeekError(SeekErrorKind::Unseekable | SeekErrorKind::ForwardOnly)) => Ok(None),
+            Err(e) => Err(anyhow!("Seek error: {e}")),
+        }
+    }
+
+    fn read(&mut self, out: &mut Vec<f32>) -> Result<bool> {
+        use symphonia::core::audio::SampleBuffer;
+        use symphonia::core::errors::Error as SymError;
+
+        if !self.pending.is_empty() {
+            out.append(&mut self.pending);
+            return Ok(true);
+        }
+        loop {
+            let packet = match self.format.next_packet() {
+                Ok(p) => p,
+                Err(SymError::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(false),
+                Err(SymError::ResetRequired) => return Ok(false),
+                Err(e) => return Err(anyhow!("Demux error: {e}")),
+            };
+            if packet.track_id() != self.track_id {
+                continue;
+            }
+            let decoded = match self.decoder.decode(&packet) {
+                Ok(d) => d,
+                Err(SymError::DecodeError(_)) => continue, // skip corrupt packets
+                Err(e) => return Err(anyhow!("Decode error: {e}")),
+            };
+            let spec = *decoded.spec();
+            let channels = spec.channels.count().max(1);
+            if self.rate == 0 || self.channels == 0 {
+                self.rate = spec.rate;
+                self.channels = channels;
+            }
+            let buf = self
+                .sample_buf
+                .get_or_insert_with(|| SampleBuffer::<f32>::new(decoded.capacity() as u64, spec));
+            if buf.capacity() < decoded.capacity() * channels {
+                *buf = SampleBuffer::<f32>::new(decoded.capacity() as u64, spec);
+            }
+            buf.copy_interleaved_ref(decoded);
+            out.extend_from_slice(buf.samples());
+            return Ok(true);
+        }
+    }
+}
+
+/// Headerless samples from a regular file (seekable) or named pipe (not).
+struct RawSource {
+    reader: std::io::BufReader<std::fs::File>,
+    spec: RawSpec,
+    frames: Option<u64>,
+    bytes: Vec<u8>,
+    /// Bytes of a split frame carried to the next read
+    carried: usize,
+}
+
+impl RawSource {
+    fn open(path: &Path, spec: RawSpec) -> Result<Self> {
+        // Named pipes open like files; reads block until the writer sends
+        let file = std::fs::File::open(path).with_context(|| format!("Opening {}", path.display()))?;
+        let meta = file.metadata().with_context(|| format!("Reading {}", path.display()))?;
+        let frame_bytes = spec.format.width() * spec.channels.max(1);
+        Ok(Self {
+            reader: std::io::BufReader::new(file),
+            spec,
+            frames: meta.is_file().then(|| meta.len() / frame_bytes as u64),
+            bytes: vec![0u8; BLOCK * frame_bytes],
+            carried: 0,
+        })
+    }
+}
+
+impl FileSource for RawSource {
+    fn rate(&self) -> u32 {
+        self.spec.rate
+    }
+
+    fn channels(&self) -> usize {
+        self.spec.channels.max(1)
+    }
+
+    fn frames(&self) -> Option<u64> {
+        self.frames
+    }
+
+    fn seek(&mut self, frame: u64) -> Result<Option<u64>> {
+        use std::io::{Seek, SeekFrom};
+        if self.frames.is_none() {
+            return Ok(None);
+        }
+        let frame_bytes = (self.spec.format.width() * self.channels()) as u64;
+        self.reader.seek(SeekFrom::Start(frame * frame_bytes)).context("Seeking raw input")?;
+        self.carried = 0;
+        Ok(Some(frame))
+    }
+
+    fn read(&mut self, out: &mut Vec<f32>) -> Result<bool> {
+        let width = self.spec.format.width();
+        let frame_bytes = width * self.channels();
+        loop {
+            let n = match self.reader.read(&mut self.bytes[self.carried..]) {
+                Ok(0) => return Ok(false), // a trailing partial frame is dropped
+                Ok(n) => n,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e).context("Reading raw samples"),
+            };
+            let avail = self.carried + n;
+            let whole = avail - avail % frame_bytes;
+            let format = self.spec.format;
+            out.extend(self.bytes[..whole].chunks_exact(width).map(|b| format.decode(b)));
+            self.bytes.copy_within(whole..avail, 0);
+            self.carried = avail - whole;
+            if whole > 0 {
+                return Ok(true);
+            }
+        }
+    }
+}
+
+/// Play `range` of a file source. Without a control channel (headless
+/// render) the range plays once. With one, `Transport` commands seek, loop
+/// and change speed, and the decoder idles at the end of the range until a
+/// command moves the playhead or the UI hangs up.
+fn play<S: FileSource, F: FnMut(&[&[f32]])>(mut src: S, path: &Path, opts: &PipelineOptions, mut on_block: F) -> Result<()> {
+    let rate = src.rate().max(1) as f64;
+    let to_frame = |secs: f64| (secs.max(0.0) * rate).round() as u64;
+    let mut emitter = BlockEmitter::new(src.rate(), src.channels(), opts)?;
+    let first = to_frame(opts.range.start);
+    let len = src.frames();
+    if let Some(n) = len.filter(|&n| first > 0 && first >= n) {
+        return Err(past_end(path, opts.range.start, n as f64 / rate));
+    }
+    // Frames past the range (or the file) are never played
+    let last = match (opts.range.duration.map(|d| first + to_frame(d)), len) {
+        (Some(end), Some(n)) => Some(end.min(n)),
+        (end, n) => end.or(n),
+    };
+    // Frame the next read starts at
+    let mut pos = 0;
+    if first > 0 {
+        // Pipes can't seek; the emitter discards up to the start instead
+        if let Some(at) = src.seek(first).with_context(|| format!("Seeking {}", path.display()))? {
+            emitter.seeked_to(at);
+            pos = at;
+        }
+    }
+    let mut looping: Option<(u64, u64)> = None;
+    let mut finished = false;
+    let mut buf = Vec::with_capacity(BLOCK * src.channels());
+    loop {
+        let mut cmds: Vec<Transport> = opts.control.iter().flat_map(|rx| rx.try_iter()).collect();
+        if finished && cmds.is_empty() {
+            // Idle at the end until a command moves the playhead
+            match opts.control.as_ref().map(|rx| rx.recv()) {
+                Some(Ok(cmd)) => cmds.push(cmd),
+                _ => return Ok(()),
+            }
+        }
+        for cmd in cmds {
+            // Where to jump, if anywhere; the loop bounds always clamp it
+            let (lo, hi) = looping.unwrap_or((first, last.unwrap_or(u64::MAX)));
+            let target = match cmd {
+                Transport::SeekBy(secs) => {
+                    let t = pos as f64 + secs * rate;
+                    Some((t.max(0.0) as u64).clamp(lo, hi.saturating_sub(1).max(lo)))
                 }
-            } else {
-                let max = (1i64 << (spec.bits_per_sample - 1)) as f32;
-                for s in reader.samples::<i32>() {
-                    let v = s? as f32 / max;
-                    sum += v; cnt += 1;
-                    if cnt == channels { src_buf.push(sum / (channels as f32)); sum = 0.0; cnt = 0; }
-
-                    resample_drain(ratio, &mut src_buf, &mut src_pos, &mut out_buf);
-                    while out_buf.len() >= block {
-                        let chunk = &out_buf[..block];
-                        on_block(chunk);
-                        if realtime { throttle_realtime(chunk.len(), target_sr, start, &mut emitted_samples); }
-                        out_buf.drain(0..block);
+                Transport::Restart => Some(lo),
+                Transport::Speed(x) => {
+                    emitter.set_speed(x);
+                    None
+                }
+                Transport::Loop(Some((a, b))) => {
+                    let (a, b) = (to_frame(a).max(first), to_frame(b).min(last.unwrap_or(u64::MAX)));
+                    if b <= a {
+                        continue;
+                    }
+                    looping = Some((a, b));
+                    if (a..b).contains(&pos) && !finished {
+                        emitter.end_at(pos, Some(b));
+                        None
+                    } else {
+                        Some(a)
                     }
                 }
+                Transport::Loop(None) => {
+                    looping = None;
+                    emitter.end_at(pos, last);
+                    None
+                }
+            };
+            if let Some(target) = target {
+                let end = looping.map(|(_, b)| b).or(last);
+                if let Some(at) = jump(&mut src, &mut emitter, target, end, rate, opts, &mut on_block)? {
+                    pos = at;
+                    finished = false;
+                }
+            }
+        }
+        if finished {
+            continue;
+        }
+        buf.clear();
+        if !emitter.done() && src.read(&mut buf)? {
+            pos += (buf.len() / src.channels().max(1)) as u64;
+            emitter.feed(&buf, &mut on_block);
+            continue;
+        }
+        if let Some((a, b)) = looping {
+            // Play out this pass before wrapping
+            emitter.finish(&mut on_block);
+            if let Some(at) = jump(&mut src, &mut emitter, a, Some(b), rate, opts, &mut on_block)? {
+                pos = at;
+                continue;
             }
         }
+        emitter.finish(&mut on_block);
+        finished = true;
+        if opts.control.is_none() {
+            return Ok(());
+        }
     }
-    // Flush remaining
-    resample_drain(ratio, &mut src_buf, &mut src_pos, &mut out_buf);
-    while !out_buf.is_empty() {
-        let n = out_buf.len().min(block);
-        let chunk = &out_buf[..n];
-        on_block(chunk);
-        if realtime { throttle_realtime(chunk.len(), target_sr, start, &mut emitted_samples); }
-        out_buf.drain(0..n);
+}
+
+/// Reposition a playing source at `target`, playing on until `end`. The
+/// emitter drops buffered audio, and an empty `on_block` call marks the
+/// discontinuity so analysis state is rebuilt; the new position is
+/// published in `InputStats::resumed_at`. Returns the frame decoding
+/// resumes at, or `None` when the source can't seek.
+fn jump<S: FileSource, F: FnMut(&[&[f32]])>(
+    src: &mut S,
+    emitter: &mut BlockEmitter,
+    target: u64,
+    end: Option<u64>,
+    rate: f64,
+    opts: &PipelineOptions,
+    on_block: &mut F,
+) -> Result<Option<u64>> {
+    let Some(at) = src.seek(target)? else { return Ok(None) };
+    emitter.restart(at, target, end);
+    opts.stats.set_resumed_at(target as f64 / rate);
+    on_block(&[]);
+    Ok(Some(at))
+}
+
+fn past_end(path: &Path, start: f64, len: f64) -> anyhow::Error {
+    anyhow!(
+        "--start {} is past the end of {} ({})",
+        crate::view::format_clock(start),
+        path.display(),
+        crate::view::format_clock(len)
+    )
+}
+
+/// Synthesize a `gen:` signal. Endless signals are paced in real time, as a
+/// live source would be; finite ones (`len=` or a `--duration` window)
+/// honour `--realtime` like a file.
+fn run_gen<F: FnMut(&[&[f32]])>(spec: GenSpec, rate: u32, opts: &PipelineOptions, mut on_block: F) -> Result<()> {
+    let endless = spec.len.is_none() && opts.range.duration.is_none();
+    let opts = PipelineOptions { realtime: opts.realtime || endless, ..opts.clone() };
+    let mut emitter = BlockEmitter::new(rate, 1, &opts)?;
+    let mut gen = Generator::new(spec, rate);
+    let mut buf = vec![0.0f32; BLOCK];
+    loop {
+        let n = gen.fill(&mut buf);
+        emitter.feed(&buf[..n], &mut on_block);
+        if n < buf.len() || emitter.done() {
+            break;
+        }
     }
+    emitter.finish(&mut on_block);
     Ok(())
 }
 
-fn resample_drain(ratio: f32, src_buf: &mut Vec<f32>, src_pos: &mut f32, out_buf: &mut Vec<f32>) {
-    // ratio = dst_sr / src_sr. We step through source position by (src_sr / dst_sr)
-    if src_buf.len() < 2 { return; }
-    let step = if ratio > 0.0 { 1.0 / ratio } else { return; };
-    while *src_pos + 1.0 < src_buf.len() as f32 {
-        let i0 = (*src_pos).floor() as usize;
-        let frac = *src_pos - (i0 as f32);
-        let y = src_buf[i0] * (1.0 - frac) + src_buf[i0 + 1] * frac;
-        out_buf.push(y);
-        *src_pos += step;
-    }
-    // Drop consumed samples to avoid unbounded growth, keep one sample for interpolation
-    let consumed = (*src_pos).floor() as usize;
-    if consumed > 0 && consumed < src_buf.len() {
-        src_buf.drain(0..consumed);
-        *src_pos -= consumed as f32;
+/// Stream headerless interleaved samples until EOF.
+fn run_raw<R: Read, F: FnMut(&[&[f32]])>(reader: R, spec: RawSpec, opts: &PipelineOptions, mut on_block: F) -> Result<()> {
+    let mut emitter = BlockEmitter::new(spec.rate, spec.channels, opts)?;
+    stream_raw(reader, spec, &mut emitter, &mut on_block)?;
+    emitter.finish(&mut on_block);
+    Ok(())
+}
+
+/// Decode `reader` into `emitter` until EOF. Reads return whatever is
+/// available, so a sample or frame split across reads is carried over; a
+/// trailing partial frame at EOF is dropped.
+fn stream_raw<R: Read, F: FnMut(&[&[f32]])>(
+    mut reader: R,
+    spec: RawSpec,
+    emitter: &mut BlockEmitter,
+    on_block: &mut F,
+) -> Result<()> {
+    let frame_bytes = spec.format.width() * spec.channels.max(1);
+    let mut bytes = vec![0u8; BLOCK * frame_bytes];
+    let mut carried = 0;
+    let mut samples: Vec<f32> = Vec::with_capacity(BLOCK * spec.channels);
+    loop {
+        let n = match reader.read(&mut bytes[carried..]) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e).context("Reading raw samples"),
+        };
+        let avail = carried + n;
+        let whole = avail - avail % frame_bytes;
+        decode_into(&bytes[..whole], spec.format, &mut samples);
+        emitter.feed(&samples, on_block);
+        if emitter.done() {
+            break;
+        }
+        bytes.copy_within(whole..avail, 0);
+        carried = avail - whole;
     }
+    Ok(())
 }
 
-#[cfg(test)]
-mod tests_resample {
-    use super::*;
+fn decode_into(bytes: &[u8], format: RawFormat, samples: &mut Vec<f32>) {
+    samples.clear();
+    samples.extend(bytes.chunks_exact(format.width()).map(|b| format.decode(b)));
+}
 
-    #[test]
-    fn upsample_produces_more_samples() {
-        // simple ramp source of 100 samples
-        let mut src: Vec<f32> = (0..100).map(|i| i as f32).collect();
-        let mut out: Vec<f32> = Vec::new();
-        let mut pos = 0.0f32;
-        // 2x upsample: dst_sr = 2 * src_sr => ratio=2.0
-        resample_drain(2.0, &mut src, &mut pos, &mut out);
-        // Should generate roughly 200 samples minus edge, allow some tolerance
-        assert!(out.len() >= 180, "upsample produced too few samples: {}", out.len());
+/// First retry delay after a TCP drop; doubles per failed attempt.
+const BACKOFF_MIN: Duration = Duration::from_millis(100);
+const BACKOFF_MAX: Duration = Duration::from_secs(5);
+/// A UDP sender counts as gone after this long without a datagram: a
+/// non-persistent stream ends, a persistent one shows as disconnected.
+const UDP_IDLE_END: Duration = Duration::from_secs(2);
+/// Sequence numbers further back than this are a restarted sender, not a
+/// late packet.
+const UDP_REORDER_WINDOW: i32 = 64;
+
+fn run_tcp<F: FnMut(&[&[f32]])>(addr: &str, spec: RawSpec, persistent: bool, opts: &PipelineOptions, mut on_block: F) -> Result<()> {
+    let mut emitter = BlockEmitter::new(spec.rate, spec.channels, opts)?;
+    let mut backoff = BACKOFF_MIN;
+    let mut sessions = 0u64;
+    loop {
+        match std::net::TcpStream::connect(addr) {
+            Ok(stream) => {
+                if sessions > 0 {
+                    opts.stats.reconnects.fetch_add(1, Ordering::Relaxed);
+                }
+                sessions += 1;
+                backoff = BACKOFF_MIN;
+                opts.stats.connected.store(true, Ordering::Relaxed);
+                let res = stream_raw(stream, spec, &mut emitter, &mut on_block);
+                opts.stats.connected.store(false, Ordering::Relaxed);
+                if !persistent {
+                    res?;
+                    break;
+                }
+                // The --start/--duration range is complete: no reconnect
+                if emitter.done() {
+                    break;
+                }
+                // Show what arrived before the drop instead of holding it
+                // until the next connection fills a block
+                emitter.emit_partial(&mut on_block);
+            }
+            Err(e) if !persistent => return Err(e).with_context(|| format!("Connecting to tcp://{addr}")),
+            Err(_) => {}
+        }
+        std::thread::sleep(backoff);
+        backoff = (backoff * 2).min(BACKOFF_MAX);
     }
+    emitter.finish(&mut on_block);
+    Ok(())
+}
 
-    #[test]
-    fn downsample_produces_fewer_samples() {
-        let mut src: Vec<f32> = (0..100).map(|i| (i as f32).sin()).collect();
-        let mut out: Vec<f32> = Vec::new();
-        let mut pos = 0.0f32;
-        // 0.5x (dst_sr = 0.5 * src_sr) => ratio=0.5
-        resample_drain(0.5, &mut src, &mut pos, &mut out);
-        assert!(out.len() <= 60, "downsample produced too many samples: {}", out.len());
+fn run_udp<F: FnMut(&[&[f32]])>(
+    addr: &str,
+    spec: RawSpec,
+    seq: bool,
+    persistent: bool,
+    opts: &PipelineOptions,
+    mut on_block: F,
+) -> Result<()> {
+    let sock = std::net::UdpSocket::bind(addr).with_context(|| format!("Binding udp://{addr}"))?;
+    sock.set_read_timeout(Some(UDP_IDLE_END))?;
+    let mut emitter = BlockEmitter::new(spec.rate, spec.channels, opts)?;
+    let frame_bytes = spec.format.width() * spec.channels.max(1);
+    let mut buf = vec![0u8; 65_536];
+    let mut samples: Vec<f32> = Vec::new();
+    let mut expected: Option<u32> = None;
+    let mut last_frames = 0usize;
+    loop {
+        let n = match sock.recv(&mut buf) {
+            Ok(n) => n,
+            Err(e) if matches!(e.kind(), std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut) => {
+                opts.stats.connected.store(false, Ordering::Relaxed);
+                if persistent {
+                    continue;
+                }
+                break;
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e).context("Receiving UDP datagram"),
+        };
+        opts.stats.connected.store(true, Ordering::Relaxed);
+        let mut payload = &buf[..n];
+        if seq {
+            let Some((head, rest)) = payload.split_first_chunk::<4>() else { continue };
+            let s = u32::from_le_bytes(*head);
+            payload = rest;
+            if let Some(exp) = expected {
+                let gap = s.wrapping_sub(exp) as i32;
+                if gap < -UDP_REORDER_WINDOW {
+                    // The sender restarted its count: follow it, nothing was lost
+                } else if gap < 0 {
+                    continue; // late or duplicate: its slot was already filled
+                } else if gap > 0 {
+                    opts.stats.lost_packets.fetch_add(gap as u64, Ordering::Relaxed);
+                    // Silence stands in for the missing packets so the time
+                    // axis stays true (at most one second per gap)
+                    let frames = (gap as usize * last_frames).min(spec.rate as usize);
+                    samples.clear();
+                    samples.resize(frames * spec.channels, 0.0);
+                    emitter.feed(&samples, &mut on_block);
+                }
+            }
+            expected = Some(s.wrapping_add(1));
+        }
+        // A datagram is self-contained: a trailing partial frame is dropped
+        let whole = payload.len() - payload.len() % frame_bytes;
+        last_frames = whole / frame_bytes;
+        decode_into(&payload[..whole], spec.format, &mut samples);
+        emitter.feed(&samples, &mut on_block);
+        if emitter.done() {
+            break;
+        }
     }
+    opts.stats.connected.store(false, Ordering::Relaxed);
+    emitter.finish(&mut on_block);
+    Ok(())
 }
 
-fn throttle_realtime(emitted_now: usize, sr: u32, start: std::time::Instant, emitted_total: &mut usize) {
+fn throttle_realtime(emitted_now: usize, rate: f64, start: Instant, emitted_total: &mut usize) {
     *emitted_total += emitted_now;
-    let target = std::time::Duration::from_secs_f32((*emitted_total as f32) / (sr as f32));
+    let target = std::time::Duration::from_secs_f64((*emitted_total as f64) / rate);
     let elapsed = start.elapsed();
     if target > elapsed {
         let sleep_dur = target - elapsed;
@@ -253,8 +1068,9 @@ fn throttle_realtime(emitted_now: usize, sr: u32, start: std::time::Instant, emi
 }
 
 #[cfg(feature = "mic")]
-fn run_mic<F: FnMut(&[f32]) + Send + 'static>(target_sr: u32, device_name: Option<String>, mut on_block: F) -> Result<()> {
+fn run_mic<F: FnMut(&[&[f32]]) + Send + 'static>(opts: &PipelineOptions, device_name: Option<String>, mut on_block: F) -> Result<()> {
     use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+    use crossbeam_channel::bounded;
     let host = cpal::default_host();
     let device = if let Some(name) = device_name {
         let mut found = None;
@@ -277,16 +1093,14 @@ fn run_mic<F: FnMut(&[f32]) + Send + 'static>(target_sr: u32, device_name: Optio
     let channels = config.channels as usize;
     let err_fn = |err| eprintln!("Stream error: {err}");
 
+    // Callbacks only convert to interleaved f32; channel selection happens
+    // on this thread so the audio callback stays cheap.
     let stream = match sample_format {
         cpal::SampleFormat::F32 => device.build_input_stream(
             &config,
             move |data: &[f32], _| {
-                let mut mono = Vec::with_capacity(data.len() / channels);
-                for frame in data.chunks_exact(channels) {
-                    mono.push(frame.iter().copied().sum::<f32>() / (channels as f32));
-                }
                 // Never block inside audio callback; drop if the queue is full
-                let _ = tx.try_send(mono);
+                let _ = tx.try_send(data.to_vec());
             },
             err_fn,
             None,
@@ -294,12 +1108,7 @@ fn run_mic<F: FnMut(&[f32]) + Send + 'static>(target_sr: u32, device_name: Optio
         cpal::SampleFormat::I16 => device.build_input_stream(
             &config,
             move |data: &[i16], _| {
-                let mut mono = Vec::with_capacity(data.len() / channels);
-                for frame in data.chunks_exact(channels) {
-                    let sum: i32 = frame.iter().map(|&v| v as i32).sum();
-                    mono.push((sum as f32) / (channels as f32) / (i16::MAX as f32));
-                }
-                let _ = tx.try_send(mono);
+                let _ = tx.try_send(data.iter().map(|&v| v as f32 / (i16::MAX as f32)).collect());
             },
             err_fn,
             None,
@@ -307,15 +1116,9 @@ fn run_mic<F: FnMut(&[f32]) + Send + 'static>(target_sr: u32, device_name: Optio
         cpal::SampleFormat::U16 => device.build_input_stream(
             &config,
             move |data: &[u16], _| {
-                let mut mono = Vec::with_capacity(data.len() / channels);
-                for frame in data.chunks_exact(channels) {
-                    let sum: u32 = frame.iter().map(|&v| v as u32).sum();
-                    // Center around 0
-                    let avg = (sum as f32) / (channels as f32);
-                    let centered = (avg - (u16::MAX as f32) / 2.0) / ((u16::MAX as f32) / 2.0);
-                    mono.push(centered);
-                }
-                let _ = tx.try_send(mono);
+                // Center around 0
+                let half = (u16::MAX as f32) / 2.0;
+                let _ = tx.try_send(data.iter().map(|&v| (v as f32 - half) / half).collect());
             },
             err_fn,
             None,
@@ -323,40 +1126,540 @@ fn run_mic<F: FnMut(&[f32]) + Send + 'static>(target_sr: u32, device_name: Optio
         _ => return Err(anyhow!("Unsupported sample format")),
     };
 
-    // Resample to target_sr if needed using the same linear resampler as WAV
     stream.play()?;
-    let mut src_buf: Vec<f32> = Vec::with_capacity(8192);
-    let mut out_buf: Vec<f32> = Vec::with_capacity(8192);
-    let mut src_pos: f32 = 0.0;
-    let block = 1024usize;
-    let ratio = (target_sr as f32) / (dev_sr as f32);
+    // The device clock paces the stream; never throttle live input
+    let live = PipelineOptions { realtime: false, ..opts.clone() };
+    let mut emitter = BlockEmitter::new(dev_sr, channels, &live)?;
     while let Ok(buf) = rx.recv() {
-        if (ratio - 1.0).abs() < f32::EPSILON {
-            // same sample rate, forward in blocks
-            let mut offset = 0;
-            while offset < buf.len() {
-                let n = (buf.len() - offset).min(block);
-                on_block(&buf[offset..offset + n]);
-                offset += n;
-            }
-        } else {
-            src_buf.extend_from_slice(&buf);
-            resample_drain(ratio, &mut src_buf, &mut src_pos, &mut out_buf);
-            while out_buf.len() >= block {
-                let chunk: Vec<f32> = out_buf.drain(0..block).collect();
-                on_block(&chunk);
-            }
-            if !out_buf.is_empty() {
-                // Push remaining samples to keep UI responsive at startup
-                let chunk: Vec<f32> = std::mem::take(&mut out_buf);
-                on_block(&chunk);
-            }
-        }
+        emitter.feed(&buf, &mut on_block);
+        // Push short remainders too, keeping the UI responsive at startup
+        emitter.emit_partial(&mut on_block);
     }
     Ok(())
 }
 
 #[cfg(not(feature = "mic"))]
-fn run_mic<F: FnMut(&[f32]) + Send + 'static>(_target_sr: u32, _device_name: Option<String>, _on_block: F) -> Result<()> {
+fn run_mic<F: FnMut(&[&[f32]]) + Send + 'static>(_opts: &PipelineOptions, _device_name: Option<String>, _on_block: F) -> Result<()> {
     Err(anyhow!("Binary built without 'mic' feature"))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Stereo 16-bit WAV: left = +0.5, right = -0.25 (constant), `n` frames.
+    fn write_wav(name: &str, sr: u32, n: usize) -> PathBuf {
+        let mut p = std::env::temp_dir();
+        p.push(format!("sgram_tui_input_{}_{}.wav", name, std::process::id()));
+        let spec = hound::WavSpec { channels: 2, sample_rate: sr, bits_per_sample: 16, sample_format: hound::SampleFormat::Int };
+        let mut w = hound::WavWriter::create(&p, spec).unwrap();
+        for _ in 0..n {
+            w.write_sample(16384i16).unwrap();
+            w.write_sample(-8192i16).unwrap();
+        }
+        w.finalize().unwrap();
+        p
+    }
+
+    fn opts(target_sr: Option<u32>, channel: ChannelSelect) -> PipelineOptions {
+        PipelineOptions {
+            target_sr,
+            quality: ResampleQuality::Medium,
+            realtime: false,
+            channel,
+            rate: Arc::new(AtomicU32::new(0)),
+            stats: Arc::new(InputStats::default()),
+            range: TimeRange::default(),
+            control: None,
+        }
+    }
+
+    /// Run a WAV through the pipeline; returns (rate, samples per channel).
+    fn collect(path: PathBuf, opts: &PipelineOptions) -> Result<(u32, Vec<Vec<f32>>)> {
+        let out = Arc::new(std::sync::Mutex::new(Vec::<Vec<f32>>::new()));
+        let o = out.clone();
+        run_input_pipeline(AudioInputKind::Wav(path), opts, move |blocks| {
+            let mut o = o.lock().unwrap();
+            o.resize(blocks.len(), Vec::new());
+            for (dst, b) in o.iter_mut().zip(blocks) {
+                dst.extend_from_slice(b);
+            }
+        })?;
+        let chans = std::mem::take(&mut *out.lock().unwrap());
+        Ok((opts.rate.load(Ordering::Acquire), chans))
+    }
+
+    #[test]
+    fn native_rate_reports_source_rate_without_resampling() {
+        let path = write_wav("native", 22_050, 22_050);
+        let (rate, chans) = collect(path.clone(), &opts(None, ChannelSelect::Mix)).unwrap();
+        assert_eq!(rate, 22_050);
+        assert_eq!(chans[0].len(), 22_050);
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn fixed_rate_resamples_and_reports_target() {
+        let path = write_wav("fixed", 22_050, 22_050);
+        let (rate, chans) = collect(path.clone(), &opts(Some(48_000), ChannelSelect::Mix)).unwrap();
+        assert_eq!(rate, 48_000);
+        assert_eq!(chans[0].len(), 48_000);
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn channel_selection_picks_expected_signal() {
+        let path = write_wav("chan", 8_000, 4_000);
+        let cases = [
+            (ChannelSelect::Mix, vec![0.125f32]),
+            (ChannelSelect::Index(0), vec![0.5]),
+            (ChannelSelect::Index(1), vec![-0.25]),
+            (ChannelSelect::Mid, vec![0.125]),
+            (ChannelSelect::Side, vec![0.375]),
+            (ChannelSelect::All, vec![0.5, -0.25]),
+        ];
+        for (sel, want) in cases {
+            let (_, chans) = collect(path.clone(), &opts(None, sel)).unwrap();
+            assert_eq!(chans.len(), want.len(), "{sel:?}");
+            for (c, w) in chans.iter().zip(&want) {
+                assert_eq!(c.len(), 4_000);
+                assert!(c.iter().all(|v| (v - w).abs() < 1e-4), "{sel:?}: expected {w}");
+            }
+        }
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn missing_channel_is_an_error() {
+        let path = write_wav("chan_oob", 8_000, 100);
+        let err = collect(path.clone(), &opts(None, ChannelSelect::Index(2))).unwrap_err();
+        assert!(err.to_string().contains("channel 3"), "{err}");
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn raw_iq_file_passes_both_parts_through() {
+        // cs16 with a trailing partial frame, read through a reader that
+        // splits samples across calls
+        let mut bytes = Vec::new();
+        for _ in 0..3000 {
+            bytes.extend_from_slice(&16384i16.to_le_bytes());
+            bytes.extend_from_slice(&(-8192i16).to_le_bytes());
+        }
+        bytes.extend_from_slice(&[1, 2, 3]);
+        struct Trickle(std::io::Cursor<Vec<u8>>);
+        impl Read for Trickle {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                let n = buf.len().min(333);
+                self.0.read(&mut buf[..n])
+            }
+        }
+        let spec = RawSpec { format: RawFormat::S16Le, channels: 2, rate: 1_000_000 };
+        let o = PipelineOptions { channel: ChannelSelect::Iq, ..opts(None, ChannelSelect::Mix) };
+        let mut out = [Vec::new(), Vec::new()];
+        run_raw(Trickle(std::io::Cursor::new(bytes)), spec, &o, |blocks| {
+            for (dst, b) in out.iter_mut().zip(blocks) {
+                dst.extend_from_slice(b);
+            }
+        })
+        .unwrap();
+        assert_eq!(o.rate.load(Ordering::Acquire), 1_000_000);
+        assert_eq!(out[0].len(), 3000);
+        assert_eq!(out[1].len(), 3000);
+        assert!(out[0].iter().all(|&v| v == 0.5) && out[1].iter().all(|&v| v == -0.25));
+    }
+
+    #[test]
+    fn raw_formats_decode_to_unit_scale() {
+        let cases: [(RawFormat, Vec<u8>, f32); 6] = [
+            (RawFormat::S16Le, (-16384i16).to_le_bytes().to_vec(), -0.5),
+            (RawFormat::S24Le, vec![0x00, 0x00, 0x40], 0.5),
+            (RawFormat::S24Le, vec![0x00, 0x00, 0xC0], -0.5),
+            (RawFormat::S32Le, (i32::MIN / 4).to_le_bytes().to_vec(), -0.25),
+            (RawFormat::F32Le, 0.75f32.to_le_bytes().to_vec(), 0.75),
+            (RawFormat::F64Le, (-0.125f64).to_le_bytes().to_vec(), -0.125),
+        ];
+        for (fmt, bytes, want) in cases {
+            assert_eq!(bytes.len(), fmt.width());
+            assert_eq!(fmt.decode(&bytes), want, "{fmt:?}");
+        }
+    }
+
+    #[test]
+    fn raw_file_streams_through_selection_and_resampling() {
+        // Stereo f64: left 0.5, right -0.25, ending in half a frame
+        let mut p = std::env::temp_dir();
+        p.push(format!("sgram_tui_input_raw_{}.f64", std::process::id()));
+        let mut bytes = Vec::new();
+        for _ in 0..8_000 {
+            bytes.extend_from_slice(&0.5f64.to_le_bytes());
+            bytes.extend_from_slice(&(-0.25f64).to_le_bytes());
+        }
+        bytes.extend_from_slice(&0.5f64.to_le_bytes());
+        std::fs::write(&p, bytes).unwrap();
+        let spec = RawSpec { format: RawFormat::F64Le, channels: 2, rate: 8_000 };
+        let o = opts(Some(16_000), ChannelSelect::Index(0));
+        let out = Arc::new(std::sync::Mutex::new(Vec::<f32>::new()));
+        let sink = out.clone();
+        run_input_pipeline(AudioInputKind::Raw { path: Some(p.clone()), spec }, &o, move |blocks| {
+            sink.lock().unwrap().extend_from_slice(blocks[0]);
+        })
+        .unwrap();
+        let out = out.lock().unwrap();
+        assert_eq!(o.rate.load(Ordering::Acquire), 16_000);
+        assert_eq!(out.len(), 16_000);
+        assert!((out[8_000] - 0.5).abs() < 1e-3);
+        let _ = std::fs::remove_file(p);
+    }
+
+    /// Mono s16le at 0.5 full scale.
+    fn pcm_s16(frames: usize) -> Vec<u8> {
+        (0..frames).flat_map(|_| 16384i16.to_le_bytes()).collect()
+    }
+
+    #[test]
+    fn tcp_stream_ends_at_disconnect() {
+        use std::io::Write;
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let sender = std::thread::spawn(move || {
+            let (mut conn, _) = listener.accept().unwrap();
+            // Odd-sized writes split samples across reads
+            for chunk in pcm_s16(2_000).chunks(777) {
+                conn.write_all(chunk).unwrap();
+            }
+        });
+        let spec = RawSpec { format: RawFormat::S16Le, channels: 1, rate: 8_000 };
+        let o = opts(None, ChannelSelect::Mix);
+        let out = Arc::new(std::sync::Mutex::new(Vec::<f32>::new()));
+        let sink = out.clone();
+        run_input_pipeline(AudioInputKind::Tcp { addr, spec, persistent: false }, &o, move |blocks| {
+            sink.lock().unwrap().extend_from_slice(blocks[0]);
+        })
+        .unwrap();
+        sender.join().unwrap();
+        let out = out.lock().unwrap();
+        assert_eq!(out.len(), 2_000);
+        assert!(out.iter().all(|&v| v == 0.5));
+    }
+
+    #[test]
+    fn tcp_reconnects_after_drop() {
+        use std::io::Write;
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        std::thread::spawn(move || {
+            for _ in 0..2 {
+                let (mut conn, _) = listener.accept().unwrap();
+                conn.write_all(&pcm_s16(1_000)).unwrap();
+            }
+        });
+        let spec = RawSpec { format: RawFormat::S16Le, channels: 1, rate: 8_000 };
+        // Two connections' worth, so the pipeline ends instead of retrying
+        // against the closed port
+        let o = PipelineOptions { range: TimeRange { start: 0.0, duration: Some(0.25) }, ..opts(None, ChannelSelect::Mix) };
+        let stats = o.stats.clone();
+        let (tx, rx) = crossbeam_channel::unbounded::<usize>();
+        std::thread::spawn(move || {
+            let _ = run_input_pipeline(AudioInputKind::Tcp { addr, spec, persistent: true }, &o, move |blocks| {
+                let _ = tx.send(blocks[0].len());
+            });
+        });
+        let mut got = 0;
+        loop {
+            match rx.recv_timeout(Duration::from_secs(10)) {
+                Ok(n) => got += n,
+                Err(crossbeam_channel::RecvTimeoutError::Disconnected) => break,
+                Err(crossbeam_channel::RecvTimeoutError::Timeout) => panic!("no data after reconnect"),
+            }
+        }
+        assert_eq!(got, 2_000);
+        assert_eq!(stats.reconnects.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn persistent_tcp_ends_with_its_duration() {
+        use std::io::Write;
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        std::thread::spawn(move || {
+            for _ in 0..4 {
+                let Ok((mut conn, _)) = listener.accept() else { return };
+                let _ = conn.write_all(&pcm_s16(1_000));
+            }
+        });
+        let spec = RawSpec { format: RawFormat::S16Le, channels: 1, rate: 8_000 };
+        // 0.25 s is two connections' worth
+        let o = PipelineOptions { range: TimeRange { start: 0.0, duration: Some(0.25) }, ..opts(None, ChannelSelect::Mix) };
+        let (tx, rx) = crossbeam_channel::unbounded::<usize>();
+        std::thread::spawn(move || {
+            let out = Arc::new(std::sync::Mutex::new(0usize));
+            let sink = out.clone();
+            let res = run_input_pipeline(AudioInputKind::Tcp { addr, spec, persistent: true }, &o, move |blocks| {
+                *sink.lock().unwrap() += blocks[0].len();
+            });
+            if res.is_ok() {
+                let _ = tx.send(*out.lock().unwrap());
+            }
+        });
+        let got = rx.recv_timeout(Duration::from_secs(10)).expect("persistent source kept reconnecting");
+        assert_eq!(got, 2_000);
+    }
+
+    #[test]
+    fn udp_counts_lost_packets_and_fills_gaps() {
+        let port = std::net::UdpSocket::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let addr = format!("127.0.0.1:{port}");
+        let dest = addr.clone();
+        std::thread::spawn(move || {
+            // Give the receiver time to bind
+            std::thread::sleep(Duration::from_millis(300));
+            let sock = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+            // seq 2 never arrives; a late duplicate of seq 1 is ignored
+            for seq in [0u32, 1, 3, 1, 4] {
+                let mut pkt = seq.to_le_bytes().to_vec();
+                pkt.extend_from_slice(&pcm_s16(100));
+                sock.send_to(&pkt, &dest).unwrap();
+                std::thread::sleep(Duration::from_millis(5));
+            }
+        });
+        let spec = RawSpec { format: RawFormat::S16Le, channels: 1, rate: 8_000 };
+        let o = opts(None, ChannelSelect::Mix);
+        let out = Arc::new(std::sync::Mutex::new(Vec::<f32>::new()));
+        let sink = out.clone();
+        run_input_pipeline(AudioInputKind::Udp { addr, spec, seq: true, persistent: false }, &o, move |blocks| {
+            sink.lock().unwrap().extend_from_slice(blocks[0]);
+        })
+        .unwrap();
+        let out = out.lock().unwrap();
+        assert_eq!(o.stats.lost_packets.load(Ordering::Relaxed), 1);
+        assert_eq!(out.len(), 500);
+        assert!(out[200..300].iter().all(|&v| v == 0.0), "gap not filled with silence");
+        assert!(out[300..].iter().all(|&v| v == 0.5));
+    }
+
+    #[test]
+    fn udp_follows_a_restarted_sender() {
+        let port = std::net::UdpSocket::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let addr = format!("127.0.0.1:{port}");
+        let dest = addr.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(300));
+            let sock = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+            // The sender restarts at seq 0 after 500 and 501
+            for seq in [500u32, 501, 0, 1, 2] {
+                let mut pkt = seq.to_le_bytes().to_vec();
+                pkt.extend_from_slice(&pcm_s16(100));
+                sock.send_to(&pkt, &dest).unwrap();
+                std::thread::sleep(Duration::from_millis(5));
+            }
+        });
+        let spec = RawSpec { format: RawFormat::S16Le, channels: 1, rate: 8_000 };
+        let o = opts(None, ChannelSelect::Mix);
+        let out = Arc::new(std::sync::Mutex::new(Vec::<f32>::new()));
+        let sink = out.clone();
+        run_input_pipeline(AudioInputKind::Udp { addr, spec, seq: true, persistent: false }, &o, move |blocks| {
+            sink.lock().unwrap().extend_from_slice(blocks[0]);
+        })
+        .unwrap();
+        let out = out.lock().unwrap();
+        assert_eq!(o.stats.lost_packets.load(Ordering::Relaxed), 0);
+        assert_eq!(out.len(), 500);
+        assert!(out.iter().all(|&v| v == 0.5));
+    }
+
+    #[test]
+    fn persistent_udp_goes_idle_and_ends_with_its_duration() {
+        let port = std::net::UdpSocket::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let addr = format!("127.0.0.1:{port}");
+        let spec = RawSpec { format: RawFormat::S16Le, channels: 1, rate: 8_000 };
+        // 0.05 s is four 100-frame datagrams
+        let o = PipelineOptions { range: TimeRange { start: 0.0, duration: Some(0.05) }, ..opts(None, ChannelSelect::Mix) };
+        let stats = o.stats.clone();
+        let (tx, rx) = crossbeam_channel::unbounded::<usize>();
+        std::thread::spawn(move || {
+            let _ = run_input_pipeline(AudioInputKind::Udp { addr, spec, seq: false, persistent: true }, &o, move |blocks| {
+                let _ = tx.send(blocks[0].len());
+            });
+        });
+        let sock = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let send = || sock.send_to(&pcm_s16(100), ("127.0.0.1", port)).unwrap();
+        std::thread::sleep(Duration::from_millis(300));
+        send();
+        let wait_connected = |want: bool, within: Duration| {
+            let t0 = Instant::now();
+            while stats.connected.load(Ordering::Relaxed) != want {
+                assert!(t0.elapsed() < within, "connected never became {want}");
+                std::thread::sleep(Duration::from_millis(20));
+            }
+        };
+        wait_connected(true, Duration::from_secs(2));
+        // The sender goes quiet: still running, but no longer connected
+        wait_connected(false, UDP_IDLE_END * 2);
+        // The sender keeps going well past the range; the stream still ends
+        let mut got = 0;
+        for _ in 0..50 {
+            send();
+            std::thread::sleep(Duration::from_millis(5));
+            while let Ok(n) = rx.try_recv() {
+                got += n;
+            }
+        }
+        loop {
+            match rx.recv_timeout(Duration::from_secs(10)) {
+                Ok(n) => got += n,
+                Err(crossbeam_channel::RecvTimeoutError::Disconnected) => break,
+                Err(crossbeam_channel::RecvTimeoutError::Timeout) => panic!("persistent UDP kept receiving past its range"),
+            }
+        }
+        assert_eq!(got, 400);
+        assert!(!stats.connected.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn generator_feeds_blocks_at_its_rate() {
+        let spec = GenSpec::parse("gen:sine:1000:len=0.5s").unwrap();
+        let o = opts(Some(48_000), ChannelSelect::All);
+        let out = Arc::new(std::sync::Mutex::new(Vec::<f32>::new()));
+        let sink = out.clone();
+        run_input_pipeline(AudioInputKind::Gen { spec, rate: 24_000 }, &o, move |blocks| {
+            assert_eq!(blocks.len(), 1);
+            sink.lock().unwrap().extend_from_slice(blocks[0]);
+        })
+        .unwrap();
+        // Generated at 24 kHz, resampled to the 48 kHz analysis rate
+        assert_eq!(o.rate.load(Ordering::Acquire), 48_000);
+        assert_eq!(out.lock().unwrap().len(), 24_000);
+    }
+
+    #[test]
+    fn iq_source_detects_format_and_needs_rate() {
+        let wav = iq_source(PathBuf::from("cap.wav"), None, None).unwrap();
+        assert!(matches!(wav, AudioInputKind::Iq { raw: None, .. }));
+        let raw = iq_source(PathBuf::from("cap.cf32"), None, Some(2_000_000)).unwrap();
+        assert!(matches!(raw, AudioInputKind::Iq { raw: Some(RawSpec { format: RawFormat::F32Le, channels: 2, .. }), .. }));
+        assert!(iq_source(PathBuf::from("cap.cs16"), None, None).is_err());
+        assert!(iq_source(PathBuf::from("cap.bin"), None, Some(1)).is_err());
+    }
+
+    #[test]
+    fn iq_needs_two_channels() {
+        assert!(ChannelSelect::Iq.validate(1).is_err());
+        assert!(ChannelSelect::Iq.validate(2).is_ok());
+        assert_eq!(ChannelSelect::Iq.labels(1), vec!["I/Q"]);
+    }
+
+    #[test]
+    fn stereo_labels() {
+        assert_eq!(ChannelSelect::All.labels(2), vec!["left", "right"]);
+        assert_eq!(ChannelSelect::All.labels(3), vec!["ch1", "ch2", "ch3"]);
+        assert_eq!(ChannelSelect::Index(1).labels(2), vec!["right"]);
+    }
+
+    #[test]
+    fn time_range_seeks_every_file_reader_to_the_exact_frame() {
+        // Sample i holds the value i, so the first output names the frame
+        let sr = 8_000;
+        let p = std::env::temp_dir().join(format!("sgram_tui_range_{}.wav", std::process::id()));
+        let spec = hound::WavSpec { channels: 1, sample_rate: sr, bits_per_sample: 16, sample_format: hound::SampleFormat::Int };
+        let mut w = hound::WavWriter::create(&p, spec).unwrap();
+        for i in 0..8_000i16 {
+            w.write_sample(i).unwrap();
+        }
+        w.finalize().unwrap();
+        let raw = p.with_extension("s16");
+        let pcm: Vec<u8> = (0..8_000i16).flat_map(|i| i.to_le_bytes()).collect();
+        std::fs::write(&raw, pcm).unwrap();
+
+        let o = PipelineOptions { range: TimeRange { start: 0.25, duration: Some(0.125) }, ..opts(None, ChannelSelect::Mix) };
+        let raw_spec = RawSpec { format: RawFormat::S16Le, channels: 1, rate: sr };
+        type Reader<'a> = Box<dyn FnOnce(&mut dyn FnMut(&[&[f32]])) -> Result<()> + 'a>;
+        let readers: [(&str, Reader); 3] = [
+            ("hound", Box::new(|f| play(WavSource::open(&p)?, &p, &o, f))),
+            ("symphonia", Box::new(|f| play(SymphoniaSource::open(&p)?, &p, &o, f))),
+            ("raw", Box::new(|f| play(RawSource::open(&raw, raw_spec)?, &raw, &o, f))),
+        ];
+        for (name, read) in readers {
+            let mut got = Vec::new();
+            read(&mut |blocks: &[&[f32]]| got.extend_from_slice(blocks[0])).unwrap();
+            assert_eq!(got.len(), 1_000, "{name}");
+            assert_eq!((got[0] * 32768.0).round(), 2_000.0, "{name}");
+            assert_eq!((got[999] * 32768.0).round(), 2_999.0, "{name}");
+        }
+
+        let late = PipelineOptions { range: TimeRange { start: 2.0, duration: None }, ..opts(None, ChannelSelect::Mix) };
+        let err = play(WavSource::open(&p).unwrap(), &p, &late, |_| {}).unwrap_err().to_string();
+        assert!(err.contains("past the end"), "{err}");
+        let _ = std::fs::remove_file(p);
+        let _ = std::fs::remove_file(raw);
+    }
+
+    /// Mono 16-bit WAV whose sample i holds the value i.
+    fn write_ramp(name: &str, sr: u32, n: i16) -> PathBuf {
+        let p = std::env::temp_dir().join(format!("sgram_tui_{name}_{}.wav", std::process::id()));
+        let spec = hound::WavSpec { channels: 1, sample_rate: sr, bits_per_sample: 16, sample_format: hound::SampleFormat::Int };
+        let mut w = hound::WavWriter::create(&p, spec).unwrap();
+        for i in 0..n {
+            w.write_sample(i).unwrap();
+        }
+        w.finalize().unwrap();
+        p
+    }
+
+    #[test]
+    fn transport_seek_marks_the_jump_and_resumes_there() {
+        let p = write_ramp("seek", 8_000, 8_000);
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let o = PipelineOptions { control: Some(rx), ..opts(None, ChannelSelect::Mix) };
+        tx.send(Transport::SeekBy(0.5)).unwrap();
+        // With the sender gone the decoder stops idling at the end
+        drop(tx);
+        let mut markers = 0;
+        let mut got = Vec::new();
+        play(WavSource::open(&p).unwrap(), &p, &o, |blocks: &[&[f32]]| match blocks.first() {
+            None => markers += 1,
+            Some(b) => got.extend_from_slice(b),
+        })
+        .unwrap();
+        assert_eq!(markers, 1);
+        assert_eq!(o.stats.resumed_at(), 0.5);
+        assert_eq!(got.len(), 4_000);
+        assert_eq!((got[0] * 32768.0).round(), 4_000.0);
+        let _ = std::fs::remove_file(p);
+    }
+
+    #[test]
+    fn transport_loop_repeats_the_region_exactly() {
+        let p = write_ramp("loop", 8_000, 8_000);
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let o = PipelineOptions { control: Some(rx), ..opts(None, ChannelSelect::Mix) };
+        // Pace at 8x so the test can stop the loop after a few passes
+        tx.send(Transport::Speed(8.0)).unwrap();
+        tx.send(Transport::Loop(Some((0.25, 0.375)))).unwrap();
+        let passes = Arc::new(std::sync::Mutex::new(Vec::<Vec<f32>>::new()));
+        let sink = passes.clone();
+        let path = p.clone();
+        let player = std::thread::spawn(move || {
+            play(WavSource::open(&path).unwrap(), &path, &o, |blocks: &[&[f32]]| {
+                let mut passes = sink.lock().unwrap();
+                match blocks.first() {
+                    None => passes.push(Vec::new()),
+                    Some(b) => passes.last_mut().expect("loop starts with a jump").extend_from_slice(b),
+                }
+            })
+        });
+        while passes.lock().unwrap().len() < 4 {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        tx.send(Transport::Loop(None)).unwrap();
+        drop(tx);
+        player.join().unwrap().unwrap();
+        let passes = passes.lock().unwrap();
+        for pass in &passes[..3] {
+            let first = (pass[0] * 32768.0).round();
+            let last = (pass[pass.len() - 1] * 32768.0).round();
+            assert_eq!((pass.len(), first, last), (1_000, 2_000.0, 2_999.0));
+        }
+        let _ = std::fs::remove_file(p);
+    }
+}
@@ -1,10 +1,18 @@
 mod app;
+mod calibrate;
 mod colors;
 mod config;
+mod cqt;
 mod dsp;
 mod export;
 mod font;
+mod gen;
 mod input;
+mod measure;
+mod pitch;
+mod psd;
+mod resample;
+mod trace;
 mod ui;
 mod view;
 
@@ -12,7 +20,7 @@ use anyhow::Result;
 use clap::{ArgAction, Parser, ValueEnum};
 
 use app::{AnimationStyle, App, BinsMode, ColorPalette, Settings};
-use app::FreqScale;
+use app::{FreqAxis, FreqScale};
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
 enum RenderArg { Cell, Half, Quad }
@@ -23,11 +31,11 @@ enum ResolutionArg { Low, Medium, High, Ultra }
 #[derive(Parser, Debug)]
 #[command(name = "sgram-tui", version, about = "Terminal spectrogram viewer", long_about = None)]
 struct Cli {
-    /// Input source: mic | wav | render | FILE
-    #[arg(value_name = "SOURCE", help = "mic | wav | render (headless PNG/CSV export) | FILE (wav/mp3/flac/ogg path)", required = false)]
+    /// Input source: mic | wav | render | psd | measure | calibrate | FILE | - | tcp://HOST:PORT | udp://BIND:PORT | gen:SIGNAL
+    #[arg(value_name = "SOURCE", help = "mic | wav | render (headless PNG/CSV export) | psd (long-term average spectrum of FILE to CSV/PNG) | measure (THD/THD+N/SINAD/SNR/SFDR of FILE as JSON) | calibrate (measure a calibrator tone on the mic or FILE) | FILE (wav/mp3/flac/ogg path) | - (raw PCM on stdin) | tcp://HOST:PORT | udp://BIND:PORT | gen:SIGNAL (sine:1000, sweep:20:20k:10s, white, pink, impulse:1s, multitone:100,1k)", required = false)]
     source: Option<String>,
 
-    /// Audio file path when SOURCE is 'wav', 'file', or 'render'
+    /// Audio file path when SOURCE is 'wav', 'file', 'render', 'psd', 'measure' or 'calibrate'
     #[arg(value_name = "FILE", required = false)]
     file: Option<String>,
 
@@ -43,17 +51,79 @@ struct Cli {
     #[arg(long, default_value_t = 256)]
     hop: usize,
 
-    /// Target sample rate for processing
-    #[arg(long, default_value_t = 48000)]
-    sample_rate: u32,
+    /// Target sample rate for processing, or 'native' to analyze at the source rate
+    /// [default: 48000, or native with --iq]
+    #[arg(long, value_parser = parse_sample_rate)]
+    sample_rate: Option<SampleRateArg>,
 
-    /// Minimum dB floor (lower = more dynamic range)
-    #[arg(long, default_value_t = -80.0, allow_negative_numbers = true)]
-    floor: f32,
+    /// Channel(s) to analyze: mix (downmix), N (1-based), left, right, mid, side, or all (stacked panes)
+    #[arg(long, default_value = "mix", value_parser = parse_channel)]
+    channel: input::ChannelSelect,
 
-    /// dB ceiling (upper bound, typically 0 dB)
+    /// Treat FILE as complex baseband I/Q: a stereo WAV (I left, Q right) or
+    /// raw interleaved cf32/cs16; shows a two-sided spectrum centered on DC
+    #[arg(long, default_value_t = false)]
+    iq: bool,
+
+    /// Raw I/Q sample format (default: from the file extension)
+    #[arg(long, value_enum)]
+    iq_format: Option<IqFormatArg>,
+
+    /// Sample rate of a raw I/Q file in Hz (raw files have no header)
+    #[arg(long)]
+    iq_rate: Option<u32>,
+
+    /// Center frequency in Hz added to frequency labels, e.g. 433.92e6
     #[arg(long, default_value_t = 0.0, allow_negative_numbers = true)]
-    ceil: f32,
+    center_freq: f64,
+
+    /// Read SOURCE as headerless PCM in this format (stdin and named pipes);
+    /// any --raw-* flag selects raw input [default: s16le]
+    #[arg(long, value_enum)]
+    raw_format: Option<RawFormatArg>,
+
+    /// Interleaved channels in raw PCM input [default: 1]
+    #[arg(long)]
+    raw_channels: Option<usize>,
+
+    /// Sample rate of raw PCM input in Hz [default: 48000]
+    #[arg(long)]
+    raw_rate: Option<u32>,
+
+    /// Start analysis this far into the source: 12:30.5, 1:02:03, 20s, 500ms
+    /// or plain seconds. Files seek there directly; time axes show file time
+    #[arg(long, value_parser = parse_time)]
+    start: Option<f64>,
+
+    /// Analyze only this much of the source from --start (e.g. 20s)
+    #[arg(long, value_parser = parse_time, conflicts_with = "end")]
+    duration: Option<f64>,
+
+    /// Stop analysis at this source time (same formats as --start)
+    #[arg(long, value_parser = parse_time)]
+    end: Option<f64>,
+
+    /// Seconds the ',' and '.' keys jump back/forward in a file
+    #[arg(long, default_value_t = 5.0)]
+    seek_step: f64,
+
+    /// UDP datagrams start with a little-endian u32 sequence number (enables
+    /// lost-packet counting)
+    #[arg(long, default_value_t = false)]
+    udp_seq: bool,
+
+    /// Resampler quality when the source rate differs from --sample-rate
+    #[arg(long, value_enum, default_value_t = ResampleArg::Medium)]
+    resample_quality: ResampleArg,
+
+    /// Minimum dB floor (lower = more dynamic range) [default: -80, plus the
+    /// calibration offset when calibrated]
+    #[arg(long, allow_negative_numbers = true)]
+    floor: Option<f32>,
+
+    /// dB ceiling (upper bound) [default: 0, plus the calibration offset]
+    #[arg(long, allow_negative_numbers = true)]
+    ceil: Option<f32>,
 
     /// Frames per second for UI updates
     #[arg(long, default_value_t = 30)]
@@ -63,11 +133,21 @@ struct Cli {
     #[arg(long, default_value_t = 1.0)]
     zoom: f32,
 
+    /// Lower edge of the displayed frequency band, e.g. 3.8k (with --center-freq,
+    /// an RF frequency). Overrides --zoom
+    #[arg(long, value_parser = parse_freq, allow_negative_numbers = true)]
+    fmin: Option<f64>,
+
+    /// Upper edge of the displayed frequency band, e.g. 4.2k
+    #[arg(long, value_parser = parse_freq, allow_negative_numbers = true)]
+    fmax: Option<f64>,
+
     /// Initial palette
     #[arg(long, value_enum, default_value_t = PaletteArg::Viridis)]
     palette: PaletteArg,
 
-    /// Animation style (horizontal sweep or vertical waterfall)
+    /// Animation style (horizontal sweep, vertical waterfall, spectrum
+    /// trace or fractional-octave bars)
     #[arg(long, value_enum, default_value_t = AnimArg::Waterfall)]
     style: AnimArg,
 
@@ -103,13 +183,96 @@ struct Cli {
     #[arg(long)]
     device: Option<String>,
 
-    /// Frequency scale for display
-    #[arg(long, value_enum, default_value_t = FreqArg::Linear)]
-    freq_scale: FreqArg,
+    /// Frequency scale for display [default: linear, log with --transform cqt]
+    #[arg(long, value_enum)]
+    freq_scale: Option<FreqArg>,
+
+    /// Analysis transform: stft (FFT bins), cqt (constant-Q bins
+    /// log-spaced from --cqt-fmin; --fft and --win then follow from it), or
+    /// reassigned (FFT bins with each bin's power moved to where it was
+    /// measured, for sharp ridges)
+    #[arg(long, value_enum, default_value_t = TransformArg::Stft)]
+    transform: TransformArg,
+
+    /// Constant-Q bins per octave
+    #[arg(long, default_value_t = 24, value_parser = clap::value_parser!(u32).range(3..=96))]
+    bins_per_octave: u32,
+
+    /// Lowest constant-Q bin in Hz; its window (Q periods) sets the frame
+    /// length, so lower means slower updates and more CPU
+    #[arg(long, default_value = "32.7", value_parser = parse_cqt_fmin)]
+    cqt_fmin: f32,
+
+    /// Frequency axis labels: hz, or notes (A4, C#5, ...) with a note and
+    /// cents hover readout and a tuner in the spectrum view
+    #[arg(long, value_enum, default_value_t = AxisArg::Hz)]
+    axis: AxisArg,
+
+    /// A4 reference for --axis notes, in Hz
+    #[arg(long, default_value = "440", value_parser = parse_a4)]
+    a4: f32,
+
+    /// Analysis window: hann, hamming, blackman, blackman-harris, flattop,
+    /// kaiser[:beta], gaussian[:sigma], tukey[:alpha] or chebyshev[:dB]
+    /// [default: hann, blackman-harris for the measure command]
+    #[arg(long, value_parser = parse_window)]
+    window: Option<dsp::WindowType>,
+
+    /// Multitaper estimation: average the power spectra of K Slepian (DPSS)
+    /// tapers of time-bandwidth NW, given as NW or NW:K (K defaults to
+    /// 2NW-1), in place of --window; e.g. 4 or 3:5
+    #[arg(long, value_parser = parse_multitaper)]
+    multitaper: Option<dsp::Multitaper>,
+
+    /// dB scaling: dbfs (tone amplitude), psd (dBFS/Hz) or asd (dBFS/√Hz);
+    /// the density units read a noise floor independently of N and window
+    /// [default: dbfs, psd for the psd command]
+    #[arg(long, value_enum)]
+    units: Option<UnitsArg>,
+
+    /// IEC 61672 frequency weighting applied to every bin (not with --iq)
+    #[arg(long, value_enum, default_value_t = WeightingArg::Z)]
+    weighting: WeightingArg,
+
+    /// Bands per octave in the octave view: 1, 3 or 6
+    #[arg(long, default_value = "3", value_parser = parse_octave)]
+    octave: u32,
+
+    /// Spectrum-view traces: comma list of avg, exp, max, min (or all);
+    /// `render --style spectrum` exports them [default: none, avg in render]
+    #[arg(long, value_parser = parse_traces)]
+    traces: Option<trace::TraceSet>,
+
+    /// Rows in the moving RMS average, 0 for every row since the reset
+    /// [default: 16, 0 in render]
+    #[arg(long)]
+    avg: Option<usize>,
+
+    /// Exponential average time constant
+    #[arg(long, default_value = "1s", value_parser = parse_time)]
+    tau: f64,
+
+    /// Percentile envelopes for `psd`, a comma list such as 10,50,90
+    #[arg(long, value_parser = parse_percentiles, default_value = "")]
+    percentiles: Percentiles,
+
+    /// Tone to measure distortion against (e.g. 1k); default: the strongest.
+    /// Also opens the distortion panel
+    #[arg(long, value_parser = parse_freq)]
+    fundamental: Option<f64>,
 
-    /// Analysis window function
-    #[arg(long, value_enum, default_value_t = WindowArg::Hann)]
-    window: WindowArg,
+    /// Highest harmonic order included in THD
+    #[arg(long, default_value_t = 5, value_parser = clap::value_parser!(u32).range(2..=50))]
+    harmonics: u32,
+
+    /// Track the fundamental (YIN) and draw its contour; PNG and CSV exports
+    /// include it
+    #[arg(long, default_value_t = false)]
+    pitch: bool,
+
+    /// f0 search range for --pitch, LO:HI (e.g. 80:800)
+    #[arg(long, default_value = "60:1000", value_parser = parse_pitch_range)]
+    pitch_range: (f32, f32),
 
     /// Bin display: all bins, or only local spectral maxima
     #[arg(long, value_enum, default_value_t = BinsArg::All)]
@@ -142,13 +305,25 @@ struct Cli {
     /// Disable microphone feature fallback check
     #[arg(long, action=ArgAction::SetTrue)]
     no_mic: bool,
+
+    /// Show plain dBFS even when a calibration is configured or stored
+    #[arg(long)]
+    uncalibrated: bool,
+
+    /// Calibrator level in dB SPL, for `calibrate`
+    #[arg(long, default_value_t = 94.0)]
+    cal_ref: f64,
+
+    /// Calibrator frequency, for `calibrate`
+    #[arg(long, default_value = "1000", value_parser = parse_freq)]
+    cal_freq: f64,
 }
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
 enum PaletteArg { Grayscale, Heat, Viridis, Jet, Inferno, Magma, Plasma, Purplefire }
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
-enum AnimArg { Horizontal, Waterfall, Spectrum }
+enum AnimArg { Horizontal, Waterfall, Spectrum, Octave }
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
 enum BinsArg { All, Peaks }
@@ -163,14 +338,173 @@ impl From<BinsArg> for BinsMode {
 enum FreqArg { Linear, Log, Mel }
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
-enum WindowArg { Hann, Hamming, Blackman }
+enum AxisArg { Hz, Notes }
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+enum TransformArg { Stft, Cqt, Reassigned }
 
-impl From<WindowArg> for dsp::WindowType {
-    fn from(v: WindowArg) -> Self {
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum SampleRateArg { Native, Hz(u32) }
+
+fn parse_sample_rate(s: &str) -> Result<SampleRateArg, String> {
+    if s.eq_ignore_ascii_case("native") {
+        return Ok(SampleRateArg::Native);
+    }
+    match s.parse::<u32>() {
+        Ok(hz) if hz > 0 => Ok(SampleRateArg::Hz(hz)),
+        _ => Err(format!("expected a rate in Hz or 'native', got '{s}'")),
+    }
+}
+
+fn parse_time(s: &str) -> Result<f64, String> {
+    view::parse_time(s).ok_or_else(|| format!("expected a time like 12:30.5, 20s or 500ms, got '{s}'"))
+}
+
+fn parse_freq(s: &str) -> Result<f64, String> {
+    view::parse_freq(s).ok_or_else(|| format!("expected a frequency like 3800, 3.8k or 433.92e6, got '{s}'"))
+}
+
+fn parse_window(s: &str) -> Result<dsp::WindowType, String> {
+    dsp::WindowType::parse(s).ok_or_else(|| {
+        format!("expected hann, hamming, blackman, blackman-harris, flattop, kaiser[:beta], gaussian[:sigma], tukey[:alpha 0..1] or chebyshev[:dB], got '{s}'")
+    })
+}
+
+fn parse_multitaper(s: &str) -> Result<dsp::Multitaper, String> {
+    dsp::Multitaper::parse(s).ok_or_else(|| format!("expected NW (1 to 16) or NW:K with K at most 2NW, got '{s}'"))
+}
+
+fn parse_traces(s: &str) -> Result<trace::TraceSet, String> {
+    trace::TraceSet::parse(s).ok_or_else(|| format!("expected a comma list of avg, exp, max, min (or all, none), got '{s}'"))
+}
+
+/// Comma list of percentiles; a newtype so clap takes the whole list as one value
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Percentiles(Vec<f32>);
+
+fn parse_percentiles(s: &str) -> Result<Percentiles, String> {
+    s.split(',')
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .map(|p| match p.parse::<f32>() {
+            Ok(v) if (0.0..=100.0).contains(&v) => Ok(v),
+            _ => Err(format!("invalid percentile '{p}' (expected 0..100)")),
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map(Percentiles)
+}
+
+fn parse_pitch_range(s: &str) -> Result<(f32, f32), String> {
+    let err = || format!("expected LO:HI in Hz like 60:1000 or 80:1.2k, got '{s}'");
+    let (lo, hi) = s.split_once(':').ok_or_else(err)?;
+    match (view::parse_freq(lo), view::parse_freq(hi)) {
+        (Some(lo), Some(hi)) if lo > 0.0 && lo < hi => Ok((lo as f32, hi as f32)),
+        _ => Err(err()),
+    }
+}
+
+fn parse_cqt_fmin(s: &str) -> Result<f32, String> {
+    match view::parse_freq(s) {
+        Some(hz) if (5.0..=5000.0).contains(&hz) => Ok(hz as f32),
+        _ => Err(format!("expected a lowest bin between 5 Hz and 5 kHz like 32.7 or 55, got '{s}'")),
+    }
+}
+
+fn parse_a4(s: &str) -> Result<f32, String> {
+    match view::parse_freq(s) {
+        Some(hz) if (200.0..=1000.0).contains(&hz) => Ok(hz as f32),
+        _ => Err(format!("expected an A4 reference in Hz between 200 and 1000 like 440 or 432, got '{s}'")),
+    }
+}
+
+fn parse_octave(s: &str) -> Result<u32, String> {
+    match s.trim_start_matches("1/") {
+        "1" => Ok(1),
+        "3" => Ok(3),
+        "6" => Ok(6),
+        _ => Err(format!("expected 1, 3 or 6 bands per octave, got '{s}'")),
+    }
+}
+
+fn parse_channel(s: &str) -> Result<input::ChannelSelect, String> {
+    use input::ChannelSelect;
+    Ok(match s.to_ascii_lowercase().as_str() {
+        "mix" | "mono" => ChannelSelect::Mix,
+        "left" | "l" => ChannelSelect::Index(0),
+        "right" | "r" => ChannelSelect::Index(1),
+        "mid" | "m" => ChannelSelect::Mid,
+        "side" | "s" => ChannelSelect::Side,
+        "all" => ChannelSelect::All,
+        n => match n.parse::<usize>() {
+            Ok(i) if i >= 1 => ChannelSelect::Index(i - 1),
+            _ => return Err(format!("expected mix, N (1-based), left, right, mid, side or all, got '{s}'")),
+        },
+    })
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+enum IqFormatArg { Cf32, Cs16 }
+
+impl From<IqFormatArg> for input::RawFormat {
+    fn from(v: IqFormatArg) -> Self {
         match v {
-            WindowArg::Hann => Self::Hann,
-            WindowArg::Hamming => Self::Hamming,
-            WindowArg::Blackman => Self::Blackman,
+            IqFormatArg::Cf32 => Self::F32Le,
+            IqFormatArg::Cs16 => Self::S16Le,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+enum RawFormatArg { S16le, S24le, S32le, F32le, F64le }
+
+impl From<RawFormatArg> for input::RawFormat {
+    fn from(v: RawFormatArg) -> Self {
+        match v {
+            RawFormatArg::S16le => Self::S16Le,
+            RawFormatArg::S24le => Self::S24Le,
+            RawFormatArg::S32le => Self::S32Le,
+            RawFormatArg::F32le => Self::F32Le,
+            RawFormatArg::F64le => Self::F64Le,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+enum ResampleArg { Low, Medium, High }
+
+impl From<ResampleArg> for resample::ResampleQuality {
+    fn from(v: ResampleArg) -> Self {
+        match v {
+            ResampleArg::Low => Self::Low,
+            ResampleArg::Medium => Self::Medium,
+            ResampleArg::High => Self::High,
+        }
+    }
+}
+
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+enum UnitsArg { Dbfs, Psd, Asd }
+
+impl From<UnitsArg> for dsp::SpectrumUnits {
+    fn from(v: UnitsArg) -> Self {
+        match v {
+            UnitsArg::Dbfs => Self::Dbfs,
+            UnitsArg::Psd => Self::Psd,
+            UnitsArg::Asd => Self::Asd,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+enum WeightingArg { A, C, Z }
+
+impl From<WeightingArg> for dsp::Weighting {
+    fn from(v: WeightingArg) -> Self {
+        match v {
+            WeightingArg::A => Self::A,
+            WeightingArg::C => Self::C,
+            WeightingArg::Z => Self::Z,
         }
     }
 }
@@ -196,6 +530,7 @@ impl From<AnimArg> for AnimationStyle {
             AnimArg::Horizontal => Self::Horizontal,
             AnimArg::Waterfall => Self::Waterfall,
             AnimArg::Spectrum => Self::Spectrum,
+            AnimArg::Octave => Self::Octave,
         }
     }
 }
@@ -211,25 +546,57 @@ impl From<RenderArg> for app::RenderMode {
 }
 
 impl From<FreqArg> for FreqScale { fn from(v: FreqArg) -> Self { match v { FreqArg::Linear => FreqScale::Linear, FreqArg::Log => FreqScale::Log, FreqArg::Mel => FreqScale::Mel } } }
+impl From<AxisArg> for FreqAxis { fn from(v: AxisArg) -> Self { match v { AxisArg::Hz => FreqAxis::Hz, AxisArg::Notes => FreqAxis::Notes } } }
 
-fn main() -> Result<()> {
-    let cli = Cli::parse();
-    // Load config defaults
-    let cfg = config::load_config();
+/// Raw PCM layout when any --raw-* flag is given; unset fields take the
+/// stdin defaults.
+fn raw_spec(cli: &Cli) -> Option<input::RawSpec> {
+    if cli.raw_format.is_none() && cli.raw_channels.is_none() && cli.raw_rate.is_none() {
+        return None;
+    }
+    let d = input::RawSpec::DEFAULT;
+    Some(input::RawSpec {
+        format: cli.raw_format.map(Into::into).unwrap_or(d.format),
+        channels: cli.raw_channels.unwrap_or(d.channels).max(1),
+        rate: cli.raw_rate.unwrap_or(d.rate).max(1),
+    })
+}
 
+fn build_settings(cli: &Cli, cfg: Option<&config::FileConfig>) -> Settings {
+    // I/Q captures are analyzed at their own rate unless told otherwise
+    let sample_rate = cli.sample_rate.unwrap_or(if cli.iq { SampleRateArg::Native } else { SampleRateArg::Hz(48000) });
+    let psd_command = cli.source.as_deref().is_some_and(|s| s.eq_ignore_ascii_case("psd"));
+    let measure_command = cli.source.as_deref().is_some_and(|s| s.eq_ignore_ascii_case("measure"));
     let mut settings = Settings {
         fft_size: cli.fft.max(16),
         hop_size: cli.hop.max(1).min(cli.fft.max(16)),
         window_len: cli.win.unwrap_or(cli.fft).min(cli.fft).max(16),
-        sample_rate: cli.sample_rate,
-        db_floor: cli.floor,
-        db_ceiling: cli.ceil,
+        // Native mode: 48000 is only a placeholder until the source reports its rate
+        sample_rate: match sample_rate { SampleRateArg::Hz(hz) => hz, SampleRateArg::Native => 48000 },
+        native_rate: sample_rate == SampleRateArg::Native,
+        resample_quality: cli.resample_quality.into(),
+        channel: cli.channel,
+        iq: cli.iq,
+        iq_format: cli.iq_format.map(Into::into),
+        iq_rate: cli.iq_rate,
+        raw: raw_spec(cli),
+        udp_seq: cli.udp_seq,
+        headless: false,
+        center_freq: cli.center_freq,
+        range: input::TimeRange {
+            start: cli.start.unwrap_or(0.0),
+            duration: cli.duration.or(cli.end.map(|e| e - cli.start.unwrap_or(0.0))),
+        },
+        seek_step: cli.seek_step.abs(),
+        db_floor: cli.floor.unwrap_or(-80.0),
+        db_ceiling: cli.ceil.unwrap_or(0.0),
         fps: cli.fps,
         zoom: cli.zoom.max(1.0),
+        band: (cli.fmin, cli.fmax),
         palette: cli.palette.into(),
         style: cli.style.into(),
-        detailed: cli.detailed || cfg.as_ref().map(|c| c.detailed).unwrap_or(false),
-        fullscreen: cli.fullscreen || cfg.as_ref().map(|c| c.fullscreen).unwrap_or(false),
+        detailed: cli.detailed || cfg.map(|c| c.detailed).unwrap_or(false),
+        fullscreen: cli.fullscreen || cfg.map(|c| c.fullscreen).unwrap_or(false),
         // Explicit --history always wins; otherwise the resolution preset decides
         history: cli.history.unwrap_or(match cli.resolution {
             ResolutionArg::Low => 256,
@@ -238,14 +605,36 @@ fn main() -> Result<()> {
             ResolutionArg::Ultra => 2048,
         }),
         render_mode: cli.render.map(Into::into).unwrap_or(app::RenderMode::Quad),
-        freq_scale: cli.freq_scale.into(),
+        freq_scale: cli
+            .freq_scale
+            .unwrap_or(if cli.transform == TransformArg::Cqt { FreqArg::Log } else { FreqArg::Linear })
+            .into(),
+        freq_axis: cli.axis.into(),
+        a4: cli.a4,
         alpha: if cli.alpha == 2 { 2 } else { 1 },
         pre_emphasis: cli.pre_emphasis,
         overview: cli.overview,
         realtime: cli.realtime,
         clamp_floor: cli.clamp_floor,
         normalize: cli.normalize,
-        window: cli.window.into(),
+        // Hann's sidelobes would read as noise in THD+N
+        window: cli.window.unwrap_or(if measure_command { dsp::WindowType::BlackmanHarris } else { dsp::WindowType::Hann }),
+        // A long-term average is a noise-density measurement unless asked otherwise
+        units: cli.units.unwrap_or(if psd_command { UnitsArg::Psd } else { UnitsArg::Dbfs }).into(),
+        calibration: None,
+        weighting: cli.weighting.into(),
+        octave_fraction: cli.octave,
+        traces: cli.traces.unwrap_or_default(),
+        avg_frames: cli.avg.unwrap_or(16),
+        trace_tau: cli.tau,
+        fundamental: cli.fundamental,
+        harmonics: cli.harmonics as usize,
+        pitch: cli.pitch,
+        pitch_range: cli.pitch_range,
+        cqt: (cli.transform == TransformArg::Cqt)
+            .then_some(cqt::CqtSpec { fmin: cli.cqt_fmin, bins_per_octave: cli.bins_per_octave }),
+        reassign: cli.transform == TransformArg::Reassigned,
+        multitaper: cli.multitaper,
         bins_mode: cli.bins.into(),
     };
 
@@ -253,12 +642,67 @@ fn main() -> Result<()> {
     if cli.resolution == ResolutionArg::Low && cli.render.is_none() {
         settings.render_mode = app::RenderMode::Cell;
     }
+    settings
+}
 
-    // Resolve input per simplified usage: [mic|wav|render|FILE] [FILE]
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    // Load config defaults
+    let cfg = config::load_config();
+    let settings = build_settings(&cli, cfg.as_ref());
+    if let (Some(lo), Some(hi)) = settings.band {
+        if lo >= hi {
+            return Err(anyhow::anyhow!("--fmin ({lo} Hz) must be below --fmax ({hi} Hz)"));
+        }
+    }
+    if settings.iq && settings.weighting != dsp::Weighting::Z {
+        return Err(anyhow::anyhow!("--weighting applies to audio; I/Q rows have no acoustic frequency axis"));
+    }
+    if settings.iq && settings.freq_axis == FreqAxis::Notes {
+        return Err(anyhow::anyhow!("--axis notes labels audio frequencies; it does not apply to --iq"));
+    }
+    if settings.iq && settings.pitch {
+        return Err(anyhow::anyhow!("--pitch tracks real audio; it does not apply to --iq"));
+    }
+    if settings.cqt.is_some() || settings.reassign {
+        let transform = if settings.reassign { "reassigned" } else { "cqt" };
+        if settings.iq {
+            return Err(anyhow::anyhow!("--transform {transform} analyzes real audio; it does not apply to --iq"));
+        }
+        if settings.style == AnimationStyle::Octave {
+            return Err(anyhow::anyhow!("--style octave sums FFT bins into bands; use it with --transform stft"));
+        }
+        if settings.fundamental.is_some() {
+            return Err(anyhow::anyhow!("--fundamental measures FFT bins; use it with --transform stft"));
+        }
+        if settings.multitaper.is_some() {
+            return Err(anyhow::anyhow!("--multitaper averages FFT tapers; use it with --transform stft"));
+        }
+    }
+    if settings.multitaper.is_some() && cli.window.is_some() {
+        return Err(anyhow::anyhow!("--multitaper replaces the analysis window; drop --window"));
+    }
+
+    // Resolve input per simplified usage: [mic|wav|render|psd|measure|calibrate|FILE|-] [FILE]
     let mut headless = false;
+    let mut calibrating = false;
+    let mut psd = false;
+    let mut measuring = false;
     let input_arg = if let Some(src) = &cli.source {
         let s = src.to_lowercase();
         if s == "mic" { "mic".to_string() }
+        else if s == "calibrate" && !std::path::Path::new(src).exists() {
+            calibrating = true;
+            cli.file.clone().unwrap_or_else(|| "mic".to_string())
+        }
+        else if s == "psd" && !std::path::Path::new(src).exists() {
+            psd = true;
+            cli.file.clone().ok_or_else(|| anyhow::anyhow!("Missing FILE after 'psd'"))?
+        }
+        else if s == "measure" && !std::path::Path::new(src).exists() {
+            measuring = true;
+            cli.file.clone().ok_or_else(|| anyhow::anyhow!("Missing FILE after 'measure'"))?
+        }
         else if s == "render" {
             if cli.file.is_none() && std::path::Path::new(src).exists() {
                 // A file literally named "render" — treat as a direct path
@@ -274,17 +718,59 @@ fn main() -> Result<()> {
             src.clone()
         }
     } else {
-        return Err(anyhow::anyhow!("Usage: sgram-tui [mic|wav|render|FILE] [FILE] [flags]"));
+        return Err(anyhow::anyhow!("Usage: sgram-tui [mic|wav|render|psd|measure|calibrate|FILE|-] [FILE] [flags]"));
     };
 
+    if (settings.cqt.is_some() || settings.reassign) && (calibrating || measuring) {
+        return Err(anyhow::anyhow!("calibrate and measure work on plain FFT bins; use --transform stft"));
+    }
+    let device = cli.device.clone().or_else(|| cfg.as_ref().and_then(|c| c.device.clone()));
+    if calibrating {
+        let store = config::calibrations_path().ok_or_else(|| anyhow::anyhow!("no config directory to store the calibration in"))?;
+        calibrate::run(&input_arg, settings, device, cli.cal_ref, cli.cal_freq, &store)?;
+        return Ok(());
+    }
+    let settings = if cli.uncalibrated {
+        settings
+    } else {
+        // Stored offsets belong to a microphone; config calibration applies to every input
+        let stored = config::calibrations_path().map(|p| config::load_calibrations(&p)).unwrap_or_default();
+        let key = (input_arg == "mic").then(|| device.as_deref().unwrap_or("default"));
+        let cal = calibrate::resolve(cfg.as_ref().and_then(|c| c.calibration.as_ref()), &stored, key)?;
+        apply_calibration(settings, cal, &cli)
+    };
+
+    if psd {
+        let stem = output_stem(&input_arg);
+        let out = psd::PsdOutput {
+            png: cli.png_path.unwrap_or_else(|| format!("{stem}_psd.png")).into(),
+            csv: cli.csv_path.unwrap_or_else(|| format!("{stem}_psd.csv")).into(),
+            percentiles: cli.percentiles.0,
+        };
+        return psd::run(&input_arg, settings, &out);
+    }
+
+    if measuring {
+        let report = measure::run(&input_arg, settings)?;
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
     if headless {
+        let mut settings = settings;
         // Offline figure export: process the whole file, no TUI.
         settings.history = 1_000_000;
         settings.realtime = false;
+        // Sources that never end on their own must be bounded
+        settings.headless = true;
+        // A figure wants the whole analyzed range averaged
+        settings.avg_frames = cli.avg.unwrap_or(0);
+        if cli.traces.is_none() {
+            settings.traces = trace::TraceSet { avg: true, ..Default::default() };
+        }
         return render_offline(&input_arg, settings, cli.png_path, cli.csv_path);
     }
 
-    let device = cli.device.or_else(|| cfg.as_ref().and_then(|c| c.device.clone()));
     let mut app = App::new(input_arg, settings, cli.no_mic, device)?;
 
     if let Some(p) = cli.png_path.or_else(|| cfg.as_ref().and_then(|c| c.png_path.clone())) { app.export_png_path = Some(p.into()); }
@@ -292,55 +778,86 @@ fn main() -> Result<()> {
     ui::run(&mut app)
 }
 
+/// Use `cal`, moving the default floor and ceiling (the ones not given on
+/// the command line) up by its offset so the color range still fits.
+fn apply_calibration(mut settings: Settings, cal: Option<calibrate::Calibration>, cli: &Cli) -> Settings {
+    if let Some(c) = cal {
+        if cli.floor.is_none() {
+            settings.db_floor += c.offset_db;
+        }
+        if cli.ceil.is_none() {
+            settings.db_ceiling += c.offset_db;
+        }
+    }
+    settings.calibration = cal;
+    settings
+}
+
+/// Base name for default output files: the input's file stem, or the
+/// scheme of a network source (tcp://host:port -> tcp_sgram.png).
+fn output_stem(input: &str) -> String {
+    match input.split_once("://") {
+        Some((scheme, _)) => scheme.to_string(),
+        None => std::path::Path::new(input)
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .filter(|s| s != "-")
+            .unwrap_or_else(|| "sgram".to_string()),
+    }
+}
+
 fn render_offline(
     input: &str,
     settings: Settings,
     png_path: Option<String>,
     csv_path: Option<String>,
 ) -> Result<()> {
-    use std::path::{Path, PathBuf};
+    use std::path::PathBuf;
     let mut app = App::new(input.to_string(), settings, true, None)?;
     // Drain the DSP pipeline until the decoder thread finishes and drops its sender
-    while let Ok(row) = app.spectrogram_rx.recv() {
-        app.push_row(row);
-        app.total_rows = app.total_rows.saturating_add(1);
-    }
-    if app.buffer.is_empty() {
-        // The decoder thread drops its sender (ending the recv loop) just
-        // before it records the failure; give it a moment to land.
-        let mut cause = None;
-        for _ in 0..50 {
-            if let Some(e) = app.pipeline_error.lock().unwrap().take() {
-                cause = Some(e);
-                break;
-            }
-            std::thread::sleep(std::time::Duration::from_millis(10));
-        }
-        let cause = cause.map(|e| format!(": {e}")).unwrap_or_default();
-        return Err(anyhow::anyhow!("no audio frames decoded from {input}{cause}"));
+    while let Ok(rows) = app.spectrogram_rx.recv() {
+        app.push_rows(rows);
+    }
+    app.sync_sample_rate();
+    if app.buffer().is_empty() {
+        return Err(app.no_frames_error(input));
     }
     // Use the normalized settings: the DSP hop may have been clamped
     let hop = app.settings.hop_size;
     let sr = app.settings.sample_rate as f32;
-    let bins = app.buffer.front().map(|r| r.len()).unwrap_or(0);
+    let bins = app.buffer().front().map(|r| r.len()).unwrap_or(0);
     let seconds = (app.total_rows as f32) * (hop as f32) / sr;
-    let stem = Path::new(input)
-        .file_stem()
-        .map(|s| s.to_string_lossy().to_string())
-        .unwrap_or_else(|| "sgram".to_string());
+    let stem = output_stem(input);
+    if app.style == AnimationStyle::Spectrum {
+        // Spectrum figures plot the traces (by default the average of every row)
+        let png: PathBuf = png_path.map(PathBuf::from).unwrap_or_else(|| PathBuf::from(format!("{stem}_spectrum.png")));
+        app.save_spectrum_png(&app.plot_traces(), png.clone(), 1200, 600)?;
+        println!("wrote {} ({} of {} rows, {:.2}s of audio)", png.display(), app.trace_set.label(), app.traces[0].count(), seconds);
+        if let Some(csv) = csv_path {
+            let csv = PathBuf::from(csv);
+            app.save_spectrum_csv(&app.plot_traces(), csv.clone())?;
+            println!("wrote {}", csv.display());
+        }
+        return Ok(());
+    }
     let png: PathBuf = png_path.map(PathBuf::from).unwrap_or_else(|| PathBuf::from(format!("{stem}_sgram.png")));
     let (w, h) = app.png_content_dims();
     app.save_png(png.clone(), w, h)?;
-    if app.total_rows > app.buffer.len() {
-        let kept = (app.buffer.len() as f32) * (hop as f32) / sr;
+    if app.total_rows > app.buffer().len() {
+        let kept = (app.buffer().len() as f32) * (hop as f32) / sr;
         eprintln!(
-            "warning: history capped at {} rows; figure shows only the last {:.2}s of {:.2}s",
-            app.buffer.len(), kept, seconds
+            "warning: history capped at {} rows; figure shows only the last {:.2}s of {:.2}s (pick a window with --start/--duration)",
+            app.buffer().len(), kept, seconds
         );
     }
+    let panels = if app.buffers.len() > 1 { format!(", {} channel panels", app.buffers.len()) } else { String::new() };
+    let from = match app.time_origin {
+        Some(t0) if t0 > 0.0 => format!(" from {}", view::format_clock(t0)),
+        _ => String::new(),
+    };
     println!(
-        "wrote {} ({} rows x {} bins, {:.2}s of audio)",
-        png.display(), app.total_rows, bins, seconds
+        "wrote {} ({} rows x {} bins, {:.2}s of audio{}{})",
+        png.display(), app.total_rows, bins, seconds, from, panels
     );
     if let Some(csv) = csv_path {
         let csv = PathBuf::from(csv);
@@ -349,3 +866,136 @@ fn render_offline(
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tmp(name: &str, ext: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("sgram_tui_main_{}_{}.{ext}", name, std::process::id()))
+    }
+
+    #[test]
+    fn calibrated_render_offsets_values_and_labels_units() {
+        let csv = tmp("cal", "csv");
+        let png = tmp("cal", "png");
+        let cli = Cli::parse_from(["sgram-tui", "render", "gen:sine:6000:level=-20:len=0.5s"]);
+        let cal = calibrate::Calibration { offset_db: 114.0, reference: calibrate::Reference::Spl };
+        let mut settings = apply_calibration(build_settings(&cli, None), Some(cal), &cli);
+        assert_eq!((settings.db_floor, settings.db_ceiling), (34.0, 114.0));
+        settings.headless = true;
+        render_offline("gen:sine:6000:level=-20:len=0.5s", settings, Some(png.display().to_string()), Some(csv.display().to_string())).unwrap();
+        let text = std::fs::read_to_string(&csv).unwrap();
+        assert!(text.lines().next().unwrap().ends_with("units=dB SPL"), "{}", text.lines().next().unwrap());
+        let peak = text.lines().nth(1).unwrap().split(',').map(|v| v.parse::<f32>().unwrap()).fold(f32::MIN, f32::max);
+        assert!((peak - 94.0).abs() < 0.1, "peak {peak} dB SPL");
+        let _ = std::fs::remove_file(csv);
+        let _ = std::fs::remove_file(png);
+    }
+
+    #[test]
+    fn render_spectrum_exports_averaged_traces() {
+        let png = tmp("traces", "png");
+        let csv = tmp("traces", "csv");
+        let src = "gen:white:level=-20:len=2s";
+        let cli = Cli::parse_from(["sgram-tui", "render", src, "--style", "spectrum", "--units", "psd", "--traces", "avg,max,min", "--avg", "0"]);
+        let mut settings = build_settings(&cli, None);
+        settings.headless = true;
+        render_offline(src, settings, Some(png.display().to_string()), Some(csv.display().to_string())).unwrap();
+        assert!(std::fs::metadata(&png).unwrap().len() > 0);
+        let text = std::fs::read_to_string(&csv).unwrap();
+        let mut lines = text.lines();
+        assert!(lines.next().unwrap().ends_with("units=dBFS/Hz rows=372"));
+        assert_eq!(lines.next(), Some("freq_hz,avg(all),max,min"));
+        let rows: Vec<Vec<f32>> = lines.map(|l| l.split(',').map(|v| v.parse().unwrap()).collect()).collect();
+        assert_eq!(rows.len(), 512);
+        assert_eq!((rows[0][0], rows[1][0]), (0.0, 46.875));
+        // White noise at 0.1 RMS: 4 * 0.01 / fs relative to a full-scale sine
+        let inner = &rows[8..504];
+        let avg = inner.iter().map(|r| r[1]).sum::<f32>() / inner.len() as f32;
+        assert!((avg - -60.8).abs() < 0.3, "average floor {avg:.2}");
+        // 372 rows of averaging flatten the floor; the holds bracket it
+        assert!(inner.iter().all(|r| (r[1] - avg).abs() < 1.5 && r[2] > r[1] + 3.0 && r[3] < r[1] - 3.0));
+        let _ = std::fs::remove_file(png);
+        let _ = std::fs::remove_file(csv);
+    }
+
+    #[test]
+    fn render_offline_generator_end_to_end() {
+        // 6 kHz is bin 128 at N=1024, fs=48k: the CSV peak must sit there at ~0 dBFS
+        let png = tmp("gen", "png");
+        let csv = tmp("gen", "csv");
+        let cli = Cli::parse_from(["sgram-tui", "render", "gen:sine:6000:len=1s"]);
+        let mut settings = build_settings(&cli, None);
+        settings.headless = true;
+        settings.history = 1_000_000;
+        render_offline(
+            "gen:sine:6000:len=1s",
+            settings,
+            Some(png.display().to_string()),
+            Some(csv.display().to_string()),
+        )
+        .unwrap();
+        assert!(std::fs::metadata(&png).unwrap().len() > 0);
+        let text = std::fs::read_to_string(&csv).unwrap();
+        assert!(text.starts_with("# fs=48000Hz N=1024 L=1024 H=256 win=hann units=dBFS\n"), "CSV header: {}", text.lines().next().unwrap());
+        let rows: Vec<Vec<f32>> = text
+            .lines()
+            .skip(1)
+            .map(|l| l.split(',').map(|v| v.parse().unwrap()).collect())
+            .collect();
+        // (48000 - 1024) / 256 + 1 full frames in one second
+        assert_eq!(rows.len(), 184);
+        for row in &rows {
+            let (bin, &db) = row.iter().enumerate().max_by(|a, b| a.1.partial_cmp(b.1).unwrap()).unwrap();
+            assert_eq!(bin, 128);
+            assert!(db.abs() < 0.1, "peak {db:.2} dBFS");
+        }
+        let _ = std::fs::remove_file(png);
+        let _ = std::fs::remove_file(csv);
+    }
+
+    #[test]
+    fn time_window_bounds_rows_and_labels_source_time() {
+        let cli = Cli::parse_from(["sgram-tui", "render", "gen:sine:1000", "--start", "1:05", "--end", "1:06"]);
+        let mut settings = build_settings(&cli, None);
+        settings.headless = true;
+        settings.history = 1_000_000;
+        let mut app = App::new("gen:sine:1000".to_string(), settings, true, None).unwrap();
+        while let Ok(rows) = app.spectrogram_rx.recv() {
+            app.push_rows(rows);
+        }
+        assert_eq!(app.total_rows, 184);
+        assert_eq!(app.time_origin, Some(65.0));
+        // Newest row starts 183 hops after the window start
+        assert_eq!(app.time_label(0.0), view::format_clock(65.0 + 183.0 * 256.0 / 48_000.0));
+        assert_eq!(app.time_label(0.0), "1:05.98");
+    }
+
+    #[test]
+    fn fmin_fmax_band_pans_and_zooms_about_its_center() {
+        let cli = Cli::parse_from(["sgram-tui", "gen:sine:4k:len=0.1s", "--fmin", "3.8k", "--fmax", "4200"]);
+        let mut app = App::new("gen:sine:4k:len=0.1s".to_string(), build_settings(&cli, None), true, None).unwrap();
+        assert_eq!(app.freq_map().band(), (3_800.0, 4_200.0));
+        // Pans move by a fraction of the band and stop at Nyquist
+        app.pan_freq(0.5);
+        assert_eq!(app.freq_map().band(), (4_000.0, 4_400.0));
+        for _ in 0..200 {
+            app.pan_freq(1.0);
+        }
+        assert_eq!(app.freq_map().band(), (23_600.0, 24_000.0));
+        // Zooming out keeps the band centered until it hits the edge
+        app.set_band(3_800.0, 4_200.0);
+        app.adjust_zoom(0.5);
+        assert_eq!(app.freq_map().band(), (3_600.0, 4_400.0));
+        // Hover and axis labels come from the same map
+        assert_eq!(app.freq_map().frac_to_freq(0.5), 4_000.0);
+        app.reset_band();
+        assert_eq!(app.freq_map().band(), (0.0, 24_000.0));
+
+        // An open upper edge runs to Nyquist
+        let cli = Cli::parse_from(["sgram-tui", "gen:white:len=0.1s", "--fmin", "12k"]);
+        let app = App::new("gen:white:len=0.1s".to_string(), build_settings(&cli, None), true, None).unwrap();
+        assert_eq!(app.freq_map().band(), (12_000.0, 24_000.0));
+    }
+}
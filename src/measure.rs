@@ -0,0 +1,333 @@
+//! Distortion and noise of a single tone, as on an audio analyzer: THD and
+//! THD+N over harmonics up to a chosen order, SINAD, ENOB, SNR and SFDR.
+//! Everything is read off one spectrum row (live: the displayed frame;
+//! `sgram-tui measure FILE`: the power average of every row, printed as
+//! JSON). Tone powers are sums of bin power over the window's main lobe,
+//! so they hold for off-bin tones and any window; a low-sidelobe window
+//! (blackman-harris, the `measure` default) keeps the fundamental's
+//! leakage out of the noise.
+
+use crate::app::{App, Settings};
+use crate::dsp::{Scalloping, SpectrumUnits, Weighting};
+use crate::view;
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+
+/// What to measure.
+#[derive(Copy, Clone, Debug)]
+pub struct MeasureSpec {
+    /// Track the strongest tone near this frequency instead of the
+    /// strongest tone overall
+    pub fundamental: Option<f64>,
+    /// Highest harmonic order included in THD (2 = second harmonic only)
+    pub harmonics: usize,
+    /// Half-width in bins of a tone's main lobe, over which its power is
+    /// summed; also the DC region left out of the noise
+    pub lobe_bins: usize,
+}
+
+impl MeasureSpec {
+    /// Lobe width from the window's noise bandwidth (zero padding included):
+    /// 4 bins for Hann, 5 for Blackman-Harris, 10 for flat-top.
+    pub fn new(fundamental: Option<f64>, harmonics: usize, enbw_bins: f32) -> Self {
+        Self { fundamental, harmonics, lobe_bins: (2.5 * enbw_bins).ceil().max(2.0) as usize }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Harmonic {
+    pub order: usize,
+    pub freq_hz: f64,
+    /// Level in the row's unit
+    pub level_db: f32,
+    /// Level relative to the fundamental
+    pub dbc: f32,
+}
+
+/// One measurement. Ratios are in dB relative to the fundamental (THD and
+/// THD+N are negative), with the matching percentages.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Distortion {
+    pub fundamental_hz: f64,
+    /// Fundamental level in the row's unit
+    pub fundamental_db: f32,
+    pub thd_db: f32,
+    pub thd_pct: f32,
+    pub thdn_db: f32,
+    pub thdn_pct: f32,
+    pub sinad_db: f32,
+    pub enob_bits: f32,
+    pub snr_db: f32,
+    /// Fundamental over the strongest other spectral component
+    pub sfdr_dbc: f32,
+    /// Harmonics 2..=order below Nyquist
+    pub harmonics: Vec<Harmonic>,
+}
+
+/// Power of `row[i]` on a linear scale
+fn power(db: f32) -> f64 {
+    10f64.powf(db as f64 / 10.0)
+}
+
+fn db10(ratio: f64) -> f32 {
+    (10.0 * ratio.max(1e-30).log10()) as f32
+}
+
+/// Measure the tone in a one-sided `row` of dB values (`hz_per_bin` apart).
+/// `None` when there is no tone clear of DC to measure.
+pub fn analyze(row: &[f32], hz_per_bin: f64, spec: &MeasureSpec, scallop: &Scalloping) -> Option<Distortion> {
+    let n = row.len();
+    let lobe = spec.lobe_bins;
+    if n <= 4 * lobe {
+        return None;
+    }
+    // Bins already counted: DC, then the fundamental and each harmonic
+    let mut claimed = vec![false; n];
+    claimed[..=lobe].iter_mut().for_each(|c| *c = true);
+    let claim = |center: usize, claimed: &mut Vec<bool>| -> f64 {
+        let (lo, hi) = (center.saturating_sub(lobe), (center + lobe + 1).min(n));
+        let mut sum = 0.0;
+        for i in lo..hi {
+            if !claimed[i] {
+                claimed[i] = true;
+                sum += power(row[i]);
+            }
+        }
+        sum
+    };
+
+    let (lo, hi) = match spec.fundamental {
+        Some(f) => {
+            let b = (f / hz_per_bin).round() as usize;
+            (b.saturating_sub(lobe).max(lobe + 1), (b + lobe + 1).min(n))
+        }
+        None => (lobe + 1, n),
+    };
+    if lo >= hi {
+        return None;
+    }
+    let (fb, fv) = view::max_bin_in(row, lo, hi);
+    if !fv.is_finite() {
+        return None;
+    }
+    let peak = view::interpolate_peak(row, fb, scallop);
+    let f0_bins = peak.bin as f64;
+    let p1 = claim(fb, &mut claimed);
+
+    let mut harmonics = Vec::new();
+    let mut p_harm = 0.0;
+    for order in 2..=spec.harmonics {
+        let center = f0_bins * order as f64;
+        if center + 1.0 >= n as f64 {
+            break;
+        }
+        // The harmonic's own peak, within a lobe of where it should be
+        let c = center.round() as usize;
+        let (hb, _) = view::max_bin_in(row, c.saturating_sub(lobe / 2), (c + lobe / 2 + 1).min(n));
+        let p = claim(hb, &mut claimed);
+        p_harm += p;
+        let dbc = db10(p / p1);
+        harmonics.push(Harmonic { order, freq_hz: center * hz_per_bin, level_db: peak.db + dbc, dbc });
+    }
+
+    // Noise: what is left, scaled up over the bins the tones and DC covered
+    let free: Vec<usize> = (0..n).filter(|&i| !claimed[i]).collect();
+    let noise_sum: f64 = free.iter().map(|&i| power(row[i])).sum();
+    let p_noise = noise_sum * (n - lobe - 1) as f64 / free.len().max(1) as f64;
+
+    // SFDR: the strongest bin outside the fundamental's lobe and DC
+    let spur = (lobe + 1..n)
+        .filter(|&i| i + lobe < fb || i > fb + lobe)
+        .max_by(|&a, &b| row[a].total_cmp(&row[b]))
+        .map(|i| view::interpolate_peak(row, i, scallop).db)
+        .unwrap_or(f32::NEG_INFINITY);
+
+    let thd = p_harm / p1;
+    let thdn = (p_harm + p_noise) / p1;
+    let sinad_db = db10((p1 + p_harm + p_noise) / (p_harm + p_noise));
+    Some(Distortion {
+        fundamental_hz: f0_bins * hz_per_bin,
+        fundamental_db: peak.db,
+        thd_db: db10(thd),
+        thd_pct: (100.0 * thd.sqrt()) as f32,
+        thdn_db: db10(thdn),
+        thdn_pct: (100.0 * thdn.sqrt()) as f32,
+        sinad_db,
+        enob_bits: (sinad_db - 1.76) / 6.02,
+        snr_db: db10(p1 / p_noise),
+        sfdr_dbc: peak.db - spur,
+        harmonics,
+    })
+}
+
+/// `measure`'s JSON report.
+#[derive(Debug, Serialize)]
+pub struct Report {
+    pub source: String,
+    pub sample_rate: u32,
+    pub fft: usize,
+    pub window: String,
+    pub unit: String,
+    pub rows: usize,
+    pub seconds: f64,
+    pub channels: Vec<ChannelReport>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChannelReport {
+    pub channel: String,
+    #[serde(flatten)]
+    pub distortion: Distortion,
+}
+
+/// Average the whole of `input` in power and measure every channel of it
+/// (`settings.fundamental`, up to `settings.harmonics`).
+pub fn run(input: &str, mut settings: Settings) -> Result<Report> {
+    if settings.iq {
+        return Err(anyhow!("measure needs a real (audio) signal, not I/Q"));
+    }
+    settings.headless = true;
+    settings.realtime = false;
+    settings.history = 1;
+    settings.avg_frames = 0;
+    settings.pitch = false;
+    settings.traces = crate::trace::TraceSet { avg: true, ..Default::default() };
+    // Levels in tone units; ratios don't depend on it
+    settings.units = SpectrumUnits::Dbfs;
+    settings.normalize = false;
+    settings.clamp_floor = false;
+    // A/C weighting would give each harmonic its own gain, and skew the ratios
+    settings.weighting = Weighting::Z;
+    let mut app = App::new(input.to_string(), settings, true, None)?;
+    while let Ok(rows) = app.spectrogram_rx.recv() {
+        app.push_rows(rows);
+    }
+    app.sync_sample_rate();
+    let rows = app.traces.first().map(|t| t.count()).unwrap_or(0);
+    if rows == 0 {
+        return Err(app.no_frames_error(input));
+    }
+    let hz_per_bin = app.settings.sample_rate as f64 / app.settings.fft_size as f64;
+    let spec = app.measure_spec();
+    let mut channels = Vec::new();
+    for (traces, name) in app.traces.iter().zip(&app.channel_labels) {
+        let avg = traces.get(crate::trace::TraceKind::Avg).unwrap_or_default();
+        let distortion = analyze(&avg, hz_per_bin, &spec, &app.scalloping)
+            .ok_or_else(|| anyhow!("no tone to measure in {input} ({name})"))?;
+        channels.push(ChannelReport { channel: name.clone(), distortion });
+    }
+    Ok(Report {
+        source: input.to_string(),
+        sample_rate: app.settings.sample_rate,
+        fft: app.settings.fft_size,
+        window: app.window_name(),
+        unit: app.unit_label(),
+        rows,
+        seconds: rows as f64 * app.row_secs(),
+        channels,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsp::{self, SpectrogramBuilder, WindowType};
+    use crate::trace::{TraceKind, Traces};
+
+    /// Power-averaged rows of `x` through a Blackman-Harris analyzer
+    fn averaged_row(x: &[f32], n: usize) -> Vec<f32> {
+        let mut spec = SpectrogramBuilder::new(n, n, n / 2).window(WindowType::BlackmanHarris).alpha(1).build();
+        let mut traces = Traces::new(0);
+        for row in spec.process_samples(x) {
+            traces.push(&row, 1.0);
+        }
+        traces.get(TraceKind::Avg).unwrap()
+    }
+
+    #[test]
+    fn harmonics_and_noise_read_their_levels() {
+        let (fs, n) = (48_000.0f64, 4096usize);
+        // -6 dBFS at 1001 Hz (off the bin grid), H2 at -40 dBc, H3 at -60 dBc,
+        // and uniform noise 60 dB below the fundamental's power
+        let noise_amp = (3.0 * 0.125e-6f64).sqrt();
+        let mut seed = 0x2545_f491u32;
+        let x: Vec<f32> = (0..fs as usize)
+            .map(|i| {
+                let t = i as f64 / fs;
+                let w = 2.0 * std::f64::consts::PI * 1001.0 * t;
+                seed ^= seed << 13;
+                seed ^= seed >> 17;
+                seed ^= seed << 5;
+                let u = seed as f64 / u32::MAX as f64 * 2.0 - 1.0;
+                (0.5 * w.sin() + 0.005 * (2.0 * w).sin() + 0.0005 * (3.0 * w).sin() + noise_amp * u) as f32
+            })
+            .collect();
+        let row = averaged_row(&x, n);
+        let spec = MeasureSpec::new(None, 5, dsp::enbw_bins(&[dsp::window_coeffs(WindowType::BlackmanHarris, n)], n));
+        let scallop = Scalloping::new(WindowType::BlackmanHarris, n, n);
+        let d = analyze(&row, fs / n as f64, &spec, &scallop).unwrap();
+        assert!((d.fundamental_hz - 1001.0).abs() < 0.5, "{}", d.fundamental_hz);
+        assert!((d.fundamental_db - -6.02).abs() < 0.1, "{}", d.fundamental_db);
+        assert_eq!(d.harmonics.iter().map(|h| h.order).collect::<Vec<_>>(), vec![2, 3, 4, 5]);
+        assert!((d.harmonics[0].dbc - -40.0).abs() < 0.2 && (d.harmonics[1].dbc - -60.0).abs() < 0.5, "{:?}", d.harmonics);
+        // THD: sqrt(0.01² + 0.001²) = 1.005%, -39.96 dB
+        assert!((d.thd_db - -39.96).abs() < 0.2 && (d.thd_pct - 1.005).abs() < 0.03, "THD {} dB {}%", d.thd_db, d.thd_pct);
+        assert!((d.snr_db - 60.0).abs() < 1.0, "SNR {}", d.snr_db);
+        // THD+N is dominated by H2, and SINAD mirrors it
+        assert!(d.thdn_db > d.thd_db && d.thdn_db - d.thd_db < 0.1);
+        assert!((d.sinad_db + d.thdn_db).abs() < 0.01);
+        assert!((d.enob_bits - (d.sinad_db - 1.76) / 6.02).abs() < 1e-4);
+        assert!((d.sfdr_dbc - 40.0).abs() < 0.3, "SFDR {}", d.sfdr_dbc);
+    }
+
+    #[test]
+    fn measure_command_reports_json() {
+        use clap::Parser;
+        let src = "gen:sine:1000:level=-20:len=1s";
+        let cli = crate::Cli::parse_from(["sgram-tui", "measure", src, "--harmonics", "3"]);
+        let report = run(src, crate::build_settings(&cli, None)).unwrap();
+        assert_eq!((report.window.as_str(), report.unit.as_str()), ("blackman-harris", "dBFS"));
+        let d = &report.channels[0].distortion;
+        assert!((d.fundamental_db - -20.0).abs() < 0.1 && (d.fundamental_hz - 1000.0).abs() < 1.0, "{d:?}");
+        // A clean generator tone: only leakage and rounding below it
+        assert!(d.thd_db < -90.0 && d.sfdr_dbc > 80.0, "{d:?}");
+        let json: serde_json::Value = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["channels"][0]["channel"], "mix");
+        assert_eq!(json["channels"][0]["harmonics"].as_array().unwrap().len(), 2);
+        assert!(json["channels"][0]["thdn_db"].is_number());
+    }
+
+    #[test]
+    fn measure_ignores_weighting() {
+        use clap::Parser;
+        // A-weighting lifts 3 kHz by 1.2 dB against 1 kHz; H3 must still read 0 dBc
+        let src = "gen:multitone:1000,3000:level=-20:len=1s";
+        let cli = crate::Cli::parse_from(["sgram-tui", "measure", src, "--fundamental", "1k", "--weighting", "a"]);
+        let report = run(src, crate::build_settings(&cli, None)).unwrap();
+        let d = &report.channels[0].distortion;
+        assert!((d.fundamental_db - -20.0).abs() < 0.1, "{d:?}");
+        assert!(d.harmonics[1].dbc.abs() < 0.1, "{:?}", d.harmonics);
+    }
+
+    #[test]
+    fn fundamental_search_follows_the_requested_frequency() {
+        let (fs, n) = (48_000.0f64, 4096usize);
+        // A louder tone at 5 kHz would win an open search
+        let x: Vec<f32> = (0..n * 4)
+            .map(|i| {
+                let t = i as f64 / fs;
+                (0.1 * (2.0 * std::f64::consts::PI * 1000.0 * t).sin() + 0.5 * (2.0 * std::f64::consts::PI * 5000.0 * t).sin()) as f32
+            })
+            .collect();
+        let row = averaged_row(&x, n);
+        let scallop = Scalloping::new(WindowType::BlackmanHarris, n, n);
+        let enbw = dsp::enbw_bins(&[dsp::window_coeffs(WindowType::BlackmanHarris, n)], n);
+        let open = analyze(&row, fs / n as f64, &MeasureSpec::new(None, 3, enbw), &scallop).unwrap();
+        assert!((open.fundamental_hz - 5000.0).abs() < 1.0);
+        let tracked = analyze(&row, fs / n as f64, &MeasureSpec::new(Some(1000.0), 5, enbw), &scallop).unwrap();
+        assert!((tracked.fundamental_hz - 1000.0).abs() < 1.0);
+        // 5 kHz is the fifth harmonic, 14 dB up
+        assert!((tracked.harmonics[3].dbc - 13.98).abs() < 0.2, "{:?}", tracked.harmonics);
+        assert!((tracked.sfdr_dbc - -13.98).abs() < 0.2);
+    }
+}
@@ -0,0 +1,162 @@
+//! Fundamental-frequency (f0) tracking with YIN (de Cheveigné & Kawahara,
+//! 2002). A tracker runs next to each channel's analyzer on the same sample
+//! blocks and with the same frame length and hop, so it yields exactly one
+//! estimate per spectrogram row.
+
+/// Cumulative-mean-normalized difference below which a dip counts as the
+/// period (the paper's absolute threshold)
+const YIN_THRESHOLD: f32 = 0.15;
+/// Frames quieter than this mean-square level (-70 dBFS) are unvoiced
+const SILENCE_POWER: f32 = 1e-7;
+
+use std::collections::VecDeque;
+
+/// One f0 estimate.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Pitch {
+    /// Fundamental in Hz, `None` when the frame is unvoiced
+    pub hz: Option<f32>,
+    /// 1 - the normalized difference at the chosen lag: near 1 for a clean
+    /// periodic frame, near 0 for noise
+    pub confidence: f32,
+}
+
+/// YIN over one frame. `range` bounds the f0 search in Hz.
+pub fn yin(frame: &[f32], sample_rate: u32, range: (f32, f32)) -> Pitch {
+    let sr = sample_rate as f32;
+    // The lag search needs the frame to hold two periods of the lowest f0
+    let tau_max = ((sr / range.0).ceil() as usize).min(frame.len() / 2);
+    let tau_min = ((sr / range.1).floor() as usize).max(2);
+    if tau_min + 2 > tau_max {
+        return Pitch::default();
+    }
+    // Integration window: as much of the frame as the longest lag leaves
+    let w = frame.len() - tau_max;
+    let power = frame[..w].iter().map(|x| x * x).sum::<f32>() / w as f32;
+    if power < SILENCE_POWER {
+        return Pitch::default();
+    }
+
+    // Difference function, then its cumulative mean normalization
+    let mut cmndf = vec![1.0f32; tau_max + 1];
+    let mut running = 0.0f64;
+    for tau in 1..=tau_max {
+        let d: f64 = frame[..w].iter().zip(&frame[tau..tau + w]).map(|(&a, &b)| ((a - b) as f64).powi(2)).sum();
+        running += d;
+        cmndf[tau] = if running > 0.0 { (d * tau as f64 / running) as f32 } else { 1.0 };
+    }
+
+    // First dip under the threshold, followed down to its minimum; failing
+    // that the global minimum, which stays unvoiced
+    let voiced = (tau_min..tau_max).find(|&t| cmndf[t] < YIN_THRESHOLD);
+    let tau = match voiced {
+        Some(mut t) => {
+            while t + 1 < tau_max && cmndf[t + 1] < cmndf[t] {
+                t += 1;
+            }
+            t
+        }
+        None => (tau_min..tau_max).min_by(|&a, &b| cmndf[a].total_cmp(&cmndf[b])).unwrap_or(tau_min),
+    };
+    let confidence = (1.0 - cmndf[tau]).clamp(0.0, 1.0);
+    if voiced.is_none() {
+        return Pitch { hz: None, confidence };
+    }
+
+    // Parabolic interpolation of the dip for a sub-sample period
+    let (a, b, c) = (cmndf[tau - 1], cmndf[tau], cmndf[tau + 1]);
+    let den = a - 2.0 * b + c;
+    let period = if den > 0.0 { tau as f32 + 0.5 * (a - c) / den } else { tau as f32 };
+    let hz = sr / period;
+    Pitch { hz: (hz >= range.0 && hz <= range.1).then_some(hz), confidence }
+}
+
+/// Median voiced f0 over history rows `r0..r1` (0 = newest), so a stray
+/// octave jump doesn't drag a pooled display cell off the contour.
+pub fn median_hz(track: &VecDeque<Pitch>, r0: usize, r1: usize) -> Option<f32> {
+    let mut voiced: Vec<f32> = track.range(r0.min(track.len())..r1.min(track.len())).filter_map(|p| p.hz).collect();
+    voiced.sort_by(f32::total_cmp);
+    voiced.get(voiced.len() / 2).copied()
+}
+
+/// Frames a channel's samples like `dsp::Spectrogram` and runs `yin` on
+/// every frame.
+pub struct PitchTracker {
+    frame_len: usize,
+    hop: usize,
+    sample_rate: u32,
+    range: (f32, f32),
+    buf: Vec<f32>,
+}
+
+impl PitchTracker {
+    pub fn new(frame_len: usize, hop: usize, sample_rate: u32, range: (f32, f32)) -> Self {
+        Self { frame_len, hop: hop.min(frame_len).max(1), sample_rate, range, buf: Vec::with_capacity(frame_len * 2) }
+    }
+
+    /// One estimate per complete frame, as `process_samples` yields rows.
+    pub fn process_samples(&mut self, samples: &[f32]) -> Vec<Pitch> {
+        self.buf.extend_from_slice(samples);
+        let mut out = Vec::new();
+        // Four periods of the lowest f0 are plenty; longer (constant-Q)
+        // frames only cost time, so YIN sees their centered part
+        let len = self.frame_len.min((4.0 * self.sample_rate as f32 / self.range.0).ceil() as usize);
+        let start = (self.frame_len - len) / 2;
+        while self.buf.len() >= self.frame_len {
+            out.push(yin(&self.buf[start..start + len], self.sample_rate, self.range));
+            let hop = self.hop.min(self.buf.len());
+            self.buf.drain(..hop);
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::TAU;
+
+    fn tone(f0: f32, sr: u32, n: usize) -> Vec<f32> {
+        // A few harmonics, the fundamental not the strongest
+        (0..n)
+            .map(|i| {
+                let t = i as f32 / sr as f32;
+                0.2 * (TAU * f0 * t).sin() + 0.3 * (TAU * 2.0 * f0 * t).sin() + 0.1 * (TAU * 3.0 * f0 * t).sin()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn yin_finds_the_fundamental_of_a_harmonic_tone() {
+        for f0 in [82.4, 220.0, 311.1, 880.0] {
+            let p = yin(&tone(f0, 48000, 2048), 48000, (60.0, 1000.0));
+            let hz = p.hz.unwrap_or_else(|| panic!("{f0} Hz unvoiced: {p:?}"));
+            assert!((hz - f0).abs() < f0 * 0.002, "{f0} Hz read {hz}");
+            assert!(p.confidence > 0.9, "{p:?}");
+        }
+    }
+
+    #[test]
+    fn silence_and_noise_are_unvoiced() {
+        assert_eq!(yin(&[0.0; 2048], 48000, (60.0, 1000.0)), Pitch::default());
+        let mut seed = 1u32;
+        let noise: Vec<f32> = (0..2048)
+            .map(|_| {
+                seed = seed.wrapping_mul(1664525).wrapping_add(1013904223);
+                (seed >> 8) as f32 / (1u32 << 24) as f32 - 0.5
+            })
+            .collect();
+        let p = yin(&noise, 48000, (60.0, 1000.0));
+        assert_eq!(p.hz, None, "{p:?}");
+        assert!(p.confidence < 0.5);
+    }
+
+    #[test]
+    fn tracker_yields_one_estimate_per_frame() {
+        let mut t = PitchTracker::new(1024, 256, 48000, (60.0, 1000.0));
+        let samples = tone(440.0, 48000, 4800);
+        // Split across calls like the pipeline's blocks
+        let n = t.process_samples(&samples[..1000]).len() + t.process_samples(&samples[1000..]).len();
+        assert_eq!(n, (4800 - 1024) / 256 + 1);
+    }
+}
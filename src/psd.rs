@@ -0,0 +1,179 @@
+//! Long-term average spectrum: `sgram-tui psd FILE` streams the whole
+//! source through the analyzer and averages every row in linear power
+//! (Welch's method: overlapping windowed segments, `--hop` apart), then
+//! writes the result as a CSV and a PNG line plot. Only running
+//! accumulators are kept, never the rows, so memory stays flat however
+//! long the file.
+
+use crate::app::{AnimationStyle, App, Settings};
+use crate::export::PlotTrace;
+use crate::trace::TraceSet;
+use anyhow::Result;
+use std::path::PathBuf;
+
+/// Lowest dB value the percentile histograms resolve; anything below
+/// counts here
+const HIST_MIN_DB: f32 = -240.0;
+/// Histogram slot width in dB
+const HIST_STEP_DB: f32 = 0.25;
+/// Slots per bin, covering -240..+200 dB
+const HIST_SLOTS: usize = 1760;
+
+/// Envelope colors, cycled through the requested percentiles
+const PERCENTILE_RGB: [[u8; 3]; 4] = [[240, 190, 70], [200, 130, 240], [190, 190, 190], [240, 140, 190]];
+
+/// What `run` writes.
+pub struct PsdOutput {
+    pub png: PathBuf,
+    pub csv: PathBuf,
+    /// Percentile envelopes (0..=100) to add to both
+    pub percentiles: Vec<f32>,
+}
+
+/// Per-bin histogram of row levels, for percentile envelopes in fixed
+/// memory however many rows are seen.
+#[derive(Clone, Debug, Default)]
+pub struct Histogram {
+    /// `HIST_SLOTS` counts per bin, bin-major
+    counts: Vec<u32>,
+    rows: u32,
+}
+
+impl Histogram {
+    pub fn push(&mut self, row: &[f32]) {
+        if self.counts.len() != row.len() * HIST_SLOTS {
+            self.counts = vec![0; row.len() * HIST_SLOTS];
+            self.rows = 0;
+        }
+        for (counts, &v) in self.counts.chunks_exact_mut(HIST_SLOTS).zip(row) {
+            let slot = ((v - HIST_MIN_DB) / HIST_STEP_DB).floor().clamp(0.0, (HIST_SLOTS - 1) as f32) as usize;
+            counts[slot] += 1;
+        }
+        self.rows += 1;
+    }
+
+    /// The `p`th percentile level of every bin, to within a slot, or `None`
+    /// before the first row.
+    pub fn percentile(&self, p: f32) -> Option<Vec<f32>> {
+        if self.rows == 0 {
+            return None;
+        }
+        // Nearest rank: the smallest level at or above p% of the rows
+        let rank = ((p / 100.0 * self.rows as f32).ceil() as u32).clamp(1, self.rows);
+        Some(
+            self.counts
+                .chunks_exact(HIST_SLOTS)
+                .map(|counts| {
+                    let mut seen = 0;
+                    let slot = counts.iter().position(|&c| {
+                        seen += c;
+                        seen >= rank
+                    });
+                    HIST_MIN_DB + (slot.unwrap_or(HIST_SLOTS - 1) as f32 + 0.5) * HIST_STEP_DB
+                })
+                .collect(),
+        )
+    }
+}
+
+/// "p90", or "p99.9"
+fn percentile_label(p: f32) -> String {
+    format!("p{}", (p * 100.0).round() / 100.0)
+}
+
+/// Average the whole of `input` and write `out`. `settings.traces` picks
+/// the traces besides the percentiles (the average, plus min/max hold for
+/// a full envelope).
+pub fn run(input: &str, mut settings: Settings, out: &PsdOutput) -> Result<()> {
+    settings.headless = true;
+    settings.realtime = false;
+    settings.style = AnimationStyle::Spectrum;
+    // The accumulators hold everything; keep a single row of history
+    settings.history = 1;
+    settings.avg_frames = 0;
+    settings.pitch = false;
+    if settings.traces.is_empty() {
+        settings.traces = TraceSet { avg: true, ..Default::default() };
+    }
+    let mut app = App::new(input.to_string(), settings, true, None)?;
+    let mut hists: Vec<Histogram> = Vec::new();
+    while let Ok(rows) = app.spectrogram_rx.recv() {
+        if !out.percentiles.is_empty() {
+            // An empty frame restarts the analysis (seek), as in push_rows
+            hists.resize_with(rows.len(), Histogram::default);
+            for (h, row) in hists.iter_mut().zip(&rows) {
+                h.push(row);
+            }
+        }
+        app.push_rows(rows);
+    }
+    app.sync_sample_rate();
+    let count = app.traces.first().map(|t| t.count()).unwrap_or(0);
+    if count == 0 {
+        return Err(app.no_frames_error(input));
+    }
+
+    let mut traces = app.plot_traces();
+    for (h, name) in hists.iter().zip(&app.channel_labels) {
+        for (i, &p) in out.percentiles.iter().enumerate() {
+            let Some(values) = h.percentile(p) else { continue };
+            let label = if hists.len() > 1 { format!("{name} {}", percentile_label(p)) } else { percentile_label(p) };
+            traces.push(PlotTrace { label, color: PERCENTILE_RGB[i % PERCENTILE_RGB.len()], values });
+        }
+    }
+    app.save_spectrum_csv(&traces, out.csv.clone())?;
+    app.save_spectrum_png(&traces, out.png.clone(), 1200, 600)?;
+    let seconds = count as f64 * app.row_secs();
+    println!("wrote {} and {} (Welch average of {count} rows, {seconds:.2}s of audio)", out.csv.display(), out.png.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn histogram_percentiles_track_each_bin() {
+        let mut h = Histogram::default();
+        for i in 0..100 {
+            h.push(&[-(i as f32), -50.0, -500.0]);
+        }
+        let p10 = h.percentile(10.0).unwrap();
+        let p90 = h.percentile(90.0).unwrap();
+        // Levels 0, -1, ... -99: the 10th lowest is -90, the 90th -10
+        assert!((p10[0] - -89.875).abs() < 0.3, "{p10:?}");
+        assert!((p90[0] - -9.875).abs() < 0.3, "{p90:?}");
+        assert!((p10[1] - -50.0).abs() <= HIST_STEP_DB && (p90[1] - -50.0).abs() <= HIST_STEP_DB);
+        // Below the histogram's range reads its bottom slot
+        assert!(p10[2] < -239.0);
+        assert_eq!(Histogram::default().percentile(50.0), None);
+        assert_eq!(percentile_label(99.9), "p99.9");
+    }
+
+    #[test]
+    fn psd_command_averages_the_whole_source() {
+        use clap::Parser;
+        let png = std::env::temp_dir().join(format!("sgram_tui_psd_{}.png", std::process::id()));
+        let csv = png.with_extension("csv");
+        let src = "gen:white:level=-20:len=3s";
+        let cli = crate::Cli::parse_from(["sgram-tui", "psd", src, "--percentiles", "10,90"]);
+        let settings = crate::build_settings(&cli, None);
+        let out = PsdOutput { png: png.clone(), csv: csv.clone(), percentiles: cli.percentiles.0.clone() };
+        run(src, settings, &out).unwrap();
+        assert!(std::fs::metadata(&png).unwrap().len() > 0);
+        let text = std::fs::read_to_string(&csv).unwrap();
+        let mut lines = text.lines();
+        // Density units by default, and every row of the source averaged
+        assert!(lines.next().unwrap().ends_with("units=dBFS/Hz rows=559"));
+        assert_eq!(lines.next(), Some("freq_hz,avg(all),p10,p90"));
+        let rows: Vec<Vec<f32>> = lines.map(|l| l.split(',').map(|v| v.parse().unwrap()).collect()).collect();
+        assert_eq!(rows.len(), 512);
+        // White noise at RMS 0.1: 10·log10(4·0.01/48000) = -60.8 dBFS/Hz
+        let inner = &rows[2..500];
+        let avg = inner.iter().map(|r| r[1]).sum::<f32>() / inner.len() as f32;
+        assert!((avg - -60.8).abs() < 0.3, "average floor {avg:.2}");
+        assert!(inner.iter().all(|r| r[2] < r[1] && r[1] < r[3]), "p10 < avg < p90 in every bin");
+        let _ = std::fs::remove_file(png);
+        let _ = std::fs::remove_file(csv);
+    }
+}
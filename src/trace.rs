@@ -0,0 +1,245 @@
+//! Spectrum traces, as on a bench analyzer: a moving RMS average, an
+//! exponential average, and max/min hold, accumulated from every analysis
+//! row as it arrives. The spectrum view overlays them on the live frame and
+//! `render --style spectrum` exports them as a line plot and CSV.
+
+use std::collections::VecDeque;
+
+/// One kind of trace.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TraceKind {
+    /// Linear-power (RMS) average over the last N rows
+    Avg,
+    /// Exponential power average with a time constant
+    Exp,
+    MaxHold,
+    MinHold,
+}
+
+impl TraceKind {
+    pub const ALL: [TraceKind; 4] = [TraceKind::Avg, TraceKind::Exp, TraceKind::MaxHold, TraceKind::MinHold];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Avg => "avg",
+            Self::Exp => "exp",
+            Self::MaxHold => "max",
+            Self::MinHold => "min",
+        }
+    }
+
+    /// Trace color, shared by the terminal and the PNG plot
+    pub fn rgb(self) -> [u8; 3] {
+        match self {
+            Self::Avg => [80, 220, 240],
+            Self::Exp => [120, 230, 90],
+            Self::MaxHold => [250, 80, 70],
+            Self::MinHold => [110, 140, 255],
+        }
+    }
+}
+
+/// Which traces are shown; any combination.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct TraceSet {
+    pub avg: bool,
+    pub exp: bool,
+    pub max: bool,
+    pub min: bool,
+}
+
+impl TraceSet {
+    /// Parse a comma list of `avg`, `exp`, `max`, `min`, or `all`/`none`.
+    pub fn parse(s: &str) -> Option<Self> {
+        let mut set = Self::default();
+        for name in s.to_ascii_lowercase().split(',').map(str::trim) {
+            match name {
+                "avg" | "rms" => set.avg = true,
+                "exp" => set.exp = true,
+                "max" => set.max = true,
+                "min" => set.min = true,
+                "all" => set = Self { avg: true, exp: true, max: true, min: true },
+                "none" | "off" => {}
+                _ => return None,
+            }
+        }
+        Some(set)
+    }
+
+    pub fn contains(self, kind: TraceKind) -> bool {
+        match kind {
+            TraceKind::Avg => self.avg,
+            TraceKind::Exp => self.exp,
+            TraceKind::MaxHold => self.max,
+            TraceKind::MinHold => self.min,
+        }
+    }
+
+    /// Enabled kinds in legend order: avg, exp, max, min
+    pub fn kinds(self) -> Vec<TraceKind> {
+        TraceKind::ALL.into_iter().filter(|&k| self.contains(k)).collect()
+    }
+
+    pub fn is_empty(self) -> bool {
+        self == Self::default()
+    }
+
+    /// "avg+max", or "off"
+    pub fn label(self) -> String {
+        let names: Vec<&str> = self.kinds().into_iter().map(TraceKind::name).collect();
+        if names.is_empty() { "off".to_string() } else { names.join("+") }
+    }
+
+    /// The trace key's presets: off, each trace alone, then avg+max+min.
+    pub fn next(self) -> Self {
+        const PRESETS: [TraceSet; 6] = [
+            TraceSet { avg: false, exp: false, max: false, min: false },
+            TraceSet { avg: true, exp: false, max: false, min: false },
+            TraceSet { avg: false, exp: true, max: false, min: false },
+            TraceSet { avg: false, exp: false, max: true, min: false },
+            TraceSet { avg: false, exp: false, max: false, min: true },
+            TraceSet { avg: true, exp: false, max: true, min: true },
+        ];
+        match PRESETS.iter().position(|&p| p == self) {
+            Some(i) => PRESETS[(i + 1) % PRESETS.len()],
+            // A custom --traces list starts the cycle over
+            None => PRESETS[0],
+        }
+    }
+}
+
+/// Trace accumulators for one channel. Rows are dB per bin; averages are
+/// taken in linear power, holds per bin.
+#[derive(Clone, Debug)]
+pub struct Traces {
+    /// Rows in the moving average; 0 averages every row since the reset
+    avg_frames: usize,
+    /// Linear power of the rows in the moving average, oldest first
+    window: VecDeque<Vec<f64>>,
+    sum: Vec<f64>,
+    count: usize,
+    exp: Vec<f64>,
+    max: Vec<f32>,
+    min: Vec<f32>,
+}
+
+impl Traces {
+    pub fn new(avg_frames: usize) -> Self {
+        Self { avg_frames, window: VecDeque::new(), sum: Vec::new(), count: 0, exp: Vec::new(), max: Vec::new(), min: Vec::new() }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new(self.avg_frames);
+    }
+
+    /// Rows accumulated since the last reset
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Add a row. `exp_coeff` is the exponential average's weight for the
+    /// new row, 1 - exp(-row_secs / tau).
+    pub fn push(&mut self, row: &[f32], exp_coeff: f64) {
+        if self.sum.len() != row.len() {
+            self.reset();
+        }
+        let power: Vec<f64> = row.iter().map(|&v| 10f64.powf(v as f64 / 10.0)).collect();
+        if self.count == 0 {
+            self.sum = vec![0.0; row.len()];
+            self.exp = power.clone();
+            self.max = row.to_vec();
+            self.min = row.to_vec();
+        } else {
+            for (e, &p) in self.exp.iter_mut().zip(&power) {
+                *e += exp_coeff * (p - *e);
+            }
+            for ((mx, mn), &v) in self.max.iter_mut().zip(&mut self.min).zip(row) {
+                *mx = mx.max(v);
+                *mn = mn.min(v);
+            }
+        }
+        for (s, &p) in self.sum.iter_mut().zip(&power) {
+            *s += p;
+        }
+        self.count += 1;
+        if self.avg_frames > 0 {
+            self.window.push_back(power);
+            if self.window.len() > self.avg_frames {
+                let old = self.window.pop_front().unwrap_or_default();
+                for (s, p) in self.sum.iter_mut().zip(old) {
+                    // Rounding can leave a hair below zero
+                    *s = (*s - p).max(0.0);
+                }
+            }
+        }
+    }
+
+    /// A trace in dB per bin, or `None` before the first row.
+    pub fn get(&self, kind: TraceKind) -> Option<Vec<f32>> {
+        if self.count == 0 {
+            return None;
+        }
+        let db = |p: f64| (10.0 * p.max(1e-30).log10()) as f32;
+        Some(match kind {
+            TraceKind::Avg => {
+                let n = if self.avg_frames > 0 { self.window.len() } else { self.count };
+                self.sum.iter().map(|&s| db(s / n.max(1) as f64)).collect()
+            }
+            TraceKind::Exp => self.exp.iter().map(|&p| db(p)).collect(),
+            TraceKind::MaxHold => self.max.clone(),
+            TraceKind::MinHold => self.min.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn average_is_in_linear_power_over_the_last_n_rows() {
+        let mut t = Traces::new(2);
+        t.push(&[0.0, -20.0], 0.5);
+        // Equal power from 0 dB and -inf-ish: mean power 0.5 is -3 dB
+        t.push(&[-300.0, -20.0], 0.5);
+        let avg = t.get(TraceKind::Avg).unwrap();
+        assert!((avg[0] + 3.01).abs() < 0.01 && (avg[1] + 20.0).abs() < 1e-4, "{avg:?}");
+        // The first row leaves the window
+        t.push(&[-300.0, -20.0], 0.5);
+        assert!(t.get(TraceKind::Avg).unwrap()[0] < -100.0);
+        assert_eq!(t.get(TraceKind::MaxHold).unwrap(), vec![0.0, -20.0]);
+        assert_eq!(t.get(TraceKind::MinHold).unwrap(), vec![-300.0, -20.0]);
+        // Exponential: 1 -> 0.5 -> 0.25 in power
+        assert!((t.get(TraceKind::Exp).unwrap()[0] + 6.02).abs() < 0.01);
+        t.reset();
+        assert_eq!((t.count(), t.get(TraceKind::Avg)), (0, None));
+    }
+
+    #[test]
+    fn unbounded_average_covers_every_row() {
+        let mut t = Traces::new(0);
+        for i in 0..100 {
+            t.push(&[if i == 0 { 20.0 } else { -300.0 }], 1.0);
+        }
+        // One row of 100x power among 100 rows averages to 0 dB
+        assert!(t.get(TraceKind::Avg).unwrap()[0].abs() < 0.01);
+    }
+
+    #[test]
+    fn trace_sets_parse_and_cycle() {
+        let set = TraceSet::parse("avg,max").unwrap();
+        assert_eq!(set.label(), "avg+max");
+        assert_eq!(set.kinds(), vec![TraceKind::Avg, TraceKind::MaxHold]);
+        assert_eq!(TraceSet::parse("all").unwrap().label(), "avg+exp+max+min");
+        assert!(TraceSet::parse("none").unwrap().is_empty());
+        assert_eq!(TraceSet::parse("avg,bogus"), None);
+        let mut cycle = vec![TraceSet::default().label()];
+        let mut s = TraceSet::default().next();
+        while !s.is_empty() {
+            cycle.push(s.label());
+            s = s.next();
+        }
+        assert_eq!(cycle, ["off", "avg", "exp", "max", "min", "avg+max+min"]);
+        assert!(set.next().is_empty());
+    }
+}
@@ -1,18 +1,25 @@
-use crate::app::{AnimationStyle, App, BinsMode};
+use crate::app::{AnimationStyle, App, BinsMode, Marker};
+use crate::dsp::Weighting;
 use crate::view::{self, FreqMap};
 use anyhow::Result;
-use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, MouseEventKind};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEventKind};
 use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
 use ratatui::backend::CrosstermBackend;
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
-use ratatui::style::Style;
+use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
-use ratatui::widgets::{Block, Borders, Paragraph};
+use ratatui::widgets::{Block, Borders, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState};
 use ratatui::Terminal;
+use std::collections::VecDeque;
 use std::io;
 use std::path::PathBuf;
 use std::time::{Duration, Instant};
 
+/// Frequency zoom factor per `+`/`-` press or ctrl+wheel notch.
+const ZOOM_STEP: f32 = 1.25;
+/// Fraction of the band width moved per pan step.
+const PAN_STEP: f32 = 0.125;
+
 enum UiMode {
     Normal,
     PromptSave { kind: SaveKind, input: String },
@@ -76,6 +83,21 @@ fn event_loop(app: &mut App, terminal: &mut Terminal<CrosstermBackend<io::Stdout
                         app.hover = Some((me.column, me.row));
                         app.hover_at = Instant::now();
                     }
+                    MouseEventKind::Down(MouseButton::Left) => {
+                        if let Some((freq, row)) = point_at(app, me.column, me.row) {
+                            app.place_marker(freq, row);
+                        }
+                    }
+                    // Ctrl+wheel zooms and shift+wheel (or a sideways wheel)
+                    // pans the frequency band; the plain wheel scrolls time
+                    MouseEventKind::ScrollUp if me.modifiers.contains(KeyModifiers::CONTROL) => app.adjust_zoom(ZOOM_STEP),
+                    MouseEventKind::ScrollDown if me.modifiers.contains(KeyModifiers::CONTROL) => app.adjust_zoom(1.0 / ZOOM_STEP),
+                    MouseEventKind::ScrollUp if me.modifiers.contains(KeyModifiers::SHIFT) => app.pan_freq(PAN_STEP),
+                    MouseEventKind::ScrollDown if me.modifiers.contains(KeyModifiers::SHIFT) => app.pan_freq(-PAN_STEP),
+                    MouseEventKind::ScrollRight => app.pan_freq(PAN_STEP),
+                    MouseEventKind::ScrollLeft => app.pan_freq(-PAN_STEP),
+                    MouseEventKind::ScrollUp => app.scroll_by(3 * app.rows_per_cell() as isize),
+                    MouseEventKind::ScrollDown => app.scroll_by(-3 * app.rows_per_cell() as isize),
                     _ => {}
                 },
                 _ => {}
@@ -92,13 +114,13 @@ fn event_loop(app: &mut App, terminal: &mut Terminal<CrosstermBackend<io::Stdout
             // Drain any available rows to minimize latency
             if !app.paused {
                 let mut drained = 0usize;
-                while let Ok(row) = app.spectrogram_rx.try_recv() {
-                    app.push_row(row);
+                while let Ok(rows) = app.spectrogram_rx.try_recv() {
+                    app.push_rows(rows);
                     drained += 1;
                     app.stats_rows_count += 1;
-                    app.total_rows = app.total_rows.saturating_add(1);
                     if drained > 1024 { break; }
                 }
+                if drained > 0 { app.sync_sample_rate(); }
                 let now = Instant::now();
                 if now.duration_since(app.stats_last_instant) >= Duration::from_secs(1) {
                     app.stats_rows_sec = app.stats_rows_count as f32 / now.duration_since(app.stats_last_instant).as_secs_f32();
@@ -143,8 +165,14 @@ fn handle_key(app: &mut App, key: KeyEvent, mode: &mut UiMode) -> Result<()> {
         (KeyCode::Char('q'), _) | (KeyCode::Esc, _) => app.running = false,
         (KeyCode::Char('p'), _) => app.toggle_pause(),
         (KeyCode::Char('a'), _) => app.toggle_style(),
-        (KeyCode::Char('+'), _) | (KeyCode::Char('='), _) => app.adjust_zoom(0.25),
-        (KeyCode::Char('-'), _) => app.adjust_zoom(-0.25),
+        (KeyCode::Char('g'), _) => app.cycle_octave_fraction(),
+        (KeyCode::Char('t'), _) => app.cycle_traces(),
+        (KeyCode::Char('T'), _) => { app.reset_traces(); app.set_status("traces reset"); }
+        (KeyCode::Char('e'), _) => app.toggle_distortion(),
+        (KeyCode::Char('v'), _) => app.toggle_pitch(),
+        (KeyCode::Char('+'), _) | (KeyCode::Char('='), _) => app.adjust_zoom(ZOOM_STEP),
+        (KeyCode::Char('-'), _) => app.adjust_zoom(1.0 / ZOOM_STEP),
+        (KeyCode::Char('z'), _) => { app.reset_band(); app.set_status("full frequency range"); }
         (KeyCode::Char('['), _) => app.adjust_floor(-2.0),
         (KeyCode::Char(']'), _) => app.adjust_floor(2.0),
         (KeyCode::Char('c'), _) => app.next_palette(),
@@ -165,6 +193,36 @@ fn handle_key(app: &mut App, key: KeyEvent, mode: &mut UiMode) -> Result<()> {
         (KeyCode::Char('d'), _) => { app.detailed = !app.detailed; }
         (KeyCode::Char('o'), _) => { app.overview = !app.overview; }
         (KeyCode::Char('h'), _) | (KeyCode::F(1), _) => { app.toggle_help(); },
+        (KeyCode::Char(','), _) => app.seek_by(-app.settings.seek_step),
+        (KeyCode::Char('.'), _) => app.seek_by(app.settings.seek_step),
+        (KeyCode::Char('<'), _) => app.step_speed(false),
+        (KeyCode::Char('>'), _) => app.step_speed(true),
+        (KeyCode::Char('l'), _) => app.loop_key(),
+        (KeyCode::Char('0'), _) => app.restart(),
+        // Left/right pan the frequency band; shift moves a whole band width
+        (KeyCode::Left, m) => app.pan_freq(if m.contains(KeyModifiers::SHIFT) { -1.0 } else { -PAN_STEP }),
+        (KeyCode::Right, m) => app.pan_freq(if m.contains(KeyModifiers::SHIFT) { 1.0 } else { PAN_STEP }),
+        // Scrollback: up/PageUp go back in time, as in a terminal
+        (KeyCode::Up, _) => app.scroll_by(app.rows_per_cell() as isize),
+        (KeyCode::Down, _) => app.scroll_by(-(app.rows_per_cell() as isize)),
+        (KeyCode::PageUp, _) => app.scroll_by(app.view_rows as isize),
+        (KeyCode::PageDown, _) => app.scroll_by(-(app.view_rows as isize)),
+        (KeyCode::Char('m'), _) => {
+            // At the mouse when it hovers a pane, else live at the band center
+            let at = app.active_hover().and_then(|(x, y)| point_at(app, x, y));
+            let (freq, row) = at.unwrap_or((fmap(app).frac_to_freq(0.5), None));
+            app.place_marker(freq, row);
+        }
+        (KeyCode::Char('k'), _) => app.peak_search(),
+        (KeyCode::Char('n'), _) => app.next_peak(),
+        (KeyCode::Char('M'), _) => {
+            app.toggle_delta_markers();
+            app.set_status(if app.delta_markers { "delta markers: relative to M1" } else { "delta markers off" });
+        }
+        (KeyCode::Char('x'), _) => app.clear_markers(),
+        (KeyCode::Tab, _) => app.next_marker(),
+        (KeyCode::Home, _) => app.scroll_by(isize::MAX),
+        (KeyCode::End, _) => app.scroll_by(isize::MIN),
         _ => {}
     }
     Ok(())
@@ -239,23 +297,86 @@ fn draw_spectrogram(f: &mut ratatui::Frame, area: Rect, app: &mut App, mode: &Ui
         Rect { x: area.x + 1, y: area.y + 1, width: area.width - 2, height: area.height - 2 }
     };
 
-    match app.style {
-        AnimationStyle::Waterfall => draw_waterfall(f, inner, app),
-        AnimationStyle::Horizontal => draw_horizontal(f, inner, app),
-        AnimationStyle::Spectrum => draw_spectrum(f, inner, app),
+    let panes = pane_rects(inner, app.buffers.len());
+    // Keep the scroll valid for this layout (style or size may have changed)
+    app.view_rows = pane_view_rows(app, panes[0]).max(1);
+    app.scroll = app.scroll.min(app.max_scroll());
+    for (i, &pane) in panes.iter().enumerate() {
+        let buf = &app.buffers[i];
+        match app.style {
+            AnimationStyle::Waterfall => draw_waterfall(f, pane, app, buf),
+            AnimationStyle::Horizontal => draw_horizontal(f, pane, app, buf),
+            AnimationStyle::Spectrum => draw_spectrum(f, pane, app, buf, i),
+            AnimationStyle::Octave => draw_octave(f, pane, app, buf),
+        }
+        if app.show_pitch { draw_pitch(f, pane, app, i); }
+        if app.detailed { draw_axis_labels(f, pane, app); }
+        if panes.len() > 1 { draw_pane_label(f, pane, &app.channel_labels[i]); }
+        if app.paused && app.max_scroll() > 0 { draw_scrollbar(f, pane, app); }
     }
+    draw_markers(f, &panes, app);
     if app.detailed { draw_overlay(f, inner, app, mode); }
-    draw_hover_readout(f, inner, app);
+    draw_hover_readout(f, &panes, app);
+    app.panes = panes;
+}
+
+/// Split the spectrogram area into one stacked pane per analyzed channel;
+/// every pane shares the same time axis.
+fn pane_rects(inner: Rect, n: usize) -> Vec<Rect> {
+    let n = n.max(1) as u32;
+    Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![Constraint::Ratio(1, n); n as usize])
+        .split(inner)
+        .to_vec()
+}
+
+/// Channel name at the pane's top-left corner (the hover readout, which
+/// repeats the name, replaces it while the mouse is over the pane).
+fn draw_pane_label(f: &mut ratatui::Frame, pane: Rect, label: &str) {
+    let text = format!(" {label} ");
+    let lw = (text.chars().count() as u16).min(pane.width);
+    if pane.height == 0 { return; }
+    let r = Rect { x: pane.x, y: pane.y, width: lw, height: 1 };
+    f.render_widget(Paragraph::new(text).style(Style::default().add_modifier(ratatui::style::Modifier::REVERSED)), r);
 }
 
 fn fmap(app: &App) -> FreqMap {
-    FreqMap {
-        sample_rate: app.settings.sample_rate,
-        zoom: app.zoom.max(1.0),
-        scale: app.freq_scale,
+    app.freq_map()
+}
+
+/// History rows available to a pane: those at or behind the scroll position.
+fn visible_rows(app: &App, buf: &VecDeque<Vec<f32>>) -> usize {
+    buf.len().saturating_sub(app.scroll)
+}
+
+/// `view::pool_cell` over display rows [r0, r1), counted from the newest row
+/// on screen rather than the newest row in history.
+fn pool(app: &App, buf: &VecDeque<Vec<f32>>, r0: usize, r1: usize, lo: usize, hi: usize) -> f32 {
+    view::pool_cell(buf, app.scroll + r0, app.scroll + r1, lo, hi, app.bins_mode)
+}
+
+/// History rows one pane shows at once in the current style; overview and
+/// horizontal fit all of history, so there is nothing to scroll.
+fn pane_view_rows(app: &App, pane: Rect) -> usize {
+    let len = app.buffer().len();
+    match app.style {
+        AnimationStyle::Waterfall if app.overview => len,
+        AnimationStyle::Waterfall => pane.height as usize * app.rows_per_cell(),
+        AnimationStyle::Horizontal => len,
+        AnimationStyle::Spectrum | AnimationStyle::Octave => 1,
     }
 }
 
+/// Scroll-position indicator along the pane's right edge, newest at the top.
+fn draw_scrollbar(f: &mut ratatui::Frame, pane: Rect, app: &App) {
+    let mut state = ScrollbarState::new(app.max_scroll() + 1)
+        .viewport_content_length(app.view_rows)
+        .position(app.scroll);
+    let bar = Scrollbar::new(ScrollbarOrientation::VerticalRight).begin_symbol(None).end_symbol(None);
+    f.render_stateful_widget(bar, pane, &mut state);
+}
+
 /// Palette position (0..1) for a pooled dB value.
 fn color_frac(app: &App, v: f32) -> f32 {
     ((v - app.db_floor) / (app.db_ceiling - app.db_floor).max(1.0)).clamp(0.0, 1.0)
@@ -270,6 +391,22 @@ fn waterfall_row_range(app: &App, y: usize, h: usize, total: usize) -> (usize, u
     }
 }
 
+/// Every buffer row behind terminal row `y` in waterfall mode, for any renderer.
+fn waterfall_cell_rows(app: &App, y: usize, h: usize, total: usize) -> (usize, usize) {
+    match app.render_mode {
+        crate::app::RenderMode::Cell => waterfall_row_range(app, y, h, total),
+        crate::app::RenderMode::Half | crate::app::RenderMode::Quad => {
+            if app.overview {
+                let (a, _) = view::overview_row_range(2 * y, h * 2, total);
+                let (_, b) = view::overview_row_range(2 * y + 1, h * 2, total);
+                (a, b)
+            } else {
+                (2 * y, (2 * y + 2).min(total))
+            }
+        }
+    }
+}
+
 /// Quadrant glyphs indexed by sub-pixel bits (TL=8, TR=4, BL=2, BR=1).
 const QUAD_CHARS: [&str; 16] = [
     " ", "▗", "▖", "▄", "▝", "▐", "▞", "▟", "▘", "▚", "▌", "▙", "▀", "▜", "▛", "█",
@@ -304,12 +441,12 @@ fn quad_cell_span(app: &App, ts: [f32; 4]) -> Span<'static> {
     Span::styled(QUAD_CHARS[bits], Style::default().fg(fg).bg(bg))
 }
 
-fn draw_waterfall(f: &mut ratatui::Frame, area: Rect, app: &mut App) {
+fn draw_waterfall(f: &mut ratatui::Frame, area: Rect, app: &App, buf: &VecDeque<Vec<f32>>) {
     let h = area.height as usize;
     let w = area.width as usize;
-    let total = app.buffer.len();
+    let total = visible_rows(app, buf);
     if total == 0 || w == 0 || h == 0 { return; }
-    let bins = app.buffer.front().map(|r| r.len()).unwrap_or(1).max(1);
+    let bins = buf.front().map(|r| r.len()).unwrap_or(1).max(1);
     let m = fmap(app);
     // Bin ranges per column: every covered bin is max-pooled, so narrowband
     // peaks are never lost when many bins map to one cell.
@@ -321,7 +458,7 @@ fn draw_waterfall(f: &mut ratatui::Frame, area: Rect, app: &mut App) {
                 let (r0, r1) = waterfall_row_range(app, y, h, total);
                 let mut spans = Vec::with_capacity(w);
                 for &(lo, hi) in &ranges {
-                    let v = view::pool_cell(&app.buffer, r0, r1, lo, hi, app.bins_mode);
+                    let v = pool(app, buf, r0, r1, lo, hi);
                     spans.push(Span::styled(" ", Style::default().bg(app.palette.color_at(color_frac(app, v)))));
                 }
                 let r = Rect { x: area.x, y: area.y + y as u16, width: area.width, height: 1 };
@@ -346,8 +483,8 @@ fn draw_waterfall(f: &mut ratatui::Frame, area: Rect, app: &mut App) {
                 };
                 let mut spans = Vec::with_capacity(w);
                 for &(lo, hi) in &ranges {
-                    let v_top = view::pool_cell(&app.buffer, t0, t1, lo, hi, app.bins_mode);
-                    let v_bot = view::pool_cell(&app.buffer, b0, b1, lo, hi, app.bins_mode);
+                    let v_top = pool(app, buf, t0, t1, lo, hi);
+                    let v_bot = pool(app, buf, b0, b1, lo, hi);
                     let style = Style::default()
                         .fg(app.palette.color_at(color_frac(app, v_top)))
                         .bg(app.palette.color_at(color_frac(app, v_bot)));
@@ -381,7 +518,7 @@ fn draw_waterfall(f: &mut ratatui::Frame, area: Rect, app: &mut App) {
                     for (i, t) in ts.iter_mut().enumerate() {
                         let (r0, r1) = sub_rows[i / 2];
                         let (lo, hi) = vranges[2 * x + (i % 2)];
-                        let v = view::pool_cell(&app.buffer, r0, r1, lo, hi, app.bins_mode);
+                        let v = pool(app, buf, r0, r1, lo, hi);
                         *t = color_frac(app, v);
                     }
                     spans.push(quad_cell_span(app, ts));
@@ -393,6 +530,50 @@ fn draw_waterfall(f: &mut ratatui::Frame, area: Rect, app: &mut App) {
     }
 }
 
+/// f0 contour over a waterfall or horizontal pane: a dot per screen row
+/// (waterfall) or column (horizontal) at the median f0 of the rows it covers.
+fn draw_pitch(f: &mut ratatui::Frame, area: Rect, app: &App, channel: usize) {
+    let (w, h) = (area.width as usize, area.height as usize);
+    let total = app.buffers.get(channel).map(|b| visible_rows(app, b)).unwrap_or(0);
+    if total == 0 || w == 0 || h == 0 {
+        return;
+    }
+    let m = fmap(app);
+    let (cells, vertical) = match app.style {
+        AnimationStyle::Waterfall => (h, false),
+        AnimationStyle::Horizontal => (w, true),
+        AnimationStyle::Spectrum | AnimationStyle::Octave => return,
+    };
+    let style = Style::default().fg(Color::White).add_modifier(Modifier::BOLD);
+    let buffer = f.buffer_mut();
+    for c in 0..cells {
+        let (r0, r1) = if vertical { horizontal_col_range(c, w, total) } else { waterfall_cell_rows(app, c, h, total) };
+        if r0 >= total {
+            break;
+        }
+        let Some(hz) = app.pitch_over(channel, app.scroll + r0, app.scroll + r1) else { continue };
+        let frac = m.freq_to_frac(hz);
+        if !(0.0..=1.0).contains(&frac) {
+            continue;
+        }
+        let (x, y) = if vertical {
+            (c, (((1.0 - frac) * h as f32) as usize).min(h - 1))
+        } else {
+            (((frac * w as f32) as usize).min(w - 1), c)
+        };
+        buffer.get_mut(area.x + x as u16, area.y + y as u16).set_symbol("•").set_style(style);
+    }
+}
+
+/// Hover suffix " | f0 220.3Hz" when pitch tracking is shown and the rows
+/// under the mouse are voiced.
+fn pitch_str(app: &App, channel: usize, r0: usize, r1: usize) -> String {
+    if !app.show_pitch {
+        return String::new();
+    }
+    app.pitch_over(channel, app.scroll + r0, app.scroll + r1).map(|hz| format!(" | f0 {hz:.1}Hz")).unwrap_or_default()
+}
+
 /// Buffer row range covered by column `x` of `w` in horizontal mode
 /// (time left->right, newest on the right; buffer index 0 = newest).
 fn horizontal_col_range(x: usize, w: usize, total: usize) -> (usize, usize) {
@@ -402,13 +583,13 @@ fn horizontal_col_range(x: usize, w: usize, total: usize) -> (usize, usize) {
     (total - t1, total - t0)
 }
 
-fn draw_horizontal(f: &mut ratatui::Frame, area: Rect, app: &mut App) {
+fn draw_horizontal(f: &mut ratatui::Frame, area: Rect, app: &App, buf: &VecDeque<Vec<f32>>) {
     // Time runs left->right (newest on right), frequency low->high is bottom->top
     let w = area.width as usize;
     let h = area.height as usize;
-    let total = app.buffer.len();
+    let total = visible_rows(app, buf);
     if total == 0 || w == 0 || h == 0 { return; }
-    let bins = app.buffer.front().map(|r| r.len()).unwrap_or(1).max(1);
+    let bins = buf.front().map(|r| r.len()).unwrap_or(1).max(1);
     let m = fmap(app);
     if app.render_mode == crate::app::RenderMode::Quad {
         // 2x2 sub-pixels per cell
@@ -423,7 +604,7 @@ fn draw_horizontal(f: &mut ratatui::Frame, area: Rect, app: &mut App) {
                 for (i, t) in ts.iter_mut().enumerate() {
                     let (lo, hi) = bin_ranges[2 * y + i / 2];
                     let (r0, r1) = col_ranges[2 * x + (i % 2)];
-                    let v = view::pool_cell(&app.buffer, r0, r1, lo, hi, app.bins_mode);
+                    let v = pool(app, buf, r0, r1, lo, hi);
                     *t = color_frac(app, v);
                 }
                 spans.push(quad_cell_span(app, ts));
@@ -438,7 +619,7 @@ fn draw_horizontal(f: &mut ratatui::Frame, area: Rect, app: &mut App) {
         let (lo, hi) = m.cell_bin_range(h - 1 - y, h, bins); // low freq at bottom
         let mut spans = Vec::with_capacity(w);
         for &(r0, r1) in &col_ranges {
-            let v = view::pool_cell(&app.buffer, r0, r1, lo, hi, app.bins_mode);
+            let v = pool(app, buf, r0, r1, lo, hi);
             spans.push(Span::styled(" ", Style::default().bg(app.palette.color_at(color_frac(app, v)))));
         }
         let r = Rect { x: area.x, y: area.y + y as u16, width: area.width, height: 1 };
@@ -448,11 +629,11 @@ fn draw_horizontal(f: &mut ratatui::Frame, area: Rect, app: &mut App) {
 
 const EIGHTHS: [&str; 9] = [" ", "▁", "▂", "▃", "▄", "▅", "▆", "▇", "█"];
 
-fn draw_spectrum(f: &mut ratatui::Frame, area: Rect, app: &mut App) {
+fn draw_spectrum(f: &mut ratatui::Frame, area: Rect, app: &App, buf: &VecDeque<Vec<f32>>, channel: usize) {
     // Instantaneous spectrum of the newest frame: frequency on x, dB on y.
     let w = area.width as usize;
     let h = area.height as usize;
-    let Some(row) = app.buffer.front() else { return };
+    let Some(row) = buf.get(app.scroll) else { return };
     if w == 0 || h == 0 { return; }
     let bins = row.len().max(1);
     let m = fmap(app);
@@ -479,71 +660,211 @@ fn draw_spectrum(f: &mut ratatui::Frame, area: Rect, app: &mut App) {
         let r = Rect { x: area.x, y: area.y + y as u16, width: area.width, height: 1 };
         f.render_widget(Paragraph::new(Line::from(spans)), r);
     }
+    draw_traces(f, area, app, channel);
     // Peak annotation: strongest displayed bin. Drawn at the top-left so the
     // details panel (top-right) can't cover it; a hover readout replaces it.
     if app.detailed && area.width > 24 && app.active_hover().is_none() {
-        let fs = app.settings.sample_rate as f32;
-        let hz_per_bin = (fs / 2.0) / (bins as f32);
-        let vis_hi = ((fs / 2.0 / app.zoom.max(1.0)) / hz_per_bin).ceil() as usize;
-        let (pi, pv) = view::max_bin_in(row, 0, vis_hi.clamp(1, bins));
+        let (lo, hi) = m.visible_bin_range(bins);
+        let (pi, pv) = view::max_bin_in(row, lo, hi);
         if pv.is_finite() {
-            let label = format!(" peak {} {:+.1} dB ", format_freq(pi as f32 * hz_per_bin), pv);
+            let pk = view::interpolate_peak(row, pi, &app.scalloping);
+            let label = format!(" peak {} {:+.1} {} ", view::format_hz(m.absolute(m.bin_to_freq(pk.bin, bins))), pk.db, app.unit_label());
             let lw = (label.chars().count() as u16).min(area.width);
             // Top-center: clear of the dB axis labels (left) and details panel (right)
             let r = Rect { x: area.x + (area.width - lw) / 2, y: area.y, width: lw, height: 1 };
             f.render_widget(Paragraph::new(label).style(Style::default().add_modifier(ratatui::style::Modifier::REVERSED)), r);
         }
     }
+    if let Some(a4) = app.note_a4() {
+        draw_tuner(f, area, app, row, &m, a4);
+    }
 }
 
-fn draw_hover_readout(f: &mut ratatui::Frame, inner: Rect, app: &App) {
-    let Some((cx, cy)) = app.active_hover() else { return };
-    if cx < inner.x || cy < inner.y || cx >= inner.x + inner.width || cy >= inner.y + inner.height {
+/// Tuner readout for `--axis notes`: the dominant peak's nearest note and
+/// cents on a ±50 cent needle (green within 5 cents), under the peak
+/// annotation.
+fn draw_tuner(f: &mut ratatui::Frame, area: Rect, app: &App, row: &[f32], m: &FreqMap, a4: f64) {
+    if area.width < 48 || area.height < 4 { return; }
+    let bins = row.len().max(1);
+    let (lo, hi) = m.visible_bin_range(bins);
+    let (pi, pv) = view::max_bin_in(row, lo, hi);
+    if !pv.is_finite() || pv <= app.db_floor { return; }
+    let pk = view::interpolate_peak(row, pi, &app.scalloping);
+    let hz = m.absolute(m.bin_to_freq(pk.bin, bins));
+    let Some(note) = view::Note::nearest(hz, a4) else { return };
+    // 21 cells of 5 cents, in tune in the middle
+    let needle = ((note.cents / 5.0).round() as i32 + 10).clamp(0, 20) as usize;
+    let gauge: String = (0..21).map(|i| if i == needle { '●' } else if i == 10 { '┃' } else { '─' }).collect();
+    let label = format!(" {:<3} {:+3.0}c ♭{gauge}♯ {} ", note.name(), note.cents.round() + 0.0, view::format_hz(hz));
+    let style = if note.cents.abs() < 5.0 { Style::default().fg(Color::Green) } else { Style::default() };
+    let lw = (label.chars().count() as u16).min(area.width);
+    let r = Rect { x: area.x + (area.width - lw) / 2, y: area.y + 1, width: lw, height: 1 };
+    f.render_widget(Paragraph::new(label).style(style.add_modifier(Modifier::REVERSED)), r);
+}
+
+/// Left edge and width of the octave view's band columns: equal columns
+/// centered in the pane, at least one cell each (bands past the right edge
+/// are not drawn).
+fn octave_columns(w: usize, n_bands: usize) -> (usize, usize) {
+    let bw = (w / n_bands.max(1)).max(1);
+    (w.saturating_sub(bw * n_bands) / 2, bw)
+}
+
+/// Band under pane column `x` in the octave view.
+fn octave_band_at(x: usize, w: usize, n_bands: usize) -> Option<usize> {
+    let (x0, bw) = octave_columns(w, n_bands);
+    let i = x.checked_sub(x0)? / bw;
+    (i < n_bands).then_some(i)
+}
+
+fn draw_octave(f: &mut ratatui::Frame, area: Rect, app: &App, buf: &VecDeque<Vec<f32>>) {
+    // Fractional-octave band levels of the newest frame as bars, with the
+    // Leq (white) and Lmax (red) of the history as ticks and the nominal
+    // band centers along the bottom row.
+    let w = area.width as usize;
+    let h = area.height.saturating_sub(1) as usize;
+    let Some(row) = buf.get(app.scroll) else { return };
+    let bands = app.octave_bands();
+    if w == 0 || h == 0 || bands.is_empty() { return; }
+    let levels = app.band_levels(row, &bands);
+    let (leq, lmax) = app.band_stats(buf, &bands);
+    let (x0, bw) = octave_columns(w, bands.len());
+    // One cell of gap between bars once they are wide enough to spare it
+    let fill_w = if bw >= 3 { bw - 1 } else { bw };
+    // Ticks only for levels above the floor
+    let tick_at = |v: f32, y: usize| v > app.db_floor && ((color_frac(app, v) * h as f32) as usize).min(h - 1) == h - 1 - y;
+    for y in 0..h {
+        let cell_base = (h - 1 - y) * 8;
+        let mut spans = vec![Span::raw(" ".repeat(x0.min(w)))];
+        for (i, &v) in levels.iter().enumerate().take((w - x0.min(w)) / bw) {
+            let frac = color_frac(app, v);
+            let fill = ((frac * (h * 8) as f32).round() as usize).saturating_sub(cell_base).min(8);
+            let (text, style) = if fill < 8 && tick_at(lmax[i], y) {
+                ("─", Style::default().fg(Color::Red))
+            } else if fill < 8 && tick_at(leq[i], y) {
+                ("─", Style::default().fg(Color::White))
+            } else {
+                (EIGHTHS[fill], Style::default().fg(app.palette.color_at(frac.max(0.15))))
+            };
+            spans.push(Span::styled(text.repeat(fill_w), style));
+            spans.push(Span::raw(" ".repeat(bw - fill_w)));
+        }
+        let r = Rect { x: area.x, y: area.y + y as u16, width: area.width, height: 1 };
+        f.render_widget(Paragraph::new(Line::from(spans)), r);
+    }
+    // Band centers, skipping any that would run into the previous label
+    let mut next_free = 0usize;
+    for (i, band) in bands.iter().enumerate() {
+        let label = view::octave_label(app.octave_fraction, band.center);
+        let lw = label.chars().count();
+        let x = (x0 + i * bw + fill_w / 2).saturating_sub(lw / 2);
+        if x < next_free || x + lw > w { continue; }
+        next_free = x + lw + 1;
+        let r = Rect { x: area.x + x as u16, y: area.y + h as u16, width: lw as u16, height: 1 };
+        f.render_widget(Paragraph::new(label), r);
+    }
+    // Broadband levels at the top-center; a hover readout replaces them
+    if area.width > 40 && app.active_hover().is_none() {
+        let n = bands.len();
+        let label = format!(
+            " 1/{} oct | L {} | Leq {} | Lmax {} {} ",
+            app.octave_fraction, level_str(view::total_level(&levels)), level_str(leq[n]), level_str(lmax[n]), app.level_label()
+        );
+        let lw = (label.chars().count() as u16).min(area.width);
+        let r = Rect { x: area.x + (area.width - lw) / 2, y: area.y, width: lw, height: 1 };
+        f.render_widget(Paragraph::new(label).style(Style::default().add_modifier(ratatui::style::Modifier::REVERSED)), r);
+    }
+}
+
+/// Averaging and hold traces over the spectrum bars: one glyph per column
+/// at the trace level (a third of a cell of vertical resolution), in the
+/// trace's color, and a legend at the top-right (below the details panel).
+fn draw_traces(f: &mut ratatui::Frame, area: Rect, app: &App, channel: usize) {
+    const THIRDS: [&str; 3] = ["▁", "─", "▔"];
+    let kinds = app.trace_set.kinds();
+    let Some(traces) = app.traces.get(channel).filter(|_| !app.trace_set.is_empty()) else { return };
+    let (w, h) = (area.width as usize, area.height as usize);
+    let m = fmap(app);
+    let buffer = f.buffer_mut();
+    // The average is drawn last, on top of the holds
+    for &kind in kinds.iter().rev() {
+        let Some(values) = traces.get(kind) else { continue };
+        let [r, g, b] = kind.rgb();
+        let style = Style::default().fg(Color::Rgb(r, g, b));
+        for x in 0..w {
+            let (lo, hi) = m.cell_bin_range(x, w, values.len().max(1));
+            let (_, v) = view::max_bin_in(&values, lo, hi);
+            if !v.is_finite() || v < app.db_floor {
+                continue;
+            }
+            let level3 = ((color_frac(app, v) * (h * 3) as f32) as usize).min(h * 3 - 1);
+            let y = h - 1 - level3 / 3;
+            buffer.get_mut(area.x + x as u16, area.y + y as u16).set_symbol(THIRDS[level3 % 3]).set_style(style);
+        }
+    }
+    let top = if app.detailed && channel == 0 { 7 } else { 0 };
+    if h <= top + 1 || app.traces[channel].count() == 0 {
         return;
     }
-    let total = app.buffer.len();
+    let mut spans = Vec::new();
+    for &kind in &kinds {
+        let [r, g, b] = kind.rgb();
+        spans.push(Span::styled(format!(" ─ {}", app.trace_label(kind)), Style::default().fg(Color::Rgb(r, g, b))));
+    }
+    spans.push(Span::raw(" "));
+    let lw = (spans.iter().map(|s| s.content.chars().count()).sum::<usize>() as u16).min(area.width);
+    let r = Rect { x: area.x + area.width - lw, y: area.y + top as u16, width: lw, height: 1 };
+    f.render_widget(Paragraph::new(Line::from(spans)).style(Style::default().bg(Color::Black)), r);
+}
+
+fn draw_hover_readout(f: &mut ratatui::Frame, panes: &[Rect], app: &App) {
+    let Some((cx, cy)) = app.active_hover() else { return };
+    let Some(idx) = panes.iter().position(|p| {
+        cx >= p.x && cy >= p.y && cx < p.x + p.width && cy < p.y + p.height
+    }) else {
+        return;
+    };
+    let inner = panes[idx];
+    let buf = &app.buffers[idx];
+    let total = visible_rows(app, buf);
     if total == 0 || inner.width < 24 { return; }
     let x = (cx - inner.x) as usize;
     let y = (cy - inner.y) as usize;
     let w = inner.width as usize;
     let h = inner.height as usize;
-    let bins = app.buffer.front().map(|r| r.len()).unwrap_or(1).max(1);
+    let bins = buf.front().map(|r| r.len()).unwrap_or(1).max(1);
     let m = fmap(app);
-    let fs = app.settings.sample_rate as f32;
-    let sec_per_row = (app.settings.hop_size as f32) / fs.max(1.0);
+    let sec_per_row = app.row_secs();
 
     let text = match app.style {
         AnimationStyle::Waterfall => {
             let (lo, hi) = m.cell_bin_range(x, w, bins);
-            let (r0, r1) = match app.render_mode {
-                crate::app::RenderMode::Cell => waterfall_row_range(app, y, h, total),
-                crate::app::RenderMode::Half | crate::app::RenderMode::Quad => {
-                    if app.overview {
-                        let (a, _) = view::overview_row_range(2 * y, h * 2, total);
-                        let (_, b) = view::overview_row_range(2 * y + 1, h * 2, total);
-                        (a, b)
-                    } else {
-                        (2 * y, (2 * y + 2).min(total))
-                    }
-                }
-            };
+            let (r0, r1) = waterfall_cell_rows(app, y, h, total);
             if r0 >= total { return; }
-            let v = view::pool_cell(&app.buffer, r0, r1, lo, hi, app.bins_mode);
+            let v = pool(app, buf, r0, r1, lo, hi);
             let f_mid = m.frac_to_freq((x as f32 + 0.5) / w as f32);
-            let sec = ((r0 + r1) as f32 / 2.0) * sec_per_row;
-            format!(" t -{:.2}s | {} | {} ", sec, format_freq(f_mid), db_str(v, app))
+            let sec = (app.scroll as f64 + (r0 + r1) as f64 / 2.0) * sec_per_row;
+            let pk = view::cell_argmax(buf, app.scroll + r0, app.scroll + r1, lo, hi)
+                .map(|(r, bi)| peak_str(app, &buf[r], bi))
+                .unwrap_or_default();
+            let f0 = pitch_str(app, idx, r0, r1);
+            format!(" t {} | {}{} | {}{pk}{f0} ", app.hover_time(sec), format_freq(m.absolute(f_mid)), note_str(app, m.absolute(f_mid)), db_str(v, app))
         }
         AnimationStyle::Horizontal => {
             let (lo, hi) = m.cell_bin_range(h - 1 - y, h, bins);
             let (r0, r1) = horizontal_col_range(x, w, total);
             if r0 >= total { return; }
-            let v = view::pool_cell(&app.buffer, r0, r1, lo, hi, app.bins_mode);
+            let v = pool(app, buf, r0, r1, lo, hi);
             let f_mid = m.frac_to_freq(1.0 - (y as f32 + 0.5) / h as f32);
-            let sec = ((r0 + r1) as f32 / 2.0) * sec_per_row;
-            format!(" t -{:.2}s | {} | {} ", sec, format_freq(f_mid), db_str(v, app))
+            let sec = (app.scroll as f64 + (r0 + r1) as f64 / 2.0) * sec_per_row;
+            let pk = view::cell_argmax(buf, app.scroll + r0, app.scroll + r1, lo, hi)
+                .map(|(r, bi)| peak_str(app, &buf[r], bi))
+                .unwrap_or_default();
+            let f0 = pitch_str(app, idx, r0, r1);
+            format!(" t {} | {}{} | {}{pk}{f0} ", app.hover_time(sec), format_freq(m.absolute(f_mid)), note_str(app, m.absolute(f_mid)), db_str(v, app))
         }
         AnimationStyle::Spectrum => {
-            let row = app.buffer.front().expect("total > 0");
+            let row = &buf[app.scroll];
             let (lo, hi) = m.cell_bin_range(x, w, bins);
             let (bi, mut v) = view::max_bin_in(row, lo, hi);
             // Match the bars: suppressed non-peaks read as below-floor
@@ -551,9 +872,27 @@ fn draw_hover_readout(f: &mut ratatui::Frame, inner: Rect, app: &App) {
                 v = f32::NEG_INFINITY;
             }
             let f_mid = m.frac_to_freq((x as f32 + 0.5) / w as f32);
-            format!(" {} | {} ", format_freq(f_mid), db_str(v, app))
+            let t = if app.scroll > 0 { format!(" t {} |", app.hover_time(app.scroll as f64 * sec_per_row)) } else { String::new() };
+            format!("{t} {}{} | {}{}{} ", format_freq(m.absolute(f_mid)), note_str(app, m.absolute(f_mid)), db_str(v, app), trace_str(app, idx, lo, hi), peak_str(app, row, bi))
+        }
+        AnimationStyle::Octave => {
+            let bands = app.octave_bands();
+            let Some(i) = octave_band_at(x, w, bands.len()) else { return };
+            let level = app.band_levels(&buf[app.scroll], &bands[i..=i])[0];
+            let (leq, lmax) = app.band_stats(buf, &bands[i..=i]);
+            let t = if app.scroll > 0 { format!(" t {} |", app.hover_time(app.scroll as f64 * sec_per_row)) } else { String::new() };
+            format!(
+                "{t} {}Hz 1/{} oct | L {} | Leq {} | Lmax {} {} ",
+                view::octave_label(app.octave_fraction, bands[i].center),
+                app.octave_fraction,
+                level_str(level),
+                level_str(leq[0]),
+                level_str(lmax[0]),
+                app.level_label()
+            )
         }
     };
+    let text = if panes.len() > 1 { format!(" {} |{}", app.channel_labels[idx], text) } else { text };
     let lw = (text.chars().count() as u16).min(inner.width);
     let r = Rect { x: inner.x, y: inner.y, width: lw, height: 1 };
     f.render_widget(
@@ -562,11 +901,126 @@ fn draw_hover_readout(f: &mut ratatui::Frame, inner: Rect, app: &App) {
     );
 }
 
+/// Center-relative frequency and stream row under a screen position, for
+/// placing markers. The spectrum view shows a single row, so its markers
+/// stay live (row `None`).
+fn point_at(app: &App, cx: u16, cy: u16) -> Option<(f32, Option<usize>)> {
+    let pane = *app.panes.iter().find(|p| cx >= p.x && cy >= p.y && cx < p.x + p.width && cy < p.y + p.height)?;
+    let (x, y) = ((cx - pane.x) as usize, (cy - pane.y) as usize);
+    let (w, h) = (pane.width as usize, pane.height as usize);
+    let m = fmap(app);
+    let total = visible_rows(app, app.buffer());
+    let stream_row = |r0: usize| (r0 < total).then(|| app.total_rows - 1 - (app.scroll + r0));
+    match app.style {
+        AnimationStyle::Waterfall => {
+            let (r0, _) = waterfall_cell_rows(app, y, h, total);
+            Some((m.frac_to_freq((x as f32 + 0.5) / w as f32), Some(stream_row(r0)?)))
+        }
+        AnimationStyle::Horizontal => {
+            let (r0, _) = horizontal_col_range(x, w, total);
+            Some((m.frac_to_freq(1.0 - (y as f32 + 0.5) / h as f32), Some(stream_row(r0)?)))
+        }
+        AnimationStyle::Spectrum => Some((m.frac_to_freq((x as f32 + 0.5) / w as f32), None)),
+        // Bands, not bins: nothing to pin a marker to
+        AnimationStyle::Octave => None,
+    }
+}
+
+/// Screen cell of a marker inside `pane`, if it is in view: on its row and
+/// frequency in the spectrograms, just above the bar top in the spectrum.
+fn marker_cell(app: &App, pane: Rect, marker: &Marker) -> Option<(u16, u16)> {
+    let (w, h) = (pane.width as usize, pane.height as usize);
+    let frac = fmap(app).freq_to_frac(marker.freq);
+    if !(0.0..=1.0).contains(&frac) || w == 0 || h == 0 {
+        return None;
+    }
+    let idx = app.marker_index(marker)?.checked_sub(app.scroll)?;
+    let total = visible_rows(app, app.buffer()).max(1);
+    let along = ((frac * w as f32) as usize).min(w - 1);
+    let (x, y) = match app.style {
+        AnimationStyle::Waterfall if app.overview => (along, idx * h / total),
+        AnimationStyle::Waterfall => (along, idx / app.rows_per_cell()),
+        AnimationStyle::Horizontal => ((total - 1 - idx) * w / total, (((1.0 - frac) * h as f32) as usize).min(h - 1)),
+        AnimationStyle::Spectrum => {
+            let v = app.marker_value(marker).unwrap_or(f32::NEG_INFINITY);
+            let bar = (color_frac(app, v) * h as f32).ceil() as usize;
+            (along, h.saturating_sub(bar + 1))
+        }
+        AnimationStyle::Octave => return None,
+    };
+    (x < w && y < h).then(|| (pane.x + x as u16, pane.y + y as u16))
+}
+
+/// Marker numbers at their positions in every pane, plus the readout table
+/// (values from the first channel) at the bottom-left of the first pane.
+fn draw_markers(f: &mut ratatui::Frame, panes: &[Rect], app: &App) {
+    if app.markers.is_empty() {
+        return;
+    }
+    let style = |i: usize| {
+        let bg = if i == app.active_marker { Color::Yellow } else { Color::White };
+        Style::default().fg(Color::Black).bg(bg).add_modifier(Modifier::BOLD)
+    };
+    for &pane in panes {
+        for (i, m) in app.markers.iter().enumerate() {
+            if let Some((x, y)) = marker_cell(app, pane, m) {
+                f.render_widget(Paragraph::new(format!("{}", i + 1)).style(style(i)), Rect { x, y, width: 1, height: 1 });
+            }
+        }
+    }
+    let pane = panes[0];
+    let lines = app.marker_readouts();
+    if pane.height as usize <= lines.len() + 1 || pane.width < 24 {
+        return;
+    }
+    for (i, line) in lines.iter().enumerate() {
+        let text = format!(" {line} ");
+        let lw = (text.chars().count() as u16).min(pane.width);
+        let y = pane.y + pane.height - (lines.len() - i) as u16;
+        f.render_widget(Paragraph::new(text).style(style(i)), Rect { x: pane.x, y, width: lw, height: 1 });
+    }
+}
+
+/// Hover suffix " | pk 6.0123kHz -0.2 dBFS" when the loudest bin under the
+/// mouse is a spectral peak: its interpolated frequency and corrected level.
+fn peak_str(app: &App, row: &[f32], bin: usize) -> String {
+    if bin == 0 || bin + 1 >= row.len() || !view::is_local_peak(row, bin) {
+        return String::new();
+    }
+    let pk = view::interpolate_peak(row, bin, &app.scalloping);
+    let m = fmap(app);
+    let hz = m.absolute(m.bin_to_freq(pk.bin, row.len()));
+    format!(" | pk {}{} {:+.1} {}", view::format_hz(hz), note_str(app, hz), pk.db, app.unit_label())
+}
+
+/// " (A4 +12c)": nearest note and cents offset when axes show notes.
+fn note_str(app: &App, hz: f64) -> String {
+    app.note_a4().and_then(|a4| view::format_note(hz, a4)).map(|n| format!(" ({n})")).unwrap_or_default()
+}
+
+/// Hover suffix " | avg -42.1 max -30.5" with each shown trace's level
+/// over bins [lo, hi).
+fn trace_str(app: &App, channel: usize, lo: usize, hi: usize) -> String {
+    let Some(traces) = app.traces.get(channel) else { return String::new() };
+    app.trace_set
+        .kinds()
+        .into_iter()
+        .filter_map(|kind| Some((kind, view::max_bin_in(&traces.get(kind)?, lo, hi).1)))
+        .enumerate()
+        .map(|(i, (kind, v))| format!("{} {} {v:+.1}", if i == 0 { " |" } else { "" }, kind.name()))
+        .collect()
+}
+
+/// Band level for the octave readouts; "--" when no energy was seen.
+fn level_str(v: f32) -> String {
+    if v.is_finite() { format!("{v:+.1}") } else { "--".to_string() }
+}
+
 fn db_str(v: f32, app: &App) -> String {
     if v.is_finite() {
-        format!("{:+.1} dB", v)
+        format!("{:+.1} {}", v, app.unit_label())
     } else {
-        format!("< {:.0} dB", app.db_floor)
+        format!("< {:.0} {}", app.db_floor, app.unit_label())
     }
 }
 
@@ -574,21 +1028,38 @@ fn draw_status(f: &mut ratatui::Frame, area: Rect, app: &App, mode: &UiMode) {
     if app.fullscreen { return; }
     let mut lines = vec![
         Line::from(vec![
-            Span::raw("[q] quit  [p] pause  [a] style  [b] bins  [+/-] zoom  [[/]] floor  [c/C] palette  [s/S] png  [w/W] csv  [r] reset  [f] fullscreen  [d] details  [o] overview  [h] help"),
+            Span::raw("[q] quit  [p] pause  [a] style  [g] octave  [t/T] traces  [b] bins  [+/-] zoom  [←/→] pan  [z] full band  [m/k/n/M/x] markers  [[/]] floor  [c/C] palette  [s/S] png  [w/W] csv  [r] reset  [f] fullscreen  [d] details  [o] overview  [,/.] seek  [</>] speed  [l] loop  [0] restart  [↑/↓/PgUp/PgDn] scrollback  [h] help"),
         ]),
     ];
-    let f_max = (app.settings.sample_rate as f32) / 2.0 / app.zoom;
-    let seconds = (app.buffer.len() as f32) * (app.settings.hop_size as f32) / (app.settings.sample_rate as f32);
+    let m = fmap(app);
+    let f_range = format!("{}..{}", format_freq(m.absolute(m.frac_to_freq(0.0))), format_freq(m.absolute(m.frac_to_freq(1.0))));
+    let seconds = (app.buffer().len() as f32) * (app.settings.hop_size as f32) / (app.settings.sample_rate as f32);
+    // Span of the rows on screen, which lags the newest row while scrolled back
+    let back = app.scroll as f64 * app.row_secs();
+    let shown = app.buffer().len().saturating_sub(app.scroll).min(app.view_rows.max(1));
+    let span = shown.saturating_sub(1) as f64 * app.row_secs();
+    let t_range = match app.newest_time() {
+        Some(t) => format!("{}..{}", view::format_clock(t - back - span), view::format_clock(t - back)),
+        None if app.scroll > 0 => format!("-{:.2}..-{back:.2}s", back + span),
+        None => format!("0..{seconds:.2}s"),
+    };
+    let src = match app.net_status().or_else(|| app.transport_status()) {
+        Some(state) => format!("{} [{state}]", app.input_desc),
+        None => app.input_desc.clone(),
+    };
+    // Leads the line so it stays visible on narrow terminals
+    let scrolled = if app.scroll > 0 { format!("[scrollback -{back:.2}s] ") } else { String::new() };
     lines.push(Line::from(Span::raw(format!(
-        "src: {} | style: {:?} | zoom: {:.2} | floor: {:.1} dB ceil: {:.1} | rows: {} | freq: 0..{:.0} Hz | time: 0..{:.2}s | L/H/N: {}/{}/{} | fps: {} | rps: {:.1} | rt: {} | scale: {:?} | render: {:?} | bins: {:?}",
-        app.input_desc,
+        "{scrolled}src: {} | style: {:?} | zoom: {:.2} | floor: {:.1} ceil: {:.1} {} | rows: {} | freq: {} | time: {} | L/H/N: {}/{}/{} | fps: {} | rps: {:.1} | rt: {} | scale: {:?} | render: {:?} | bins: {:?}",
+        src,
         app.style,
         app.zoom,
         app.db_floor,
         app.db_ceiling,
-        app.buffer.len(),
-        f_max,
-        seconds,
+        app.unit_label(),
+        app.buffer().len(),
+        f_range,
+        t_range,
         app.settings.window_len,
         app.settings.hop_size,
         app.settings.fft_size,
@@ -618,38 +1089,59 @@ fn draw_status(f: &mut ratatui::Frame, area: Rect, app: &App, mode: &UiMode) {
     f.render_widget(p, area);
 }
 
-fn format_freq(hz: f32) -> String {
-    if hz >= 10_000.0 {
+fn format_freq(hz: f64) -> String {
+    if let Some(rf) = view::format_rf(hz) {
+        rf
+    } else if hz.abs() >= 10_000.0 {
         format!("{:.1}kHz", hz / 1000.0)
-    } else if hz >= 1_000.0 {
+    } else if hz.abs() >= 1_000.0 {
         format!("{:.2}kHz", hz / 1000.0)
     } else {
         format!("{:.0}Hz", hz)
     }
 }
 
-fn draw_overlay(f: &mut ratatui::Frame, area: Rect, app: &App, _mode: &UiMode) {
+/// Frequency tick positions (0..1 up the band) and labels, `ticks + 1` of
+/// them, or note names with `--axis notes`.
+fn freq_tick_labels(app: &App, m: &FreqMap, ticks: usize) -> Vec<(f32, String)> {
+    if let Some(a4) = app.note_a4() {
+        return view::note_ticks(m, a4, ticks as u32);
+    }
+    (0..=ticks)
+        .map(|i| {
+            let frac = i as f32 / ticks as f32;
+            (frac, format_freq(m.absolute(m.frac_to_freq(frac))))
+        })
+        .collect()
+}
+
+/// Axis tick labels for one pane (drawn per pane when channels are stacked).
+fn draw_axis_labels(f: &mut ratatui::Frame, area: Rect, app: &App) {
     if area.width < 12 || area.height < 4 { return; }
     let m = fmap(app);
     match app.style {
-        AnimationStyle::Spectrum => {
-            // Spectrum view: y axis is dB, x axis is frequency
-            let ticks = (area.height as usize / 6).clamp(3, 8);
+        AnimationStyle::Spectrum | AnimationStyle::Octave => {
+            // Spectrum view: y axis is dB, x axis is frequency. The octave
+            // view keeps its bottom row for band labels.
+            let octave = app.style == AnimationStyle::Octave;
+            let db_h = if octave { area.height - 1 } else { area.height };
+            let ticks = (db_h as usize / 6).clamp(3, 8);
             for i in 0..=ticks {
-                let y = area.y + (i as u16) * (area.height.saturating_sub(1)) / (ticks as u16);
+                let y = area.y + (i as u16) * (db_h.saturating_sub(1)) / (ticks as u16);
                 let frac = (ticks - i) as f32 / ticks as f32;
                 let db = app.db_floor + frac * (app.db_ceiling - app.db_floor);
                 let label = format!("{:.0}dB", db);
                 let r = Rect { x: area.x, y, width: (label.len() as u16).min(area.width), height: 1 };
                 f.render_widget(Paragraph::new(label), r);
             }
+            if octave {
+                return;
+            }
             // Frequency labels along the bottom edge
             let fticks = (area.width as usize / 20).clamp(2, 8);
-            for i in 0..=fticks {
-                let frac = i as f32 / fticks as f32;
-                let label = format_freq(m.frac_to_freq(frac));
+            for (frac, label) in freq_tick_labels(app, &m, fticks) {
                 let lw = label.len() as u16;
-                let x_pos = area.x + (i as u16) * (area.width.saturating_sub(1)) / (fticks as u16);
+                let x_pos = area.x + (frac * area.width.saturating_sub(1) as f32) as u16;
                 let x_pos = x_pos.min(area.x + area.width.saturating_sub(lw));
                 let r = Rect { x: x_pos, y: area.y + area.height - 1, width: lw.min(area.width), height: 1 };
                 f.render_widget(Paragraph::new(label), r);
@@ -660,21 +1152,17 @@ fn draw_overlay(f: &mut ratatui::Frame, area: Rect, app: &App, _mode: &UiMode) {
             if horizontal {
                 // Frequency on the y axis (low at bottom)
                 let ticks = (area.height as usize / 6).clamp(4, 12);
-                for i in 0..=ticks {
-                    let y = area.y + (i as u16) * (area.height.saturating_sub(1)) / (ticks as u16);
-                    let frac = (ticks - i) as f32 / ticks as f32;
-                    let label = format_freq(m.frac_to_freq(frac));
+                for (frac, label) in freq_tick_labels(app, &m, ticks) {
+                    let y = area.y + ((1.0 - frac) * area.height.saturating_sub(1) as f32) as u16;
                     let r = Rect { x: area.x, y, width: (label.len() as u16).min(area.width), height: 1 };
                     f.render_widget(Paragraph::new(label), r);
                 }
             } else {
                 // Waterfall: frequency on the x axis (low at left)
                 let fticks = (area.width as usize / 20).clamp(2, 8);
-                for i in 0..=fticks {
-                    let frac = i as f32 / fticks as f32;
-                    let label = format_freq(m.frac_to_freq(frac));
+                for (frac, label) in freq_tick_labels(app, &m, fticks) {
                     let lw = label.len() as u16;
-                    let x_pos = area.x + (i as u16) * (area.width.saturating_sub(1)) / (fticks as u16);
+                    let x_pos = area.x + (frac * area.width.saturating_sub(1) as f32) as u16;
                     let x_pos = x_pos.min(area.x + area.width.saturating_sub(lw));
                     let r = Rect { x: x_pos, y: area.y + area.height - 1, width: lw.min(area.width), height: 1 };
                     f.render_widget(Paragraph::new(label), r);
@@ -682,7 +1170,11 @@ fn draw_overlay(f: &mut ratatui::Frame, area: Rect, app: &App, _mode: &UiMode) {
             }
         }
     }
-    if !matches!(app.style, AnimationStyle::Spectrum) {
+}
+
+fn draw_overlay(f: &mut ratatui::Frame, area: Rect, app: &App, _mode: &UiMode) {
+    if area.width < 12 || area.height < 4 { return; }
+    if !matches!(app.style, AnimationStyle::Spectrum | AnimationStyle::Octave) {
         draw_colorbar(f, area, app);
     }
     // Metadata panel (top-right); clamp to the pane so tiny terminals
@@ -697,16 +1189,65 @@ fn draw_overlay(f: &mut ratatui::Frame, area: Rect, app: &App, _mode: &UiMode) {
     let total_time = (app.total_rows as f32) * (app.settings.hop_size as f32) / (app.settings.sample_rate as f32);
     let meta = vec![
         Line::from(format!("src: {}", app.input_desc)),
-        Line::from(format!("fs: {} Hz | L/H/N: {}/{}/{}", app.settings.sample_rate, app.settings.window_len, app.settings.hop_size, app.settings.fft_size)),
-        Line::from(format!("bins: {} | df: {:.1} Hz", app.settings.fft_size/2, df)),
+        Line::from(format!("fs: {} Hz{} | L/H/N: {}/{}/{}", app.settings.sample_rate, if app.settings.native_rate { " (native)" } else { "" }, app.settings.window_len, app.settings.hop_size, app.settings.fft_size)),
+        Line::from(match app.settings.cqt {
+            Some(cqt) => format!("cqt: {} bins | {}/oct from {} | Q {:.1}", cqt.bins(app.settings.sample_rate), cqt.bins_per_octave, view::format_hz(cqt.fmin as f64), cqt.q()),
+            None => format!("bins: {} | df: {:.1} Hz | win: {}{}", if app.settings.iq { app.settings.fft_size } else { app.settings.fft_size / 2 }, df, app.window_name(), if app.settings.reassign { " | reassigned" } else { "" }),
+        }),
         Line::from(format!("floor/ceil: {:.0}/{:.0} dB | zoom: {:.2}", app.db_floor, app.db_ceiling, app.zoom)),
         Line::from(format!("throughput: {:.1} rows/s | RTF: {:.2}x", rps, rtf)),
-        Line::from(format!("frames: vis {} | rows: {} | total: {:.2}s", app.buffer.len(), app.total_rows, total_time)),
-        Line::from(format!("scale: {:?} | render: {:?}", app.freq_scale, app.render_mode)),
+        Line::from(format!("frames: vis {} | rows: {} | total: {:.2}s", app.buffer().len(), app.total_rows, total_time)),
+        Line::from(match app.note_a4() {
+            Some(a4) => format!("scale: {:?} notes A4={a4:.1}Hz | render: {:?}", app.freq_scale, app.render_mode),
+            None => format!("scale: {:?} | render: {:?}", app.freq_scale, app.render_mode),
+        }),
     ];
     let p = Paragraph::new(meta).block(Block::default().borders(Borders::ALL).title("details"));
     let rect = Rect { x: px, y: py, width: panel_w, height: panel_h };
     f.render_widget(p, rect);
+    if app.show_distortion {
+        draw_distortion(f, area, app);
+    }
+}
+
+/// "1.005%", "0.0123%": a ratio as a percentage to three significant figures
+fn pct_str(pct: f32) -> String {
+    let decimals = (2.0 - pct.max(1e-9).log10().floor()).clamp(0.0, 7.0) as usize;
+    format!("{pct:.decimals$}%")
+}
+
+/// Distortion readouts of the displayed frame, under the details panel
+/// (and left of the colorbar when there is one).
+fn draw_distortion(f: &mut ratatui::Frame, area: Rect, app: &App) {
+    let lines: Vec<Line> = match app.distortion(0) {
+        Some(d) => {
+            let mut lines = vec![
+                Line::from(format!("f0 {} {:+.1} {}", view::format_hz(d.fundamental_hz), d.fundamental_db, app.unit_label())),
+                Line::from(format!("THD   {:7.1} dB  {}", d.thd_db, pct_str(d.thd_pct))),
+                Line::from(format!("THD+N {:7.1} dB  {}", d.thdn_db, pct_str(d.thdn_pct))),
+                Line::from(format!("SINAD {:.1} dB | ENOB {:.2} bits", d.sinad_db, d.enob_bits)),
+                Line::from(format!("SNR {:.1} dB | SFDR {:.1} dBc", d.snr_db, d.sfdr_dbc)),
+            ];
+            for chunk in d.harmonics.chunks(3) {
+                let hs: Vec<String> = chunk.iter().map(|h| format!("H{} {:.1}", h.order, h.dbc)).collect();
+                lines.push(Line::from(format!("dBc {}", hs.join("  "))));
+            }
+            lines
+        }
+        None if app.settings.weighting != Weighting::Z => vec![Line::from("needs --weighting z")],
+        None => vec![Line::from("no tone to measure")],
+    };
+    let title = match app.channel_labels.first() {
+        Some(name) if app.buffers.len() > 1 => format!("distortion ({name})"),
+        _ => "distortion".to_string(),
+    };
+    let colorbar = if matches!(app.style, AnimationStyle::Spectrum | AnimationStyle::Octave) { 0 } else { 8 };
+    let w = 40u16.min(area.width.saturating_sub(colorbar));
+    let top = 8u16;
+    let h = (lines.len() as u16 + 2).min(area.height.saturating_sub(top));
+    if w < 20 || h < 3 { return; }
+    let rect = Rect { x: area.x + area.width - colorbar - w, y: area.y + top, width: w, height: h };
+    f.render_widget(Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(title)), rect);
 }
 
 fn draw_colorbar(f: &mut ratatui::Frame, area: Rect, app: &App) {
@@ -718,8 +1259,10 @@ fn draw_colorbar(f: &mut ratatui::Frame, area: Rect, app: &App) {
     let h = bottom - top;
     let bar_x = area.x + area.width.saturating_sub(2);
 
-    let title = Paragraph::new("dB");
-    f.render_widget(title, Rect { x: bar_x, y: top - 1, width: 2, height: 1 });
+    // Unit label above the bar, right-aligned so long units stay on screen
+    let unit = app.unit_label();
+    let uw = (unit.chars().count() as u16).min(area.width);
+    f.render_widget(Paragraph::new(unit.as_str()), Rect { x: area.x + area.width - uw, y: top - 1, width: uw, height: 1 });
     for i in 0..h {
         let t = 1.0 - (i as f32) / ((h.max(2) - 1) as f32);
         let color = app.palette.color_at(t);
@@ -742,16 +1285,254 @@ fn draw_colorbar(f: &mut ratatui::Frame, area: Rect, app: &App) {
 // help overlay
 fn draw_help(f: &mut ratatui::Frame, area: Rect) {
     let lines = vec![
-        Line::from("Usage: sgram-tui [mic|wav|render|FILE] [FILE] [flags]"),
+        Line::from("Usage: sgram-tui [mic|wav|render|psd|measure|FILE] [FILE] [flags]"),
         Line::from("Examples: sgram-tui song.mp3  |  sgram-tui mic  |  sgram-tui render song.wav"),
-        Line::from("Keys: q/Esc quit, p pause, a style (waterfall/horizontal/spectrum), b bins, +/- zoom, [[/]] floor, c/C palette,"),
-        Line::from("      r reset, f fullscreen, o overview, d details, s/S png, w/W csv, h help. Hover mouse for freq/dB readout."),
+        Line::from("Keys: q/Esc quit, p pause, a style (waterfall/horizontal/spectrum/octave), g octave bands, b bins,"),
+        Line::from("      [[/]] floor, c/C palette, r reset, f fullscreen, o overview, d details, s/S png, w/W csv, h help."),
+        Line::from("Spectrum traces: t cycles off/avg/exp/max/min/avg+max+min (--traces, --avg N, --tau), T resets them."),
+        Line::from("Distortion: e toggles THD, THD+N, SINAD, ENOB, SNR, SFDR of the shown frame (--fundamental, --harmonics)."),
+        Line::from("Pitch: v toggles the f0 contour in the waterfall and horizontal views (start with --pitch, --pitch-range)."),
+        Line::from("Notes: --axis notes labels frequencies as notes (--a4 432), adds cents on hover and a spectrum-view tuner."),
+        Line::from("Hover mouse for freq/dB readout (band Leq/Lmax in the octave view)."),
+        Line::from("Files: ,/. seek -/+ --seek-step s, </> speed 0.25x-8x, l loop (start, end, off), 0 restart."),
+        Line::from("Band: +/- or ctrl+wheel zoom, Left/Right or shift+wheel pan (shift: a whole band), z full range, --fmin/--fmax."),
+        Line::from("Markers: click or m place, k peak search, n next peak, M delta vs M1, Tab select, x clear (up to 4)."),
+        Line::from("Scrollback (pauses): Up/wheel up older, Down/wheel down newer, PgUp/PgDn page, Home oldest, End newest."),
     ];
     let p = Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title("Help"));
     let w = area.width.min(112);
-    let h = 6u16;
+    let h = 15u16;
     if area.width < 10 || area.height < h { return; }
     let x = area.x + (area.width - w) / 2;
     let y = area.y + (area.height - h) / 2;
     f.render_widget(p, Rect { x, y, width: w, height: h });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use ratatui::backend::TestBackend;
+
+    #[test]
+    fn generator_draws_spectrum_peak_in_tui() {
+        let cli = crate::Cli::parse_from(["sgram-tui", "gen:sine:6000:len=0.5s", "--style", "spectrum", "--detailed"]);
+        let settings = crate::build_settings(&cli, None);
+        let mut app = App::new("gen:sine:6000:len=0.5s".to_string(), settings, true, None).unwrap();
+        while let Ok(rows) = app.spectrogram_rx.recv() {
+            app.push_rows(rows);
+        }
+        app.sync_sample_rate();
+        let mut terminal = Terminal::new(TestBackend::new(160, 40)).unwrap();
+        terminal.draw(|f| draw(f, &mut app, &UiMode::Normal)).unwrap();
+        let screen: String = terminal.backend().buffer().content().iter().map(|c| c.symbol()).collect();
+        assert!(screen.contains("peak 6.0000kHz"), "peak annotation missing");
+        assert!(screen.contains("src: generator: gen:sine:6000"), "source missing from status");
+    }
+
+    #[test]
+    fn octave_view_reads_weighted_band_levels() {
+        // -10 dBFS at 1 kHz (0 dB under A) and 100 Hz (-19.1 dB under A)
+        let src = "gen:multitone:100,1000:level=-10:len=1s";
+        let cli = crate::Cli::parse_from(["sgram-tui", src, "--style", "octave", "--weighting", "a", "--fft", "4096"]);
+        let mut app = App::new(src.to_string(), crate::build_settings(&cli, None), true, None).unwrap();
+        while let Ok(rows) = app.spectrogram_rx.recv() {
+            app.push_rows(rows);
+        }
+        app.sync_sample_rate();
+        let mut terminal = Terminal::new(TestBackend::new(160, 40)).unwrap();
+        let screen = |terminal: &mut Terminal<TestBackend>, app: &mut App| -> String {
+            terminal.draw(|f| draw(f, app, &UiMode::Normal)).unwrap();
+            terminal.backend().buffer().content().iter().map(|c| c.symbol()).collect()
+        };
+        let text = screen(&mut terminal, &mut app);
+        assert!(text.contains("1/3 oct |") && text.contains("dBFS(A)"), "broadband readout missing");
+        for label in ["125", "1k", "10k"] {
+            assert!(text.contains(label), "band label {label} missing");
+        }
+        let bands = app.octave_bands();
+        let levels = app.band_levels(&app.buffer()[0], &bands);
+        let level_at = |hz: f32| levels[bands.iter().position(|b| (b.center - hz).abs() < 1.0).unwrap()];
+        assert!((level_at(1000.0) + 10.0).abs() < 0.2, "1 kHz band {:.2}", level_at(1000.0));
+        assert!((level_at(100.0) + 29.1).abs() < 0.3, "100 Hz band {:.2}", level_at(100.0));
+
+        // Hovering the 1 kHz band shows its level, Leq and Lmax
+        let pane = app.panes[0];
+        let (x0, bw) = octave_columns(pane.width as usize, bands.len());
+        let i = bands.iter().position(|b| (b.center - 1000.0).abs() < 1.0).unwrap();
+        app.hover = Some((pane.x + (x0 + i * bw) as u16, pane.y + 5));
+        app.hover_at = Instant::now();
+        let text = screen(&mut terminal, &mut app);
+        assert!(text.contains("1kHz 1/3 oct | L -10.0 | Leq -10.0 | Lmax -10.0 dBFS(A)"), "hover readout missing");
+
+        let mut mode = UiMode::Normal;
+        handle_key(&mut app, KeyEvent::new(KeyCode::Char('g'), crossterm::event::KeyModifiers::NONE), &mut mode).unwrap();
+        assert_eq!(app.octave_fraction, 6);
+    }
+
+    #[test]
+    fn distortion_panel_tracks_the_fundamental() {
+        // 2 kHz is louder, but --fundamental picks 1 kHz: 2 kHz reads as H2
+        let src = "gen:multitone:1000,2000:level=-20:len=0.5s";
+        let cli = crate::Cli::parse_from(["sgram-tui", src, "--style", "spectrum", "--fundamental", "1k", "--window", "blackman-harris"]);
+        let mut app = App::new(src.to_string(), crate::build_settings(&cli, None), true, None).unwrap();
+        while let Ok(rows) = app.spectrogram_rx.recv() {
+            app.push_rows(rows);
+        }
+        assert!(app.show_distortion && app.detailed);
+        let mut terminal = Terminal::new(TestBackend::new(160, 40)).unwrap();
+        let screen = |terminal: &mut Terminal<TestBackend>, app: &mut App| -> String {
+            terminal.draw(|f| draw(f, app, &UiMode::Normal)).unwrap();
+            terminal.backend().buffer().content().iter().map(|c| c.symbol()).collect()
+        };
+        let text = screen(&mut terminal, &mut app);
+        assert!(text.contains("distortion"), "panel missing");
+        assert!(text.contains("f0 1.000") && text.contains("kHz -20.0 dBFS"), "fundamental missing");
+        assert!(text.contains("dBc H2 0.0  H3 "), "harmonics missing");
+        let mut mode = UiMode::Normal;
+        handle_key(&mut app, KeyEvent::new(KeyCode::Char('e'), crossterm::event::KeyModifiers::NONE), &mut mode).unwrap();
+        assert!(!screen(&mut terminal, &mut app).contains("THD+N"));
+
+        // Weighted rows give each harmonic its own gain: no readout
+        let cli = crate::Cli::parse_from(["sgram-tui", src, "--style", "spectrum", "--fundamental", "1k", "--weighting", "a"]);
+        let mut app = App::new(src.to_string(), crate::build_settings(&cli, None), true, None).unwrap();
+        while let Ok(rows) = app.spectrogram_rx.recv() {
+            app.push_rows(rows);
+        }
+        assert!(app.distortion(0).is_none());
+        let text = screen(&mut terminal, &mut app);
+        assert!(text.contains("needs --weighting z") && !text.contains("THD+N"));
+    }
+
+    #[test]
+    fn transport_seek_restarts_history_at_file_time() {
+        let p = std::env::temp_dir().join(format!("sgram_tui_ui_seek_{}.wav", std::process::id()));
+        let spec = hound::WavSpec { channels: 1, sample_rate: 48_000, bits_per_sample: 16, sample_format: hound::SampleFormat::Int };
+        let mut w = hound::WavWriter::create(&p, spec).unwrap();
+        for _ in 0..48_000 * 3 {
+            w.write_sample(0i16).unwrap();
+        }
+        w.finalize().unwrap();
+        let path = p.display().to_string();
+        let cli = crate::Cli::parse_from(["sgram-tui", &path, "--seek-step", "2"]);
+        let mut app = App::new(path, crate::build_settings(&cli, None), true, None).unwrap();
+        let recv = |app: &App| app.spectrogram_rx.recv_timeout(Duration::from_secs(5)).expect("rows");
+        // The whole file decodes, then the decoder idles at the end
+        while let Ok(rows) = app.spectrogram_rx.recv_timeout(Duration::from_millis(300)) {
+            app.push_rows(rows);
+        }
+        app.seek_by(-app.settings.seek_step);
+        loop {
+            let rows = recv(&app);
+            let marker = rows.is_empty();
+            app.push_rows(rows);
+            if marker {
+                break;
+            }
+        }
+        assert!(app.buffer().is_empty());
+        assert_eq!(app.time_origin, Some(1.0));
+        let rows = recv(&app);
+        app.push_rows(rows);
+        assert_eq!(app.time_label(0.0), "1.00s");
+        let _ = std::fs::remove_file(p);
+    }
+
+    #[test]
+    fn scrollback_pans_history_and_labels_stream_time() {
+        let src = "gen:sine:1000:len=3s";
+        let cli = crate::Cli::parse_from(["sgram-tui", src, "--render", "cell", "--history", "1000"]);
+        let mut app = App::new(src.to_string(), crate::build_settings(&cli, None), true, None).unwrap();
+        while let Ok(rows) = app.spectrogram_rx.recv() {
+            app.push_rows(rows);
+        }
+        app.sync_sample_rate();
+        let mut terminal = Terminal::new(TestBackend::new(160, 40)).unwrap();
+        let mut mode = UiMode::Normal;
+        let screen = |terminal: &mut Terminal<TestBackend>, app: &mut App| -> String {
+            terminal.draw(|f| draw(f, app, &UiMode::Normal)).unwrap();
+            terminal.backend().buffer().content().iter().map(|c| c.symbol()).collect()
+        };
+        screen(&mut terminal, &mut app);
+        assert!(app.view_rows > 1 && app.max_scroll() > app.view_rows);
+        let key = |code| KeyEvent::new(code, crossterm::event::KeyModifiers::NONE);
+
+        // Paging back pauses and moves the visible window one pane height
+        handle_key(&mut app, key(KeyCode::PageUp), &mut mode).unwrap();
+        assert!(app.paused);
+        assert_eq!(app.scroll, app.view_rows);
+        let back = app.scroll as f64 * app.row_secs();
+        assert!(screen(&mut terminal, &mut app).contains(&format!("[scrollback -{back:.2}s]")));
+
+        // Hovering a row reports stream time since the start and its age
+        app.hover = Some((10, 1));
+        app.hover_at = Instant::now();
+        let newest = (app.total_rows - 1) as f64 * app.row_secs();
+        let age = (app.scroll as f64 + 0.5) * app.row_secs();
+        let expect = format!("t {} (-{age:.2}s)", view::format_clock(newest - age));
+        assert!(screen(&mut terminal, &mut app).contains(&expect), "missing {expect}");
+
+        handle_key(&mut app, key(KeyCode::Home), &mut mode).unwrap();
+        assert_eq!(app.scroll, app.max_scroll());
+        handle_key(&mut app, key(KeyCode::Down), &mut mode).unwrap();
+        assert_eq!(app.scroll, app.max_scroll() - 1);
+        handle_key(&mut app, key(KeyCode::End), &mut mode).unwrap();
+        assert_eq!(app.scroll, 0);
+        // Resuming always returns to the live edge
+        handle_key(&mut app, key(KeyCode::Up), &mut mode).unwrap();
+        handle_key(&mut app, key(KeyCode::Char('p')), &mut mode).unwrap();
+        assert!(!app.paused);
+        assert_eq!(app.scroll, 0);
+    }
+
+    #[test]
+    fn markers_snap_to_peaks_and_report_deltas() {
+        // Tones on bin centers (N=1024 at 48 kHz): bins 20 and 64, -6 dBFS each
+        let src = "gen:multitone:937.5,3000:len=0.5s";
+        let cli = crate::Cli::parse_from(["sgram-tui", src, "--render", "cell"]);
+        let mut app = App::new(src.to_string(), crate::build_settings(&cli, None), true, None).unwrap();
+        while let Ok(rows) = app.spectrogram_rx.recv() {
+            app.push_rows(rows);
+        }
+        app.sync_sample_rate();
+        let mut mode = UiMode::Normal;
+        let key = |code| KeyEvent::new(code, crossterm::event::KeyModifiers::NONE);
+        let press = |app: &mut App, mode: &mut UiMode, c| handle_key(app, key(KeyCode::Char(c)), mode).unwrap();
+
+        // Peak search places a live M1 on one tone, next peak hops to the other
+        press(&mut app, &mut mode, 'k');
+        let first = app.markers[0].freq;
+        assert!(first == 937.5 || first == 3000.0, "M1 at {first}");
+        press(&mut app, &mut mode, 'm');
+        press(&mut app, &mut mode, 'k');
+        press(&mut app, &mut mode, 'n');
+        let other = if first == 937.5 { 3000.0 } else { 937.5 };
+        assert_eq!(app.markers[1].freq, other);
+        let readouts = app.marker_readouts();
+        assert!(readouts[0].ends_with("live -6.0dBFS"), "{}", readouts[0]);
+
+        press(&mut app, &mut mode, 'M');
+        let delta = &app.marker_readouts()[1];
+        let df = view::format_hz((other - first).abs() as f64);
+        assert!(delta.starts_with("Δ2 ") && delta.contains(&df) && delta.contains("+0.00s"), "{delta}");
+
+        // Markers and their readouts show in the waterfall and in a PNG
+        let mut terminal = Terminal::new(TestBackend::new(160, 40)).unwrap();
+        terminal.draw(|f| draw(f, &mut app, &UiMode::Normal)).unwrap();
+        let screen: String = terminal.backend().buffer().content().iter().map(|c| c.symbol()).collect();
+        assert!(screen.contains(&app.marker_readouts()[0]));
+        // A click pins a marker to the row under the mouse
+        let pane = app.panes[0];
+        let (freq, row) = point_at(&app, pane.x + 10, pane.y + 3).unwrap();
+        assert_eq!(row, Some(app.total_rows - 1 - 3));
+        app.place_marker(freq, row);
+        assert_eq!(marker_cell(&app, pane, &app.markers[2]), Some((pane.x + 10, pane.y + 3)));
+        let png = std::env::temp_dir().join(format!("sgram_tui_markers_{}.png", std::process::id()));
+        app.save_png(png.clone(), 640, 480).unwrap();
+        assert!(std::fs::metadata(&png).unwrap().len() > 0);
+        let _ = std::fs::remove_file(png);
+
+        press(&mut app, &mut mode, 'x');
+        assert!(app.markers.is_empty());
+    }
+}
@@ -2,29 +2,72 @@
 //! renderer (ui.rs) and the PNG exporter (export.rs).
 //!
 //! Rows in the history buffer are full resolution: `bins` values spanning
-//! 0..fs/2. Zoom narrows the *displayed* frequency range only. Each display
+//! 0..fs/2, or -fs/2..fs/2 for complex (I/Q) input with the DC bin in the
+//! middle. Zoom narrows the *displayed* frequency range only. Each display
 //! cell (terminal cell or image pixel) covers a frequency interval; we
 //! max-pool over every bin in that interval so narrowband peaks are never
 //! skipped when many bins map to one cell.
 
 use crate::app::{BinsMode, FreqScale};
+use crate::cqt::CqtSpec;
+use crate::dsp::Scalloping;
 use std::collections::VecDeque;
 
 #[derive(Copy, Clone, Debug)]
 pub struct FreqMap {
     pub sample_rate: u32,
+    /// Full range divided by the displayed span
     pub zoom: f32,
+    /// Lower edge of the displayed band (center-relative Hz), clamped so the
+    /// band stays inside the full range. `None` anchors the band at 0 Hz,
+    /// or centers it on DC for two-sided rows.
+    pub fmin: Option<f32>,
     pub scale: FreqScale,
+    /// Rows hold all N bins of a complex FFT, -fs/2..fs/2 centered on DC.
+    /// Zoom then narrows symmetrically around DC and the scale is linear.
+    pub two_sided: bool,
+    /// Added to every *displayed* frequency (e.g. an SDR's tuned frequency);
+    /// bin mapping is unaffected.
+    pub center_freq: f64,
+    /// Rows hold constant-Q bins, log-spaced from `fmin`, instead of FFT bins
+    pub cqt: Option<CqtSpec>,
 }
 
 impl FreqMap {
-    /// Displayed frequency at fraction `t` in 0..1 (0 = bottom of range).
+    /// Every frequency a row holds (center-relative): 0..fs/2, or
+    /// -fs/2..fs/2 for two-sided rows.
+    pub fn full_range(&self) -> (f32, f32) {
+        let nyq = self.sample_rate as f32 / 2.0;
+        if let Some(cqt) = self.cqt {
+            // Bin k spans f_k..f_k+1 on the axis, as FFT bin i spans i..i+1
+            return (cqt.fmin, cqt.freq(cqt.bins(self.sample_rate) as f32));
+        }
+        if self.two_sided { (-nyq, nyq) } else { (0.0, nyq) }
+    }
+
+    /// Displayed band [lo, hi] (center-relative), before the log/mel scales
+    /// raise a lower edge under 20 Hz.
+    pub fn band(&self) -> (f32, f32) {
+        let (full_lo, full_hi) = self.full_range();
+        let span = (full_hi - full_lo) / self.zoom.max(1.0);
+        let lo = match self.fmin {
+            Some(f) => f.clamp(full_lo, full_hi - span),
+            None if self.two_sided => -span / 2.0,
+            None => full_lo,
+        };
+        (lo, lo + span)
+    }
+
+    /// Displayed frequency at fraction `t` in 0..1 (0 = bottom of range),
+    /// relative to the center frequency.
     pub fn frac_to_freq(&self, t: f32) -> f32 {
-        let fs = self.sample_rate as f32;
-        let fmax = fs / 2.0 / self.zoom.max(1.0);
-        let fmin = match self.scale { FreqScale::Linear => 0.0, _ => 20.0 };
+        let (lo, fmax) = self.band();
+        if self.two_sided {
+            return lo + t * (fmax - lo);
+        }
+        let fmin = match self.scale { FreqScale::Linear => lo, _ => lo.max(20.0) };
         match self.scale {
-            FreqScale::Linear => t * fmax,
+            FreqScale::Linear => fmin + t * (fmax - fmin),
             FreqScale::Log => {
                 let a = (fmax / fmin).max(1.01);
                 fmin * a.powf(t)
@@ -39,18 +82,232 @@ impl FreqMap {
         }
     }
 
+    /// Fraction 0..1 along the displayed axis for (center-relative) frequency
+    /// `f`, the inverse of `frac_to_freq`; outside the band it leaves 0..1.
+    pub fn freq_to_frac(&self, f: f32) -> f32 {
+        let (lo, hi) = self.band();
+        if self.two_sided || matches!(self.scale, FreqScale::Linear) {
+            return (f - lo) / (hi - lo).max(f32::EPSILON);
+        }
+        let lo = lo.max(20.0);
+        match self.scale {
+            FreqScale::Log => (f.max(f32::MIN_POSITIVE) / lo).ln() / (hi / lo).max(1.01).ln(),
+            _ => {
+                let mel = |hz: f32| 2595.0 * (1.0 + hz / 700.0).log10();
+                (mel(f) - mel(lo)) / (mel(hi) - mel(lo)).max(f32::EPSILON)
+            }
+        }
+    }
+
     /// Full-resolution bin range [lo, hi) covered by cell `i` of `n` along the
     /// displayed frequency axis. Never empty; clamped to `bins`.
     pub fn cell_bin_range(&self, i: usize, n: usize, bins: usize) -> (usize, usize) {
         let n = n.max(1);
         let bins = bins.max(1);
-        let hz_per_bin = (self.sample_rate as f32 / 2.0) / (bins as f32);
         let f0 = self.frac_to_freq(i as f32 / n as f32);
         let f1 = self.frac_to_freq((i as f32 + 1.0) / n as f32);
-        let lo = ((f0 / hz_per_bin).floor() as usize).min(bins - 1);
-        let hi = ((f1 / hz_per_bin).ceil() as usize).clamp(lo + 1, bins);
+        // Edges that land on a bin boundary (one cell per constant-Q bin)
+        // must not pick up a neighbour through float round-off
+        const SLACK: f32 = 1e-3;
+        let lo = ((self.freq_to_bin(f0, bins) + SLACK).floor().max(0.0) as usize).min(bins - 1);
+        let hi = ((self.freq_to_bin(f1, bins) - SLACK).ceil().max(0.0) as usize).clamp(lo + 1, bins);
+        (lo, hi)
+    }
+
+    /// Full-resolution bin range [lo, hi) inside the displayed (zoomed) band.
+    pub fn visible_bin_range(&self, bins: usize) -> (usize, usize) {
+        let bins = bins.max(1);
+        let lo = (self.freq_to_bin(self.frac_to_freq(0.0), bins).floor().max(0.0) as usize).min(bins - 1);
+        let hi = (self.freq_to_bin(self.frac_to_freq(1.0), bins).ceil().max(0.0) as usize).clamp(lo + 1, bins);
         (lo, hi)
     }
+
+    /// Frequency spacing between adjacent bins of a `bins`-wide row.
+    pub fn hz_per_bin(&self, bins: usize) -> f32 {
+        let span = if self.two_sided { self.sample_rate as f32 } else { self.sample_rate as f32 / 2.0 };
+        span / (bins.max(1) as f32)
+    }
+
+    /// Fractional bin position of (center-relative) frequency `f`.
+    pub fn freq_to_bin(&self, f: f32, bins: usize) -> f32 {
+        if let Some(cqt) = self.cqt {
+            return cqt.bin(f);
+        }
+        let offset = if self.two_sided { self.sample_rate as f32 / 2.0 } else { 0.0 };
+        (f + offset) / self.hz_per_bin(bins)
+    }
+
+    /// Center-relative frequency of bin `i`.
+    pub fn bin_to_freq(&self, i: f32, bins: usize) -> f32 {
+        if let Some(cqt) = self.cqt {
+            return cqt.freq(i);
+        }
+        let offset = if self.two_sided { self.sample_rate as f32 / 2.0 } else { 0.0 };
+        i * self.hz_per_bin(bins) - offset
+    }
+
+    /// Absolute frequency for display: adds the center frequency in f64 so
+    /// RF offsets (hundreds of MHz) keep Hz precision.
+    pub fn absolute(&self, f: f32) -> f64 {
+        self.center_freq + f as f64
+    }
+}
+
+/// RF-range label (|f| >= 1 MHz) in MHz or GHz to Hz precision with
+/// trailing zeros trimmed. `None` below 1 MHz, where callers keep their own
+/// Hz/kHz formats.
+pub fn format_rf(hz: f64) -> Option<String> {
+    let (div, decimals, unit) = if hz.abs() >= 1e9 {
+        (1e9, 9, "GHz")
+    } else if hz.abs() >= 1e6 {
+        (1e6, 6, "MHz")
+    } else {
+        return None;
+    };
+    let s = format!("{:.*}", decimals, hz / div);
+    let s = s.trim_end_matches('0').trim_end_matches('.');
+    Some(format!("{s}{unit}"))
+}
+
+/// Marker-precision frequency label: "4.0125kHz" to 0.1 Hz below 1 MHz,
+/// Hz-exact RF labels above.
+pub fn format_hz(hz: f64) -> String {
+    if let Some(rf) = format_rf(hz) {
+        rf
+    } else if hz.abs() >= 1000.0 {
+        format!("{:.4}kHz", hz / 1000.0)
+    } else {
+        format!("{hz:.1}Hz")
+    }
+}
+
+/// Source-time label to the centisecond: "42.50s", "12:30.50" or
+/// "1:02:03.00".
+pub fn format_clock(secs: f64) -> String {
+    let cs = (secs.max(0.0) * 100.0).round() as u64;
+    let (h, m, s) = (cs / 360_000, cs / 6_000 % 60, (cs % 6_000) as f64 / 100.0);
+    if h > 0 {
+        format!("{h}:{m:02}:{s:05.2}")
+    } else if m > 0 {
+        format!("{m}:{s:05.2}")
+    } else {
+        format!("{s:.2}s")
+    }
+}
+
+const NOTE_NAMES: [&str; 12] = ["C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B"];
+
+/// An equal-tempered note: MIDI number (69 = A4) and how far a frequency
+/// sits from it in cents.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Note {
+    pub midi: i32,
+    pub cents: f32,
+}
+
+impl Note {
+    /// Nearest note to `hz` with A4 tuned to `a4` Hz; `None` at 0 Hz and
+    /// below, which have no pitch.
+    pub fn nearest(hz: f64, a4: f64) -> Option<Note> {
+        let semis = midi_of(hz, a4).filter(|s| s.is_finite())?;
+        let midi = semis.round();
+        Some(Note { midi: midi as i32, cents: ((semis - midi) * 100.0) as f32 })
+    }
+
+    /// Frequency of MIDI note `midi` with A4 at `a4` Hz.
+    pub fn hz(midi: i32, a4: f64) -> f64 {
+        a4 * 2f64.powf((midi - 69) as f64 / 12.0)
+    }
+
+    /// Scientific pitch name: "A4", "C#5", "B-1".
+    pub fn name(&self) -> String {
+        format!("{}{}", NOTE_NAMES[self.midi.rem_euclid(12) as usize], self.midi.div_euclid(12) - 1)
+    }
+}
+
+/// Fractional MIDI number of `hz`.
+fn midi_of(hz: f64, a4: f64) -> Option<f64> {
+    (hz > 0.0 && a4 > 0.0).then(|| 69.0 + 12.0 * (hz / a4).log2())
+}
+
+/// Nearest note and cents offset: "A4 +12c", "C#5 -3c".
+pub fn format_note(hz: f64, a4: f64) -> Option<String> {
+    let n = Note::nearest(hz, a4)?;
+    // + 0.0 turns a rounded -0 into 0
+    Some(format!("{} {:+.0}c", n.name(), n.cents.round() + 0.0))
+}
+
+/// Note-name tick labels for `--axis notes`, at most `ticks + 1` of them:
+/// every C, or every second, third, ... semitone from C when the band is
+/// narrow enough, thinned so no two sit closer than half a tick interval
+/// (a linear axis crowds the low octaves together). A band holding fewer
+/// than two notes keeps evenly spaced ticks labelled with their nearest
+/// note and cents.
+pub fn note_ticks(fmap: &FreqMap, a4: f64, ticks: u32) -> Vec<(f32, String)> {
+    let ticks = ticks.max(1);
+    let (lo, hi) = (fmap.absolute(fmap.frac_to_freq(0.0)), fmap.absolute(fmap.frac_to_freq(1.0)));
+    // C0 (about 16 Hz) is the lowest labelled note
+    let m_lo = midi_of(lo, a4).unwrap_or(f64::NEG_INFINITY).max(12.0).ceil() as i32;
+    let m_hi = midi_of(hi, a4).map(|m| m.floor() as i32).unwrap_or(i32::MIN);
+    let count = |step: i32| (m_lo..=m_hi).filter(|m| m.rem_euclid(step) == 0).count();
+    let step = [1, 2, 3, 4, 6, 12, 24, 36, 48, 60, 72, 84, 96, 108, 120]
+        .into_iter()
+        .find(|&s| count(s) <= ticks as usize + 1)
+        .unwrap_or(120);
+    let min_gap = 0.5 / ticks as f32;
+    let mut out: Vec<(f32, String)> = Vec::new();
+    for m in (m_lo..=m_hi).filter(|m| m.rem_euclid(step) == 0) {
+        let frac = fmap.freq_to_frac((Note::hz(m, a4) - fmap.center_freq) as f32);
+        if !(0.0..=1.0).contains(&frac) || out.last().is_some_and(|&(f, _)| frac - f < min_gap) {
+            continue;
+        }
+        out.push((frac, Note { midi: m, cents: 0.0 }.name()));
+    }
+    if out.len() >= 2 {
+        return out;
+    }
+    (0..=ticks)
+        .map(|i| {
+            let frac = i as f32 / ticks as f32;
+            let hz = fmap.absolute(fmap.frac_to_freq(frac));
+            (frac, format_note(hz, a4).map(|s| s.replace(' ', "")).unwrap_or_default())
+        })
+        .collect()
+}
+
+/// Parse a time like "12:30.5", "1:02:03", "20s", "500ms", "2m" or "7.5"
+/// (seconds) for --start/--duration/--end.
+pub fn parse_time(s: &str) -> Option<f64> {
+    let s = s.trim();
+    let secs = if s.contains(':') {
+        // [h:]m:s, each field scaled by 60 on the way up
+        let mut total = 0.0;
+        for field in s.split(':') {
+            total = total * 60.0 + field.parse::<f64>().ok().filter(|v| *v >= 0.0)?;
+        }
+        total
+    } else if let Some(n) = s.strip_suffix("ms") {
+        n.parse::<f64>().ok()? / 1000.0
+    } else if let Some(n) = s.strip_suffix('s') {
+        n.parse::<f64>().ok()?
+    } else if let Some(n) = s.strip_suffix('m') {
+        n.parse::<f64>().ok()? * 60.0
+    } else if let Some(n) = s.strip_suffix('h') {
+        n.parse::<f64>().ok()? * 3600.0
+    } else {
+        s.parse::<f64>().ok()?
+    };
+    Some(secs).filter(|v| v.is_finite() && *v >= 0.0)
+}
+
+/// Frequency like "1000", "1k", "2.5kHz" or "433.92e6" -> Hz.
+pub fn parse_freq(s: &str) -> Option<f64> {
+    let s = s.trim().trim_end_matches("Hz");
+    let (num, mult) = match s.strip_suffix('k') {
+        Some(n) => (n, 1000.0),
+        None => (s, 1.0),
+    };
+    num.parse::<f64>().ok().filter(|v| v.is_finite()).map(|v| v * mult)
 }
 
 /// Index and value of the loudest bin in row[lo..hi].
@@ -67,6 +324,47 @@ pub fn max_bin_in(row: &[f32], lo: usize, hi: usize) -> (usize, f32) {
     (best_i, best_v)
 }
 
+/// Strongest local peak in row[lo..hi] below the one at bin `from`: the
+/// next peak down, as on a bench analyzer's next-peak key. Equal levels
+/// rank low bins first (as `max_bin_in` picks them), so repeated calls
+/// visit every peak once.
+pub fn next_peak_below(row: &[f32], lo: usize, hi: usize, from: usize) -> Option<(usize, f32)> {
+    let hi = hi.min(row.len());
+    let rank = |a: usize, b: usize| row[a].total_cmp(&row[b]).then(b.cmp(&a));
+    let from = from.min(row.len().checked_sub(1)?);
+    (lo..hi)
+        .filter(|&i| is_local_peak(row, i) && rank(i, from).is_lt())
+        .max_by(|&a, &b| rank(a, b))
+        .map(|i| (i, row[i]))
+}
+
+/// A spectral peak refined between bins: fractional bin index and dB level.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Peak {
+    pub bin: f32,
+    pub db: f32,
+}
+
+/// Sub-bin estimate of the peak at bin `i`. A parabola through the dB
+/// values of the bin and its two neighbours (a Gaussian fit on magnitude)
+/// places the tone between bins, and the window's scalloping loss at that
+/// offset is added back to the level, so an off-bin tone reads its true
+/// frequency and amplitude. Bins that are not an interior local maximum
+/// come back as they are.
+pub fn interpolate_peak(row: &[f32], i: usize, scallop: &Scalloping) -> Peak {
+    let flat = Peak { bin: i as f32, db: row.get(i).copied().unwrap_or(f32::NEG_INFINITY) };
+    if i == 0 || i + 1 >= row.len() || !is_local_peak(row, i) {
+        return flat;
+    }
+    let (a, b, c) = (row[i - 1], row[i], row[i + 1]);
+    let den = a - 2.0 * b + c;
+    if ![a, b, c].iter().all(|v| v.is_finite()) || den >= 0.0 {
+        return flat;
+    }
+    let delta = (0.5 * (a - c) / den).clamp(-0.5, 0.5);
+    Peak { bin: i as f32 + delta, db: b - scallop.gain_db(delta) }
+}
+
 /// True when bin `i` is a local spectral maximum (>= both frequency neighbours).
 pub fn is_local_peak(row: &[f32], i: usize) -> bool {
     if row.is_empty() || i >= row.len() {
@@ -88,8 +386,18 @@ pub fn pool_cell(
     hi: usize,
     mode: BinsMode,
 ) -> f32 {
+    match (cell_argmax(buffer, r0, r1, lo, hi), mode) {
+        (Some((r, bi)), BinsMode::All) => buffer[r][bi],
+        (Some((r, bi)), BinsMode::Peaks) if is_local_peak(&buffer[r], bi) => buffer[r][bi],
+        _ => f32::NEG_INFINITY,
+    }
+}
+
+/// Buffer row and bin of the loudest value in the cell covering rows
+/// [r0, r1) and bins [lo, hi), the one `pool_cell` reports.
+pub fn cell_argmax(buffer: &VecDeque<Vec<f32>>, r0: usize, r1: usize, lo: usize, hi: usize) -> Option<(usize, usize)> {
     let mut best_v = f32::NEG_INFINITY;
-    let mut best: Option<(usize, usize)> = None;
+    let mut best = None;
     for (r, row) in buffer.iter().enumerate().take(r1.min(buffer.len())).skip(r0) {
         let (bi, bv) = max_bin_in(row, lo, hi);
         if bv > best_v {
@@ -97,13 +405,77 @@ pub fn pool_cell(
             best = Some((r, bi));
         }
     }
-    match mode {
-        BinsMode::All => best_v,
-        BinsMode::Peaks => match best {
-            Some((r, bi)) if is_local_peak(&buffer[r], bi) => best_v,
-            _ => f32::NEG_INFINITY,
-        },
-    }
+    best
+}
+
+/// A fractional-octave band: exact (not nominal) center and edges in Hz.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OctaveBand {
+    pub center: f32,
+    pub lo: f32,
+    pub hi: f32,
+}
+
+/// Base-ten octave ratio of IEC 61260-1, 10^(3/10)
+const OCTAVE_RATIO: f64 = 1.995_262_314_968_879_5;
+
+/// IEC 61260-1 bands of 1/`fraction` octave (1, 3 or 6 per octave) lying
+/// inside [lo, hi] Hz, dropping bands narrower than `min_width` Hz (one
+/// analysis bin) at the bottom. Centers are 1 kHz times powers of the
+/// base-ten octave ratio, offset by half a band for even fractions.
+pub fn octave_bands(fraction: u32, lo: f32, hi: f32, min_width: f32) -> Vec<OctaveBand> {
+    let b = fraction.max(1) as i32;
+    let half = OCTAVE_RATIO.powf(0.5 / b as f64);
+    // 1/b-octave index x spans about 1 mHz to 1 GHz
+    (-20 * b..=20 * b)
+        .map(|x| {
+            let exp = if b % 2 == 1 { x as f64 / b as f64 } else { (2 * x + 1) as f64 / (2 * b) as f64 };
+            let center = 1000.0 * OCTAVE_RATIO.powf(exp);
+            OctaveBand { center: center as f32, lo: (center / half) as f32, hi: (center * half) as f32 }
+        })
+        .filter(|band| band.lo >= lo && band.hi <= hi && band.hi - band.lo >= min_width)
+        .collect()
+}
+
+/// Nominal center-frequency label of a band: the R10 series (31.5, 125,
+/// 1k, 12.5k) for octave and third-octave bands, three significant figures
+/// for finer ones.
+pub fn octave_label(fraction: u32, center: f32) -> String {
+    const R10: [f64; 10] = [1.0, 1.25, 1.6, 2.0, 2.5, 3.15, 4.0, 5.0, 6.3, 8.0];
+    let nominal = if fraction <= 3 {
+        let n = (10.0 * (center as f64).log10()).round() as i32;
+        R10[n.rem_euclid(10) as usize] * 10f64.powi(n.div_euclid(10))
+    } else {
+        let mag = 10f64.powi((center as f64).log10().floor() as i32 - 2);
+        (center as f64 / mag).round() * mag
+    };
+    let trim = |v: f64| {
+        let s = format!("{:.2}", v);
+        s.trim_end_matches('0').trim_end_matches('.').to_string()
+    };
+    if nominal >= 1000.0 { format!("{}k", trim(nominal / 1000.0)) } else { trim(nominal) }
+}
+
+/// Level of each band in a dB `row`: the power sum of every bin whose
+/// center lies in [lo, hi), plus `correction_db` (the window's noise
+/// bandwidth or the bin width, see `App::band_correction_db`). Bands with
+/// no bins read NEG_INFINITY.
+pub fn band_levels(row: &[f32], map: &FreqMap, bands: &[OctaveBand], correction_db: f32) -> Vec<f32> {
+    let bins = row.len();
+    let edge = |f: f32| (map.freq_to_bin(f, bins).ceil().max(0.0) as usize).min(bins);
+    bands
+        .iter()
+        .map(|band| {
+            let power: f64 = row[edge(band.lo)..edge(band.hi)].iter().map(|&v| 10f64.powf(v as f64 / 10.0)).sum();
+            if power > 0.0 { (10.0 * power.log10()) as f32 + correction_db } else { f32::NEG_INFINITY }
+        })
+        .collect()
+}
+
+/// Power sum of band levels in dB: the broadband level across the bands.
+pub fn total_level(levels: &[f32]) -> f32 {
+    let power: f64 = levels.iter().map(|&l| 10f64.powf(l as f64 / 10.0)).sum();
+    if power > 0.0 { (10.0 * power.log10()) as f32 } else { f32::NEG_INFINITY }
 }
 
 /// Buffer row range [r0, r1) for display row `y` of `n_rows` when the entire
@@ -120,7 +492,7 @@ mod tests {
     use super::*;
 
     fn fm(zoom: f32) -> FreqMap {
-        FreqMap { sample_rate: 48_000, zoom, scale: FreqScale::Linear }
+        FreqMap { sample_rate: 48_000, zoom, fmin: None, scale: FreqScale::Linear, two_sided: false, center_freq: 0.0, cqt: None }
     }
 
     #[test]
@@ -146,6 +518,97 @@ mod tests {
         assert!(hi <= 512 / 4 + 2, "hi={hi}");
     }
 
+    #[test]
+    fn two_sided_map_centers_dc() {
+        let m = FreqMap { two_sided: true, ..fm(1.0) };
+        let bins = 1024;
+        // DC sits in the middle of the row, the range spans -fs/2..fs/2
+        assert_eq!(m.freq_to_bin(0.0, bins), 512.0);
+        assert_eq!(m.frac_to_freq(0.0), -24_000.0);
+        assert_eq!(m.bin_to_freq(768.0, bins), 12_000.0);
+        let (lo, hi) = m.cell_bin_range(0, 100, bins);
+        assert_eq!(lo, 0);
+        assert!(hi <= 12);
+        // 4x zoom keeps DC centered
+        let z = FreqMap { two_sided: true, ..fm(4.0) };
+        assert_eq!(z.visible_bin_range(bins), (384, 640));
+    }
+
+    #[test]
+    fn explicit_band_maps_only_its_bins() {
+        // 3.8-4.2 kHz out of 0-24 kHz: a 60x zoom starting at fmin
+        let m = FreqMap { fmin: Some(3_800.0), ..fm(60.0) };
+        assert_eq!(m.band(), (3_800.0, 4_200.0));
+        assert_eq!(m.frac_to_freq(0.5), 4_000.0);
+        // 1024 bins at 23.4375 Hz: 3800 Hz is bin 162.1, 4200 Hz is bin 179.2
+        assert_eq!(m.visible_bin_range(1024), (162, 180));
+        let (lo, _) = m.cell_bin_range(0, 40, 1024);
+        let (_, hi) = m.cell_bin_range(39, 40, 1024);
+        assert_eq!((lo, hi), (162, 180));
+        // A band pushed past Nyquist slides back inside the full range
+        let edge = FreqMap { fmin: Some(23_900.0), ..m };
+        assert_eq!(edge.band(), (23_600.0, 24_000.0));
+        // Two-sided rows accept bands on either side of DC
+        let iq = FreqMap { two_sided: true, fmin: Some(-6_000.0), ..fm(4.0) };
+        assert_eq!(iq.band(), (-6_000.0, 6_000.0));
+        let iq = FreqMap { fmin: Some(-24_000.0), ..iq };
+        assert_eq!(iq.visible_bin_range(1024), (0, 256));
+    }
+
+    #[test]
+    fn freq_to_frac_inverts_every_scale() {
+        for scale in [FreqScale::Linear, FreqScale::Log, FreqScale::Mel] {
+            let m = FreqMap { scale, fmin: Some(300.0), ..fm(4.0) };
+            for t in [0.0, 0.25, 0.5, 0.9, 1.0] {
+                let back = m.freq_to_frac(m.frac_to_freq(t));
+                assert!((back - t).abs() < 1e-4, "{scale:?} t={t} -> {back}");
+            }
+        }
+        let iq = FreqMap { two_sided: true, ..fm(2.0) };
+        assert_eq!(iq.freq_to_frac(0.0), 0.5);
+    }
+
+    #[test]
+    fn next_peak_steps_down_through_local_maxima() {
+        let row = [-90.0, -10.0, -90.0, -30.0, -90.0, -20.0, -90.0, -20.0, -90.0];
+        assert_eq!(max_bin_in(&row, 0, row.len()), (1, -10.0));
+        assert_eq!(next_peak_below(&row, 0, row.len(), 1), Some((5, -20.0)));
+        // Equal peaks are each visited once
+        assert_eq!(next_peak_below(&row, 0, row.len(), 5), Some((7, -20.0)));
+        assert_eq!(next_peak_below(&row, 0, row.len(), 7), Some((3, -30.0)));
+        assert_eq!(next_peak_below(&row, 0, row.len(), 3), None);
+    }
+
+    #[test]
+    fn center_freq_only_shifts_labels() {
+        let m = FreqMap { two_sided: true, center_freq: 433.92e6, ..fm(1.0) };
+        assert_eq!(m.absolute(m.frac_to_freq(0.5)), 433.92e6);
+        assert_eq!(m.cell_bin_range(50, 100, 1024), FreqMap { center_freq: 0.0, ..m }.cell_bin_range(50, 100, 1024));
+    }
+
+    #[test]
+    fn rf_labels_trim_to_hz_precision() {
+        assert_eq!(format_rf(433.92e6).as_deref(), Some("433.92MHz"));
+        assert_eq!(format_rf(433_920_125.0).as_deref(), Some("433.920125MHz"));
+        assert_eq!(format_rf(2.4e9).as_deref(), Some("2.4GHz"));
+        assert_eq!(format_rf(-1.5e6).as_deref(), Some("-1.5MHz"));
+        assert_eq!(format_rf(12_000.0), None);
+    }
+
+    #[test]
+    fn interpolation_leaves_edges_and_slopes_alone() {
+        let sc = Scalloping::new(crate::dsp::WindowType::Hann, 64, 64);
+        let row = [-10.0, -3.0, -1.0, -1.0, -3.0, -20.0];
+        // Equal neighbours put the peak exactly halfway, at the -1.42 dB Hann
+        // scalloping point
+        let pk = interpolate_peak(&row, 2, &sc);
+        assert!((pk.bin - 2.5).abs() < 1e-6);
+        assert!((pk.db - (-1.0 + 1.42)).abs() < 0.01, "{}", pk.db);
+        assert_eq!(interpolate_peak(&row, 1, &sc), Peak { bin: 1.0, db: -3.0 });
+        assert_eq!(interpolate_peak(&row, 0, &sc), Peak { bin: 0.0, db: -10.0 });
+        assert_eq!(interpolate_peak(&row, 5, &sc).bin, 5.0);
+    }
+
     #[test]
     fn pooling_finds_narrow_peak() {
         // A single loud bin inside a wide cell range must win the pool
@@ -184,4 +647,100 @@ mod tests {
         }
         assert_eq!(covered, total);
     }
+
+    #[test]
+    fn clock_times_parse_and_format() {
+        assert_eq!(parse_time("12:30.5"), Some(750.5));
+        assert_eq!(parse_time("1:02:03"), Some(3723.0));
+        assert_eq!(parse_time("20s"), Some(20.0));
+        assert_eq!(parse_time("500ms"), Some(0.5));
+        assert_eq!(parse_time("2m"), Some(120.0));
+        assert_eq!(parse_time("7.5"), Some(7.5));
+        assert_eq!(parse_time("-3"), None);
+        assert_eq!(parse_time("1:xx"), None);
+        assert_eq!(format_clock(42.5), "42.50s");
+        assert_eq!(format_clock(750.5), "12:30.50");
+        assert_eq!(format_clock(3723.0), "1:02:03.00");
+        // Rounding carries into the minutes instead of printing 60 seconds
+        assert_eq!(format_clock(59.999), "1:00.00");
+    }
+
+    #[test]
+    fn third_octave_bands_follow_iec_61260() {
+        let bands = octave_bands(3, 20.0, 24_000.0, 0.0);
+        let labels: Vec<String> = bands.iter().map(|b| octave_label(3, b.center)).collect();
+        assert_eq!(labels.first().map(String::as_str), Some("25"));
+        assert_eq!(labels.last().map(String::as_str), Some("20k"));
+        for nominal in ["31.5", "63", "125", "1k", "3.15k", "12.5k"] {
+            assert!(labels.iter().any(|l| l == nominal), "{nominal} missing from {labels:?}");
+        }
+        let k1 = bands.iter().find(|b| (b.center - 1000.0).abs() < 1e-3).unwrap();
+        assert!((k1.lo - 891.25).abs() < 0.01 && (k1.hi - 1122.02).abs() < 0.01, "{k1:?}");
+        // Bands tile the axis; octaves are three thirds wide
+        assert!(bands.windows(2).all(|w| (w[0].hi - w[1].lo).abs() < 1e-2 * w[0].hi));
+        let oct = octave_bands(1, 20.0, 24_000.0, 0.0);
+        assert_eq!(oct.iter().map(|b| octave_label(1, b.center)).collect::<Vec<_>>().join(" "), "31.5 63 125 250 500 1k 2k 4k 8k 16k");
+        // Sixth-octave centers sit between the thirds; too-narrow bands drop out
+        assert_eq!(octave_label(6, octave_bands(6, 850.0, 1200.0, 0.0)[0].center), "944");
+        assert!(octave_bands(3, 20.0, 24_000.0, 46.875).iter().all(|b| b.hi - b.lo >= 46.875));
+    }
+
+    #[test]
+    fn band_level_of_a_tone_reads_its_level() {
+        use crate::dsp::{enbw_bins, window_coeffs, SpectrogramBuilder, WindowType};
+        let (fs, n) = (48_000u32, 4096usize);
+        // -10 dBFS at 1 kHz, off the bin grid: its main lobe lands in one band
+        let x: Vec<f32> = (0..n).map(|i| 0.316_227_8 * (2.0 * std::f32::consts::PI * 1003.0 * i as f32 / fs as f32).sin()).collect();
+        for w in [WindowType::Hann, WindowType::BlackmanHarris, WindowType::FlatTop] {
+            let row = &SpectrogramBuilder::new(n, n, n).window(w).build().process_samples(&x)[0];
+            let bands = octave_bands(3, 20.0, 24_000.0, fs as f32 / n as f32);
+            let levels = band_levels(row, &fm(1.0), &bands, -10.0 * enbw_bins(&[window_coeffs(w, n)], n).log10());
+            let k1 = bands.iter().position(|b| (b.center - 1000.0).abs() < 1e-3).unwrap();
+            assert!((levels[k1] + 10.0).abs() < 0.05, "{w:?}: {:.3}", levels[k1]);
+        }
+    }
+
+    #[test]
+    fn notes_name_and_cents() {
+        let a4 = Note::nearest(440.0, 440.0).unwrap();
+        assert_eq!((a4.name(), a4.cents), ("A4".to_string(), 0.0));
+        assert_eq!(Note::nearest(261.63, 440.0).unwrap().name(), "C4");
+        assert_eq!(Note::nearest(8.18, 440.0).unwrap().name(), "C-1");
+        // A quarter tone sharp of A4 at A4 = 432
+        assert_eq!(format_note(432.0 * 2f64.powf(0.25 / 12.0), 432.0).unwrap(), "A4 +25c");
+        assert_eq!(format_note(277.18 * 0.999, 440.0).unwrap(), "C#4 -2c");
+        assert_eq!(Note::nearest(0.0, 440.0), None);
+    }
+
+    #[test]
+    fn note_ticks_land_on_notes_and_fall_back_when_narrow() {
+        let mut m = FreqMap { scale: FreqScale::Log, ..fm(1.0) };
+        let ticks = note_ticks(&m, 440.0, 6);
+        assert!(ticks.len() >= 3 && ticks.len() <= 7, "{ticks:?}");
+        assert!(ticks.iter().all(|(_, l)| l.starts_with('C')), "{ticks:?}");
+        for (frac, label) in &ticks {
+            let hz = m.frac_to_freq(*frac) as f64;
+            assert_eq!(&Note::nearest(hz, 440.0).unwrap().name(), label);
+        }
+        // 438-442 Hz holds only A4: evenly spaced ticks with cents
+        m.scale = FreqScale::Linear;
+        m.zoom = 6000.0;
+        m.fmin = Some(438.0);
+        let ticks = note_ticks(&m, 440.0, 4);
+        assert_eq!(ticks.len(), 5);
+        assert_eq!(ticks[2].1, "A4+0c");
+    }
+
+    #[test]
+    fn cqt_rows_map_one_for_one_on_a_log_axis() {
+        let cqt = CqtSpec { fmin: 55.0, bins_per_octave: 12 };
+        let m = FreqMap { scale: FreqScale::Log, cqt: Some(cqt), ..fm(1.0) };
+        let bins = cqt.bins(48_000);
+        assert_eq!(m.freq_to_bin(110.0, bins), 12.0);
+        assert!((m.bin_to_freq(24.0, bins) - 220.0).abs() < 1e-3);
+        // As many cells as bins: each cell covers exactly its own bin
+        for i in 0..bins {
+            assert_eq!(m.cell_bin_range(i, bins, bins), (i, i + 1), "cell {i}");
+        }
+    }
 }